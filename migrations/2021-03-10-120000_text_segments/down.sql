@@ -0,0 +1,6 @@
+DROP TRIGGER text_segments_ai;
+DROP TRIGGER text_segments_ad;
+DROP TRIGGER text_segments_au;
+
+DROP TABLE fts_text_segments;
+DROP TABLE text_segments;
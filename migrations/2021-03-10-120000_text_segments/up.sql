@@ -0,0 +1,49 @@
+CREATE TABLE `text_segments` (
+	`id`                  INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`segment_id`          VARCHAR NOT NULL,         --  an4.67:2.1
+	`root_text_uid`       VARCHAR NOT NULL,         --  an/4.67/pli/ms
+	`translated_text_uid` VARCHAR NOT NULL,         --  an/4.67/en/sujato, or empty when there is no translation
+	`segment_order`       INTEGER NOT NULL,         --  position of the segment in the text, from 1
+	`root_text`           TEXT NOT NULL DEFAULT '', --  “Na hi nūna so, bhikkhave, ...
+	`translation`         TEXT NOT NULL DEFAULT '', --  “Mendicants, that monk mustn’t ...
+	`comment_html`        TEXT NOT NULL DEFAULT '', --  </p><p>, or the bilara html template <p>{}
+	UNIQUE (`segment_id`, `root_text_uid`, `translated_text_uid`)
+);
+
+CREATE INDEX `text_segments_segment_id` ON `text_segments` (`segment_id`);
+
+-- === Index tables ===
+
+CREATE VIRTUAL TABLE fts_text_segments USING fts5 (
+  content=text_segments,
+  content_rowid=id,
+  root_text,
+  translation
+);
+
+-- === Triggers to keep content synced ===
+
+CREATE TRIGGER text_segments_ai AFTER INSERT ON text_segments BEGIN
+  INSERT INTO fts_text_segments
+    (rowid, root_text, translation)
+    VALUES
+    (new.id, new.root_text, new.translation);
+END;
+
+CREATE TRIGGER text_segments_ad AFTER DELETE ON text_segments BEGIN
+  INSERT INTO fts_text_segments
+    (fts_text_segments, rowid, root_text, translation)
+    VALUES
+    ('delete', old.id, old.root_text, old.translation);
+END;
+
+CREATE TRIGGER text_segments_au AFTER UPDATE ON text_segments BEGIN
+  INSERT INTO fts_text_segments
+    (fts_text_segments, rowid, root_text, translation)
+    VALUES
+    ('delete', old.id, old.root_text, old.translation);
+  INSERT INTO fts_text_segments
+    (rowid, root_text, translation)
+    VALUES
+    (new.id, new.root_text, new.translation);
+END;
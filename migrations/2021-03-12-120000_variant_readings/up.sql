@@ -0,0 +1,12 @@
+CREATE TABLE `variant_readings` (
+	`id`            INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`segment_id`    VARCHAR NOT NULL,            --  an4.67:2.1
+	`root_text_uid` VARCHAR NOT NULL,            --  an/4.67/pli/ms
+	`lemma`         VARCHAR NOT NULL,            --  Na hi nūna
+	`reading`       VARCHAR NOT NULL,            --  nahanūna
+	`editions`      VARCHAR NOT NULL DEFAULT ''  --  bj, s1, s2, km, pts1
+);
+
+CREATE INDEX `variant_readings_segment_id` ON `variant_readings` (`segment_id`);
+CREATE INDEX `variant_readings_lemma` ON `variant_readings` (`lemma`);
+CREATE INDEX `variant_readings_reading` ON `variant_readings` (`reading`);
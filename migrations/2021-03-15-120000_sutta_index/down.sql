@@ -0,0 +1,94 @@
+-- SQLite can't drop a column with a foreign key, the tables are created again without
+-- sutta_index_id. The FTS triggers are dropped with the tables and are created again.
+
+CREATE TABLE `root_texts_new` (
+	`id`               INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`author_id`        INTEGER REFERENCES `authors` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+	`uid`              VARCHAR NOT NULL UNIQUE,
+	`acronym`          VARCHAR,
+	`volpage`          VARCHAR,
+	`title`            VARCHAR,
+	`content_language` VARCHAR,
+	`content_plain`    TEXT,
+	`content_html`     TEXT
+);
+
+INSERT INTO `root_texts_new` (`id`, `author_id`, `uid`, `acronym`, `volpage`, `title`, `content_language`, `content_plain`, `content_html`)
+SELECT `id`, `author_id`, `uid`, `acronym`, `volpage`, `title`, `content_language`, `content_plain`, `content_html` FROM `root_texts`;
+
+DROP TABLE `root_texts`;
+
+ALTER TABLE `root_texts_new` RENAME TO `root_texts`;
+
+CREATE TABLE `translated_texts_new` (
+	`id`               INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`author_id`        INTEGER REFERENCES `authors` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+	`uid`              VARCHAR NOT NULL UNIQUE,
+	`acronym`          VARCHAR,
+	`volpage`          VARCHAR,
+	`title`            VARCHAR,
+	`root_title`       VARCHAR,
+	`content_language` VARCHAR,
+	`content_plain`    TEXT,
+	`content_html`     TEXT
+);
+
+INSERT INTO `translated_texts_new` (`id`, `author_id`, `uid`, `acronym`, `volpage`, `title`, `root_title`, `content_language`, `content_plain`, `content_html`)
+SELECT `id`, `author_id`, `uid`, `acronym`, `volpage`, `title`, `root_title`, `content_language`, `content_plain`, `content_html` FROM `translated_texts`;
+
+DROP TABLE `translated_texts`;
+
+ALTER TABLE `translated_texts_new` RENAME TO `translated_texts`;
+
+CREATE TRIGGER root_texts_ai AFTER INSERT ON root_texts BEGIN
+  INSERT INTO fts_root_texts
+    (rowid, content_plain)
+    VALUES
+    (new.id, new.content_plain);
+END;
+
+CREATE TRIGGER root_texts_ad AFTER DELETE ON root_texts BEGIN
+  INSERT INTO fts_root_texts
+    (fts_root_texts, rowid, content_plain)
+    VALUES
+    ('delete', old.id, old.content_plain);
+END;
+
+CREATE TRIGGER root_texts_au AFTER UPDATE ON root_texts BEGIN
+  INSERT INTO fts_root_texts
+    (fts_root_texts, rowid, content_plain)
+    VALUES
+    ('delete', old.id, old.content_plain);
+  INSERT INTO fts_root_texts
+    (rowid, content_plain)
+    VALUES
+    (new.id, new.content_plain);
+END;
+
+CREATE TRIGGER translated_texts_ai AFTER INSERT ON translated_texts BEGIN
+  INSERT INTO fts_translated_texts
+    (rowid, content_plain)
+    VALUES
+    (new.id, new.content_plain);
+END;
+
+CREATE TRIGGER translated_texts_ad AFTER DELETE ON translated_texts BEGIN
+  INSERT INTO fts_translated_texts
+    (fts_translated_texts, rowid, content_plain)
+    VALUES
+    ('delete', old.id, old.content_plain);
+END;
+
+CREATE TRIGGER translated_texts_au AFTER UPDATE ON translated_texts BEGIN
+  INSERT INTO fts_translated_texts
+    (fts_translated_texts, rowid, content_plain)
+    VALUES
+    ('delete', old.id, old.content_plain);
+  INSERT INTO fts_translated_texts
+    (rowid, content_plain)
+    VALUES
+    (new.id, new.content_plain);
+END;
+
+DROP TABLE sutta_index;
+DROP TABLE collections;
@@ -0,0 +1,33 @@
+CREATE TABLE `collections` (
+	`id`               INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`uid`              VARCHAR NOT NULL UNIQUE, --  an, an4, an4-vagga7
+	`parent_id`        INTEGER,
+	`collection_type`  VARCHAR NOT NULL,        --  division, subdivision, vagga
+	`collection_order` INTEGER NOT NULL,
+	`title_pali`       VARCHAR NOT NULL,        --  Aṅguttara Nikāya
+	`title_trans`      VARCHAR NOT NULL,        --  Numbered Discourses
+	FOREIGN KEY (`parent_id`) REFERENCES `collections` (`id`) ON DELETE CASCADE
+);
+
+CREATE INDEX `collections_parent_id` ON `collections` (`parent_id`);
+
+CREATE TABLE `sutta_index` (
+	`id`              INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`uid`             VARCHAR NOT NULL UNIQUE, --  an4.67
+	`collection_id`   INTEGER NOT NULL,        --  the closest collection: vagga, subdivision or division
+	`division`        VARCHAR NOT NULL,        --  an
+	`subdivision`     VARCHAR NOT NULL,        --  an4
+	`vagga_number`    VARCHAR NOT NULL,        --  7
+	`number_in_vagga` VARCHAR NOT NULL,        --  7
+	`sutta_order`     INTEGER NOT NULL,
+	`acronym`         VARCHAR NOT NULL,        --  AN 4.67
+	`volpage`         VARCHAR NOT NULL,        --  AN ii 72
+	`title_pali`      VARCHAR NOT NULL,        --  Ahirājasutta
+	`title_trans`     VARCHAR NOT NULL,        --  Snakes
+	FOREIGN KEY (`collection_id`) REFERENCES `collections` (`id`) ON DELETE CASCADE
+);
+
+CREATE INDEX `sutta_index_collection_id` ON `sutta_index` (`collection_id`);
+
+ALTER TABLE `root_texts` ADD COLUMN `sutta_index_id` INTEGER REFERENCES `sutta_index` (`id`) ON DELETE SET NULL;
+ALTER TABLE `translated_texts` ADD COLUMN `sutta_index_id` INTEGER REFERENCES `sutta_index` (`id`) ON DELETE SET NULL;
@@ -0,0 +1 @@
+DROP TABLE meaning_references;
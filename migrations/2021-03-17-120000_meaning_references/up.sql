@@ -0,0 +1,11 @@
+CREATE TABLE `meaning_references` (
+	`id`             INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`meaning_id`     INTEGER NOT NULL REFERENCES `meanings` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+	`ref_text`       VARCHAR NOT NULL, --  M I 23
+	`normalized_ref` VARCHAR NOT NULL, --  MN i 23
+	`sutta_uid`      VARCHAR NOT NULL, --  mn3, empty if it was not resolved
+	`text_uid`       VARCHAR NOT NULL  --  mn/3/pli/ms, empty if the text is not in the database
+);
+
+CREATE INDEX `meaning_references_meaning_id` ON `meaning_references` (`meaning_id`);
+CREATE INDEX `meaning_references_sutta_uid` ON `meaning_references` (`sutta_uid`);
@@ -0,0 +1 @@
+DROP TABLE pts_concordance;
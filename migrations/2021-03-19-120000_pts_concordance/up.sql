@@ -0,0 +1,16 @@
+CREATE TABLE `pts_concordance` (
+	`id`                  INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`collection`          VARCHAR NOT NULL, --  mn, sn, vin
+	`volume`              INTEGER NOT NULL, --  1
+	`page`                INTEGER NOT NULL, --  23
+	`edition`             VARCHAR NOT NULL, --  volpage (first page of the sutta in sutta.json), pts1ed, pts2ed, pts
+	`sutta_uid`           VARCHAR NOT NULL, --  mn3
+	`root_text_uid`       VARCHAR NOT NULL, --  mn/3/pli/ms, empty for volpage
+	`segment_start`       VARCHAR NOT NULL, --  mn3:4.2, empty for volpage
+	`segment_end`         VARCHAR NOT NULL, --  mn3:6.1, the last segment before the next page
+	`segment_start_order` INTEGER NOT NULL, --  segment_order in text_segments
+	`segment_end_order`   INTEGER NOT NULL
+);
+
+CREATE INDEX `pts_concordance_page` ON `pts_concordance` (`collection`, `volume`, `page`);
+CREATE INDEX `pts_concordance_sutta_uid` ON `pts_concordance` (`sutta_uid`);
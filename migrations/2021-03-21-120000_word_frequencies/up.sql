@@ -0,0 +1,16 @@
+CREATE TABLE `word_frequencies` (
+	`id`           INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`dict_word_id` INTEGER NOT NULL REFERENCES `dict_words` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+	`word`         VARCHAR NOT NULL, --  dhamma
+	`total`        INTEGER NOT NULL, --  occurrences in the Pali root texts, 0 if the word is not attested
+	`texts_count`  INTEGER NOT NULL, --  number of suttas with at least one occurrence
+	`dn`           INTEGER NOT NULL,
+	`mn`           INTEGER NOT NULL,
+	`sn`           INTEGER NOT NULL,
+	`an`           INTEGER NOT NULL,
+	`kn`           INTEGER NOT NULL,
+	`vinaya`       INTEGER NOT NULL
+);
+
+CREATE INDEX `word_frequencies_dict_word_id` ON `word_frequencies` (`dict_word_id`);
+CREATE INDEX `word_frequencies_word` ON `word_frequencies` (`word`);
@@ -0,0 +1,13 @@
+-- SQLite can't drop a column, the table is created again without the language columns.
+
+CREATE TABLE `dictionaries_new` (
+	`id`   INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`label` VARCHAR NOT NULL UNIQUE,
+	`title` VARCHAR NOT NULL
+);
+
+INSERT INTO `dictionaries_new` (`id`, `label`, `title`) SELECT `id`, `label`, `title` FROM `dictionaries`;
+
+DROP TABLE `dictionaries`;
+
+ALTER TABLE `dictionaries_new` RENAME TO `dictionaries`;
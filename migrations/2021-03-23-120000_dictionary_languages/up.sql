@@ -0,0 +1,2 @@
+ALTER TABLE `dictionaries` ADD COLUMN `source_language` VARCHAR NOT NULL DEFAULT 'pi';
+ALTER TABLE `dictionaries` ADD COLUMN `target_language` VARCHAR NOT NULL DEFAULT 'en';
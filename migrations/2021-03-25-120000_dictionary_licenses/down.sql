@@ -0,0 +1,16 @@
+-- SQLite can't drop a column, the table is created again without the license columns.
+
+CREATE TABLE `dictionaries_new` (
+	`id`              INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	`label`           VARCHAR NOT NULL UNIQUE,
+	`title`           VARCHAR NOT NULL,
+	`source_language` VARCHAR NOT NULL DEFAULT 'pi',
+	`target_language` VARCHAR NOT NULL DEFAULT 'en'
+);
+
+INSERT INTO `dictionaries_new` (`id`, `label`, `title`, `source_language`, `target_language`)
+SELECT `id`, `label`, `title`, `source_language`, `target_language` FROM `dictionaries`;
+
+DROP TABLE `dictionaries`;
+
+ALTER TABLE `dictionaries_new` RENAME TO `dictionaries`;
@@ -0,0 +1,7 @@
+ALTER TABLE `dictionaries` ADD COLUMN `license` VARCHAR NOT NULL DEFAULT '';          -- CC-BY-4.0
+ALTER TABLE `dictionaries` ADD COLUMN `license_name` VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE `dictionaries` ADD COLUMN `license_url` VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE `dictionaries` ADD COLUMN `copyright_holder` VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE `dictionaries` ADD COLUMN `copyright_year` VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE `dictionaries` ADD COLUMN `attribution` TEXT NOT NULL DEFAULT '';
+ALTER TABLE `dictionaries` ADD COLUMN `source_url` VARCHAR NOT NULL DEFAULT '';
@@ -2,3 +2,4 @@ INSERT INTO fts_root_texts(fts_root_texts) VALUES('rebuild');
 INSERT INTO fts_translated_texts(fts_translated_texts) VALUES('rebuild');
 INSERT INTO fts_meanings(fts_meanings) VALUES('rebuild');
 INSERT INTO fts_examples(fts_examples) VALUES('rebuild');
+INSERT INTO fts_text_segments(fts_text_segments) VALUES('rebuild');
@@ -9,6 +9,7 @@ cargo run -- suttacentral_json_to_markdown \
 	--reuse_metadata \
 	--title "Dhammika Pali - English Dictionary" \
 	--dict_label Dhammika \
+	--sc_data_path "$1" \
 	--source_path "$SC_ROOT/dhammika.json" \
 	--output_path "$OUT_DIR/dhammika.md"
 
@@ -26,6 +27,7 @@ cargo run -- suttacentral_json_to_markdown \
 	--reuse_metadata \
 	--title "Dictionary of Pali Proper Names (DPPN)" \
 	--dict_label DPPN \
+	--sc_data_path "$1" \
 	--source_path "$SC_ROOT/dppn.json" \
 	--output_path "$OUT_DIR/dppn.md"
 
@@ -43,6 +45,7 @@ cargo run -- suttacentral_json_to_markdown \
 	--reuse_metadata \
 	--title "New Concise Pali - English Dictionary (NCPED)" \
 	--dict_label NCPED \
+	--sc_data_path "$1" \
 	--source_path "$SC_ROOT/ncped.json" \
 	--output_path "$OUT_DIR/ncped.md"
 
@@ -61,6 +64,7 @@ cargo run -- suttacentral_json_to_markdown \
 	--title "Pali Text Society Pali - English Dictionary (PTS)" \
 	--dict_label PTS \
 	--dont_remove_see_also \
+	--sc_data_path "$1" \
 	--source_path "$SC_ROOT/pts.json" \
 	--output_path "$OUT_DIR/pts.md"
 
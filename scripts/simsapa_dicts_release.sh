@@ -277,7 +277,7 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
         cd "$PROJ_ROOT"
 
         cargo run -- markdown_to_ebook \
-            --title "Combined Pali - English Dictionary" \
+            --combined_metadata ./scripts/combined_dict_metadata.toml \
             --source_paths_list ./scripts/combined_dict_md_paths.txt \
             --output_format mobi \
             --output_path "$OUT_DIR/$i.mobi" \
@@ -292,7 +292,7 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
         cd "$PROJ_ROOT"
 
         cargo run -- markdown_to_ebook \
-            --title "Combined Pali - English Dictionary" \
+            --combined_metadata ./scripts/combined_dict_metadata.toml \
             --source_paths_list ./scripts/combined_dict_md_paths.txt \
             --word_prefix "*" \
             --output_format epub \
@@ -306,7 +306,7 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
         cd "$PROJ_ROOT"
 
         cargo run -- markdown_to_babylon_gls \
-            --title "Combined Pali - English Dictionary" \
+            --combined_metadata ./scripts/combined_dict_metadata.toml \
             --source_paths_list ./scripts/combined_dict_md_paths.txt \
             --output_path "$OUT_DIR/$i-babylon.gls"
 
@@ -333,12 +333,12 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
             if [[ "$fmt" == "plaintext" ]]; then
                 cargo run -- markdown_to_stardict_xml \
                     --keep_entries_plaintext \
-                    --title "Combined Pali - English Dictionary" \
+                    --combined_metadata ./scripts/combined_dict_metadata.toml \
                     --source_paths_list ./scripts/combined_dict_md_paths.txt \
                     --output_path "$stardict_out/$i-$fmt.xml"
             else
                 cargo run -- markdown_to_stardict_xml \
-                    --title "Combined Pali - English Dictionary" \
+                    --combined_metadata ./scripts/combined_dict_metadata.toml \
                     --source_paths_list ./scripts/combined_dict_md_paths.txt \
                     --output_path "$stardict_out/$i-$fmt.xml"
             fi
@@ -380,12 +380,12 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
             if [[ "$fmt" == "plaintext" ]]; then
                 cargo run -- markdown_to_c5 \
                     --keep_entries_plaintext \
-                    --title "$title" \
+                    --combined_metadata ./scripts/combined_dict_metadata.toml \
                     --source_paths_list ./scripts/combined_dict_md_paths.txt \
                     --output_path "$dict_out/$i-$fmt.txt"
             else
                 cargo run -- markdown_to_c5 \
-                    --title "Combined Pali - English Dictionary" \
+                    --combined_metadata ./scripts/combined_dict_metadata.toml \
                     --source_paths_list ./scripts/combined_dict_md_paths.txt \
                     --output_path "$dict_out/$i-$fmt.txt"
             fi
@@ -435,7 +435,7 @@ if [[ "$BUILD_COMBINED" -eq 1 ]]; then
         cd "$PROJ_ROOT"
 
         cargo run -- markdown_to_tei \
-            --title "Combined Pali - English Dictionary" \
+            --combined_metadata ./scripts/combined_dict_metadata.toml \
             --source_paths_list ./scripts/combined_dict_md_paths.txt \
             --output_path "$OUT_DIR/$i.tei"
 
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
 use std::default::Default;
 use std::error::Error;
-use std::fs;
+use std::fs::{self, File};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -8,8 +10,10 @@ use chrono::prelude::*;
 use walkdir::WalkDir;
 use regex::Regex;
 use calamine::{open_workbook, Xlsx, Reader, RangeDeserializerBuilder};
+use scraper::{ElementRef, Html, Selector};
 
 use pali_dict_core::dict_word::{DictWordMarkdown, DictWordHeader, DictWordXlsx};
+use pali_dict_core::pali;
 use crate::dictionary::{
     Dictionary, OutputFormat, DictMetadata, DICTIONARY_METADATA_SEP, DICTIONARY_WORD_ENTRIES_SEP,
 };
@@ -21,9 +25,19 @@ pub struct AppStartParams {
     pub output_format: OutputFormat,
     pub metadata_path: Option<PathBuf>,
     pub nyanatiloka_root: Option<PathBuf>,
+    pub rules_path: Option<PathBuf>,
     pub source_paths: Option<Vec<PathBuf>>,
+    pub combined_metadata_path: Option<PathBuf>,
     pub output_path: Option<PathBuf>,
-    pub po_text_json_path: Option<PathBuf>,
+    pub sc_data_path: Option<PathBuf>,
+    pub languages: Option<Vec<String>>,
+    pub divisions: Option<Vec<String>>,
+    pub sutta_link_template: Option<String>,
+    pub reference: Option<String>,
+    pub lookup_word: Option<String>,
+    pub examples_count: usize,
+    pub markdown_path: Option<PathBuf>,
+    pub frequency_db_path: Option<PathBuf>,
     pub entries_template: Option<PathBuf>,
     pub mobi_compression: usize,
     pub kindlegen_path: Option<PathBuf>,
@@ -35,6 +49,10 @@ pub struct AppStartParams {
     pub word_prefix_velthuis: bool,
     pub allow_raw_html: bool,
     pub dont_generate_synonyms: bool,
+    pub dont_generate_inflections: bool,
+    pub add_paradigm_tables: bool,
+    pub split_compounds: bool,
+    pub merge_entries: bool,
     pub dont_run_kindlegen: bool,
     pub dont_remove_generated_files: bool,
     pub dont_process: bool,
@@ -47,7 +65,11 @@ pub struct AppStartParams {
 
 #[derive(Clone, Copy, Debug)]
 pub enum RunCommand {
+    CorpusExamplesToSqlite,
+    EpubToMarkdown,
+    HtmlToMarkdown,
     JsonToXlsx,
+    Lookup,
     MarkdownToBabylon,
     MarkdownToC5,
     MarkdownToEbook,
@@ -57,9 +79,14 @@ pub enum RunCommand {
     MarkdownToTei,
     NoOp,
     NyanatilokaToMarkdown,
+    ResolveReference,
+    SplitCompound,
+    SuttaCentralBilaraToSqlite,
     SuttaCentralJsonToMarkdown,
     SuttaCentralPoTextsToSqlite,
     SuttaCentralHtmlTextsToSqlite,
+    SuttaCentralStructureToSqlite,
+    WordFrequenciesToSqlite,
     XlsxToBabylon,
     XlsxToC5,
     XlsxToEbook,
@@ -96,9 +123,19 @@ impl Default for AppStartParams {
             output_format: OutputFormat::Epub,
             metadata_path: None,
             nyanatiloka_root: None,
+            rules_path: None,
             source_paths: None,
+            combined_metadata_path: None,
             output_path: None,
-            po_text_json_path: None,
+            sc_data_path: None,
+            languages: None,
+            divisions: None,
+            sutta_link_template: None,
+            reference: None,
+            lookup_word: None,
+            examples_count: 3,
+            markdown_path: None,
+            frequency_db_path: None,
             entries_template: None,
             kindlegen_path: None,
             reuse_metadata: false,
@@ -110,6 +147,10 @@ impl Default for AppStartParams {
             allow_raw_html: false,
             mobi_compression: 0,
             dont_generate_synonyms: false,
+            dont_generate_inflections: false,
+            add_paradigm_tables: false,
+            split_compounds: false,
+            merge_entries: false,
             dont_run_kindlegen: false,
             dont_remove_generated_files: false,
             dont_process: false,
@@ -342,6 +383,14 @@ fn process_to_ebook(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     match sub_matches.value_of("output_path") {
         Some(x) => params.output_path = Some(ensure_parent(&PathBuf::from(&x))),
 
@@ -407,6 +456,18 @@ fn process_to_ebook(
         params.allow_raw_html = true;
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
+    if sub_matches.is_present("add_paradigm_tables") {
+        params.add_paradigm_tables = true;
+    }
+
+    if sub_matches.is_present("split_compounds") {
+        params.split_compounds = true;
+    }
+
     if sub_matches.is_present("dont_remove_generated_files") {
         params.dont_remove_generated_files = true;
     }
@@ -499,6 +560,14 @@ fn process_to_babylon(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     let path = match sub_matches.value_of("output_path") {
         Some(x) => ensure_parent(&PathBuf::from(&x)),
 
@@ -525,6 +594,10 @@ fn process_to_babylon(
         params.allow_raw_html = true;
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -625,6 +698,14 @@ fn process_to_stardict(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     let path = match sub_matches.value_of("output_path") {
         Some(x) => ensure_parent(&PathBuf::from(&x)),
 
@@ -654,6 +735,20 @@ fn process_to_stardict(
         params.dont_generate_synonyms = true;
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
+    if sub_matches.is_present("add_paradigm_tables") {
+        params.add_paradigm_tables = true;
+    }
+
+    if sub_matches.is_present("split_compounds") {
+        params.split_compounds = true;
+    }
+
+    frequency_db_path_arg(params, sub_matches)?;
+
     params.run_command = run_command;
 
     Ok(())
@@ -755,6 +850,14 @@ fn process_to_latex(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     let path = match sub_matches.value_of("output_path") {
         Some(x) => ensure_parent(&PathBuf::from(&x)),
 
@@ -779,6 +882,10 @@ fn process_to_latex(
         params.dont_generate_synonyms = true;
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -874,6 +981,10 @@ fn process_to_sqlite(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
     if sub_matches.is_present("title") {
         if let Ok(x) = sub_matches.value_of("title").unwrap().parse::<String>() {
             params.title = Some(x);
@@ -898,6 +1009,10 @@ fn process_to_sqlite(
         params.output_path = Some(PathBuf::from(&x));
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -964,6 +1079,14 @@ fn process_xlsx_to_render_json(
         params.output_path = Some(PathBuf::from(&x));
     }
 
+    frequency_db_path_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -1111,6 +1234,14 @@ fn process_to_c5(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     let path = match sub_matches.value_of("output_path") {
         Some(x) => ensure_parent(&PathBuf::from(&x)),
 
@@ -1136,6 +1267,10 @@ fn process_to_c5(
         params.allow_raw_html = true;
     }
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -1220,6 +1355,14 @@ fn process_to_tei(
         }
     }
 
+    combined_metadata_arg(params, sub_matches)?;
+
+    sutta_refs_args(params, sub_matches)?;
+
+    if sub_matches.is_present("merge_entries") {
+        params.merge_entries = true;
+    }
+
     let path = match sub_matches.value_of("output_path") {
         Some(x) => ensure_parent(&PathBuf::from(&x)),
 
@@ -1243,6 +1386,10 @@ fn process_to_tei(
 
     // Not accepting --allow_raw_html for TEI.
 
+    if sub_matches.is_present("dont_generate_inflections") {
+        params.dont_generate_inflections = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
@@ -1306,33 +1453,29 @@ fn process_suttacentral_json_to_markdown(
         params.dont_remove_see_also = true;
     }
 
+    sutta_refs_args(params, sub_matches)?;
+
     params.run_command = run_command;
 
     Ok(())
 }
 
-fn process_suttacentral_po_texts_to_sqlite(
+fn process_epub_to_markdown(
     params: &mut AppStartParams,
     sub_matches: &clap::ArgMatches<'_>,
     run_command: RunCommand)
     -> Result<(), Box<dyn Error>>
 {
-    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+    if let Ok(x) = sub_matches
+        .value_of("source_path")
+            .unwrap()
+            .parse::<String>()
+    {
         let path = PathBuf::from(&x);
-        if path.is_dir() && path.join("html_text").is_dir() {
+        if path.exists() {
             params.source_paths = Some(vec![path]);
         } else {
-            let msg = format!("🔥 Wrong path: {:?}", &path);
-            return Err(Box::new(ToolError::Exit(msg)));
-        }
-    }
-
-    if let Ok(x) = sub_matches.value_of("po_text_json_path").unwrap().parse::<String>() {
-        let path = PathBuf::from(&x);
-        if path.is_dir() && path.join("pli-en").is_dir() {
-            params.po_text_json_path = Some(path);
-        } else {
-            let msg = format!("🔥 Wrong path: {:?}", &path);
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
             return Err(Box::new(ToolError::Exit(msg)));
         }
     }
@@ -1345,12 +1488,36 @@ fn process_suttacentral_po_texts_to_sqlite(
         params.output_path = Some(PathBuf::from(&x));
     }
 
+    if sub_matches.is_present("title") {
+        if let Ok(x) = sub_matches
+            .value_of("title")
+                .unwrap()
+                .parse::<String>()
+        {
+            params.title = Some(x);
+        }
+    }
+
+    if sub_matches.is_present("dict_label") {
+        if let Ok(x) = sub_matches
+            .value_of("dict_label")
+                .unwrap()
+                .parse::<String>()
+        {
+            params.dict_label = Some(x);
+        }
+    }
+
+    if sub_matches.is_present("reuse_metadata") {
+        params.reuse_metadata = true;
+    }
+
     params.run_command = run_command;
 
     Ok(())
 }
 
-fn process_suttacentral_html_texts_to_sqlite(
+fn process_suttacentral_po_texts_to_sqlite(
     params: &mut AppStartParams,
     sub_matches: &clap::ArgMatches<'_>,
     run_command: RunCommand)
@@ -1358,7 +1525,7 @@ fn process_suttacentral_html_texts_to_sqlite(
 {
     if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
         let path = PathBuf::from(&x);
-        if path.is_dir() && path.join("html_text").is_dir() {
+        if path.is_dir() && path.join("po_text").is_dir() {
             params.source_paths = Some(vec![path]);
         } else {
             let msg = format!("🔥 Wrong path: {:?}", &path);
@@ -1379,54 +1546,65 @@ fn process_suttacentral_html_texts_to_sqlite(
     Ok(())
 }
 
-fn process_nyanatiloka_to_markdown(
+fn process_corpus_examples_to_sqlite(
     params: &mut AppStartParams,
     sub_matches: &clap::ArgMatches<'_>,
     run_command: RunCommand)
     -> Result<(), Box<dyn Error>>
 {
     if let Ok(x) = sub_matches
-        .value_of("nyanatiloka_root")
+        .value_of("output_path")
             .unwrap()
             .parse::<String>()
     {
-        let path = PathBuf::from(&x);
-        if path.is_dir() {
-            params.nyanatiloka_root = Some(path);
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    if sub_matches.is_present("dict_label") {
+        params.dict_label = Some(sub_matches.value_of("dict_label").unwrap().to_string());
+    }
+
+    if sub_matches.is_present("examples_count") {
+        match sub_matches.value_of("examples_count").unwrap().parse::<usize>() {
+            Ok(x) => params.examples_count = x,
+            Err(_) => {
+                let msg = "🔥 examples_count must be a number.".to_string();
+                return Err(Box::new(ToolError::Exit(msg)));
+            }
+        }
+    }
+
+    if sub_matches.is_present("markdown_path") {
+        let path = PathBuf::from(sub_matches.value_of("markdown_path").unwrap());
+        if path.is_file() {
+            params.markdown_path = Some(path);
         } else {
             let msg = format!("🔥 Path does not exist: {:?}", &path);
             return Err(Box::new(ToolError::Exit(msg)));
         }
     }
 
-    if let Ok(x) = sub_matches
-        .value_of("output_path")
-            .unwrap()
-            .parse::<String>()
-    {
-        params.output_path = Some(PathBuf::from(&x));
-    }
+    params.run_command = run_command;
 
-    if sub_matches.is_present("title") {
-        if let Ok(x) = sub_matches
-            .value_of("title")
-                .unwrap()
-                .parse::<String>()
-        {
-            params.title = Some(x);
-        }
-    }
+    Ok(())
+}
 
+fn process_word_frequencies_to_sqlite(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
     if let Ok(x) = sub_matches
-        .value_of("dict_label")
+        .value_of("output_path")
             .unwrap()
             .parse::<String>()
     {
-        params.dict_label = Some(x);
+        params.output_path = Some(PathBuf::from(&x));
     }
 
-    if sub_matches.is_present("reuse_metadata") {
-        params.reuse_metadata = true;
+    if sub_matches.is_present("dict_label") {
+        params.dict_label = Some(sub_matches.value_of("dict_label").unwrap().to_string());
     }
 
     params.run_command = run_command;
@@ -1434,54 +1612,431 @@ fn process_nyanatiloka_to_markdown(
     Ok(())
 }
 
-pub fn process_cli_args(matches: clap::ArgMatches<'_>) -> Result<AppStartParams, Box<dyn Error>> {
-    info!("process_cli_args()");
-    let mut params = AppStartParams::default();
+/// The optional TOML file with the metadata of a combined build.
+fn combined_metadata_arg(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Some(x) = sub_matches.value_of("combined_metadata") {
+        let path = PathBuf::from(x);
+        if path.exists() {
+            params.combined_metadata_path = Some(path);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
 
-    if let Some(sub_matches) = matches.subcommand_matches("suttacentral_json_to_markdown") {
-        process_suttacentral_json_to_markdown(&mut params, sub_matches, RunCommand::SuttaCentralJsonToMarkdown)?;
+    Ok(())
+}
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_po_texts_to_sqlite") {
-        process_suttacentral_po_texts_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralPoTextsToSqlite)?;
+/// The optional sc-data path and link template to link the sutta references with.
+fn sutta_refs_args(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Some(x) = sub_matches.value_of("sc_data_path") {
+        let path = PathBuf::from(x);
+        if path.join("structure").join("sutta.json").is_file() {
+            params.sc_data_path = Some(path);
+        } else {
+            let msg = format!("🔥 Wrong path: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_html_texts_to_sqlite") {
-        process_suttacentral_html_texts_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralHtmlTextsToSqlite)?;
+    if let Some(x) = sub_matches.value_of("sutta_link_template") {
+        params.sutta_link_template = Some(x.to_string());
+    }
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("nyanatiloka_to_markdown") {
-        process_nyanatiloka_to_markdown(&mut params, sub_matches, RunCommand::NyanatilokaToMarkdown)?;
+    Ok(())
+}
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_json") {
-        process_markdown_to_json(&mut params, sub_matches, RunCommand::MarkdownToJson)?;
+/// The optional database with the `word_frequencies` counts to add to the output.
+fn frequency_db_path_arg(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>)
+    -> Result<(), Box<dyn Error>>
+{
+    if sub_matches.is_present("frequency_db_path") {
+        let path = PathBuf::from(sub_matches.value_of("frequency_db_path").unwrap());
+        if path.is_file() {
+            params.frequency_db_path = Some(path);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_sqlite") {
-        process_to_sqlite(&mut params, sub_matches, RunCommand::MarkdownToSqlite)?;
+    Ok(())
+}
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_json") {
-        process_xlsx_to_json(&mut params, sub_matches, RunCommand::XlsxToJson)?;
+fn process_lookup(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_file() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_render_json") {
-        process_xlsx_to_render_json(&mut params, sub_matches, RunCommand::XlsxToRenderJson)?;
+    params.lookup_word = Some(sub_matches.value_of("word").unwrap().to_string());
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_sqlite") {
-        process_to_sqlite(&mut params, sub_matches, RunCommand::XlsxToSqlite)?;
+    params.run_command = run_command;
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("json_to_xlsx") {
-        process_json_to_xlsx(&mut params, sub_matches, RunCommand::JsonToXlsx)?;
+    Ok(())
+}
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_ebook") {
-        process_to_ebook(&mut params, sub_matches, RunCommand::MarkdownToEbook)?;
+fn process_resolve_reference(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_file() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_ebook") {
-        process_to_ebook(&mut params, sub_matches, RunCommand::XlsxToEbook)?;
+    params.reference = Some(sub_matches.value_of("reference").unwrap().to_string());
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_babylon_gls") {
-        process_to_babylon(&mut params, sub_matches, RunCommand::MarkdownToBabylon)?;
+    params.run_command = run_command;
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_babylon_gls") {
-        process_to_babylon(&mut params, sub_matches, RunCommand::XlsxToBabylon)?;
+    Ok(())
+}
 
-    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_stardict_xml") {
-        process_to_stardict(&mut params, sub_matches, RunCommand::MarkdownToStardict)?;
+fn process_suttacentral_structure_to_sqlite(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_dir() && path.join("structure").join("sutta.json").is_file() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Wrong path: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("output_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    params.run_command = run_command;
+
+    Ok(())
+}
+
+fn process_suttacentral_bilara_to_sqlite(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_dir() && path.join("root").is_dir() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Wrong path: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches.value_of("sc_data_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_dir() && path.join("additional-info").is_dir() {
+            params.sc_data_path = Some(path);
+        } else {
+            let msg = format!("🔥 Wrong path: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("output_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    params.run_command = run_command;
+
+    Ok(())
+}
+
+fn process_suttacentral_html_texts_to_sqlite(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches.value_of("source_path").unwrap().parse::<String>() {
+        let path = PathBuf::from(&x);
+        if path.is_dir() && path.join("html_text").is_dir() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Wrong path: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("output_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    if sub_matches.is_present("languages") {
+        if let Ok(x) = sub_matches.value_of("languages").unwrap().parse::<String>() {
+            params.languages = Some(split_comma_list(&x));
+        }
+    }
+
+    if sub_matches.is_present("divisions") {
+        if let Ok(x) = sub_matches.value_of("divisions").unwrap().parse::<String>() {
+            params.divisions = Some(split_comma_list(&x));
+        }
+    }
+
+    params.run_command = run_command;
+
+    Ok(())
+}
+
+fn process_nyanatiloka_to_markdown(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches
+        .value_of("nyanatiloka_root")
+            .unwrap()
+            .parse::<String>()
+    {
+        let path = PathBuf::from(&x);
+        if path.is_dir() {
+            params.nyanatiloka_root = Some(path);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("output_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    if sub_matches.is_present("title") {
+        if let Ok(x) = sub_matches
+            .value_of("title")
+                .unwrap()
+                .parse::<String>()
+        {
+            params.title = Some(x);
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("dict_label")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.dict_label = Some(x);
+    }
+
+    if sub_matches.is_present("reuse_metadata") {
+        params.reuse_metadata = true;
+    }
+
+    sutta_refs_args(params, sub_matches)?;
+
+    params.run_command = run_command;
+
+    Ok(())
+}
+
+fn process_html_to_markdown(
+    params: &mut AppStartParams,
+    sub_matches: &clap::ArgMatches<'_>,
+    run_command: RunCommand)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Ok(x) = sub_matches
+        .value_of("source_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        let path = PathBuf::from(&x);
+        if path.is_dir() {
+            params.source_paths = Some(vec![path]);
+        } else {
+            let msg = format!("🔥 Path is not a folder: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("rules_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        let path = PathBuf::from(&x);
+        if path.exists() {
+            params.rules_path = Some(path);
+        } else {
+            let msg = format!("🔥 Path does not exist: {:?}", &path);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("output_path")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.output_path = Some(PathBuf::from(&x));
+    }
+
+    if sub_matches.is_present("title") {
+        if let Ok(x) = sub_matches
+            .value_of("title")
+                .unwrap()
+                .parse::<String>()
+        {
+            params.title = Some(x);
+        }
+    }
+
+    if let Ok(x) = sub_matches
+        .value_of("dict_label")
+            .unwrap()
+            .parse::<String>()
+    {
+        params.dict_label = Some(x);
+    }
+
+    if sub_matches.is_present("reuse_metadata") {
+        params.reuse_metadata = true;
+    }
+
+    params.run_command = run_command;
+
+    Ok(())
+}
+
+/// en, de -> ["en", "de"]
+fn split_comma_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+pub fn process_cli_args(matches: clap::ArgMatches<'_>) -> Result<AppStartParams, Box<dyn Error>> {
+    info!("process_cli_args()");
+    let mut params = AppStartParams::default();
+
+    if let Some(sub_matches) = matches.subcommand_matches("suttacentral_json_to_markdown") {
+        process_suttacentral_json_to_markdown(&mut params, sub_matches, RunCommand::SuttaCentralJsonToMarkdown)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("epub_to_markdown") {
+        process_epub_to_markdown(&mut params, sub_matches, RunCommand::EpubToMarkdown)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_po_texts_to_sqlite") {
+        process_suttacentral_po_texts_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralPoTextsToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_bilara_to_sqlite") {
+        process_suttacentral_bilara_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralBilaraToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_structure_to_sqlite") {
+        process_suttacentral_structure_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralStructureToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("lookup") {
+        process_lookup(&mut params, sub_matches, RunCommand::Lookup)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("split_compound") {
+        process_lookup(&mut params, sub_matches, RunCommand::SplitCompound)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("resolve_reference") {
+        process_resolve_reference(&mut params, sub_matches, RunCommand::ResolveReference)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("corpus_examples_to_sqlite") {
+        process_corpus_examples_to_sqlite(&mut params, sub_matches, RunCommand::CorpusExamplesToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("word_frequencies_to_sqlite") {
+        process_word_frequencies_to_sqlite(&mut params, sub_matches, RunCommand::WordFrequenciesToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("suttacentral_html_texts_to_sqlite") {
+        process_suttacentral_html_texts_to_sqlite(&mut params, sub_matches, RunCommand::SuttaCentralHtmlTextsToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("nyanatiloka_to_markdown") {
+        process_nyanatiloka_to_markdown(&mut params, sub_matches, RunCommand::NyanatilokaToMarkdown)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("html_to_markdown") {
+        process_html_to_markdown(&mut params, sub_matches, RunCommand::HtmlToMarkdown)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_json") {
+        process_markdown_to_json(&mut params, sub_matches, RunCommand::MarkdownToJson)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_sqlite") {
+        process_to_sqlite(&mut params, sub_matches, RunCommand::MarkdownToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_json") {
+        process_xlsx_to_json(&mut params, sub_matches, RunCommand::XlsxToJson)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_render_json") {
+        process_xlsx_to_render_json(&mut params, sub_matches, RunCommand::XlsxToRenderJson)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_sqlite") {
+        process_to_sqlite(&mut params, sub_matches, RunCommand::XlsxToSqlite)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("json_to_xlsx") {
+        process_json_to_xlsx(&mut params, sub_matches, RunCommand::JsonToXlsx)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_ebook") {
+        process_to_ebook(&mut params, sub_matches, RunCommand::MarkdownToEbook)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_ebook") {
+        process_to_ebook(&mut params, sub_matches, RunCommand::XlsxToEbook)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_babylon_gls") {
+        process_to_babylon(&mut params, sub_matches, RunCommand::MarkdownToBabylon)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_babylon_gls") {
+        process_to_babylon(&mut params, sub_matches, RunCommand::XlsxToBabylon)?;
+
+    } else if let Some(sub_matches) = matches.subcommand_matches("markdown_to_stardict_xml") {
+        process_to_stardict(&mut params, sub_matches, RunCommand::MarkdownToStardict)?;
 
     } else if let Some(sub_matches) = matches.subcommand_matches("xlsx_to_stardict_xml") {
         process_to_stardict(&mut params, sub_matches, RunCommand::XlsxToStardict)?;
@@ -1644,62 +2199,627 @@ pub fn process_nyanatiloka_entries(
         entries.push(Entry { word, text });
     }
 
+    // <a href="term-dhutanga.html">dhutaṅga</a>
+    // <a href="./term-mūla.html#x">mūla</a>
+    let re_term_link = Regex::new(r##"href="(?:\./)?term-([^"#]+)\.html(?:#[^"]*)?""##).unwrap();
+
     for e in entries.iter() {
+        let word = e.word.to_lowercase();
+
+        // The term-*.html links become /define/ links, and the linked terms are added to see_also.
+        let mut see_also: Vec<String> = Vec::new();
+        for cap in re_term_link.captures_iter(&e.text) {
+            let w = cap[1].to_lowercase();
+            if w != word && !see_also.contains(&w) {
+                see_also.push(w);
+            }
+        }
+        let text = re_term_link.replace_all(&e.text, |caps: &regex::Captures<'_>| {
+            format!(r#"href="/define/{}""#, caps[1].to_lowercase())
+        });
+
+        let definition_md = html_to_markdown(&text);
+
+        let mut word_header = new_word_header(&word, dict_label);
+        word_header.see_also = see_also;
+        word_header.summary = nyanatiloka_english_summary(&definition_md);
+
         let new_word = DictWordMarkdown {
-            word_header: DictWordHeader {
-                word: e.word.to_lowercase(),
-                meaning_order: 1,
-                word_nom_sg: "".to_string(),
-                is_root: false,
-                dict_label: (*dict_label).to_string(),
+            word_header,
+            definition_md,
+        };
 
-                inflections: Vec::new(),
-                phonetic: "".to_string(),
-                transliteration: "".to_string(),
+        dict.add_word(new_word)
+    }
+}
 
-                summary: "".to_string(),
+/// The entries start with the English rendering of the term in quotes, use that as the summary.
+///
+/// **abhijjhā**: 'covetousness', is a synonym of lobha ...
+fn nyanatiloka_english_summary(definition_md: &str) -> String {
+    lazy_static! {
+        static ref RE_ENGLISH: Regex = Regex::new(
+            r#"^[^:\n]{1,80}:\s*['‘"“]([^'’"”\n]{1,80})['’"”]"#).unwrap();
+    }
 
-                synonyms: Vec::new(),
-                antonyms: Vec::new(),
-                homonyms: Vec::new(),
-                also_written_as: Vec::new(),
-                see_also: Vec::new(),
-                comment: "".to_string(),
+    match RE_ENGLISH.captures(definition_md.trim_start()) {
+        Some(caps) => caps[1].trim().to_string(),
+        None => "".to_string(),
+    }
+}
 
-                grammar_roots: Vec::new(),
-                grammar_prefix_and_root: "".to_string(),
+/// Rules for the `html_to_markdown` command, read from a TOML file. They describe where the
+/// entries are in a folder of HTML files, so that a new glossary can be imported without writing a
+/// dedicated importer. See `scripts/nyanatiloka_html_rules.toml` for an example.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct HtmlImportRules {
+    /// Which files to read, relative to the source folder. `*` and `?` match within a path
+    /// segment, `**` matches across folders. E.g. `html_entries/term-*.html`
+    pub file_glob: String,
+    /// When a file contains several entries, a CSS selector for the element of each entry. When
+    /// empty, the whole file is one entry.
+    pub entry_selector: String,
+    /// CSS selector for the headword element, within the entry. When empty, the headword is taken
+    /// from the file name.
+    pub headword_selector: String,
+    /// Regex applied to the headword text (or the file name), the first capture group is the
+    /// headword. E.g. `^term-(.+)\.html$`
+    pub headword_regex: String,
+    /// Lowercase the headwords.
+    pub lowercase_headword: bool,
+    /// CSS selector for the definition elements, within the entry. When empty, the whole entry is
+    /// the definition.
+    pub definition_selector: String,
+    /// CSS selector for cross-reference links, their text is added to `see_also`.
+    pub see_also_selector: String,
+    /// CSS selectors for elements to remove before converting the definition to Markdown.
+    pub drop_selectors: Vec<String>,
+}
 
-                grammar_construction: "".to_string(),
-                grammar_base_construction: "".to_string(),
-                grammar_compound_type: "".to_string(),
-                grammar_compound_construction: "".to_string(),
+impl Default for HtmlImportRules {
+    fn default() -> Self {
+        HtmlImportRules {
+            file_glob: "**/*.html".to_string(),
+            entry_selector: "".to_string(),
+            headword_selector: "".to_string(),
+            headword_regex: "".to_string(),
+            lowercase_headword: false,
+            definition_selector: "".to_string(),
+            see_also_selector: "".to_string(),
+            drop_selectors: Vec::new(),
+        }
+    }
+}
 
-                grammar_comment: "".to_string(),
-                grammar_speech: "".to_string(),
-                grammar_case: "".to_string(),
-                grammar_num: "".to_string(),
-                grammar_gender: "".to_string(),
-                grammar_person: "".to_string(),
-                grammar_voice: "".to_string(),
-                grammar_object: "".to_string(),
-                grammar_transitive: "".to_string(),
-                grammar_negative: "".to_string(),
-                grammar_verb: "".to_string(),
+/// Read a folder of HTML files and add the entries to the dictionary, as described by the rules
+/// file. This is the generic version of `process_nyanatiloka_entries`.
+pub fn process_html_entries(
+    source_root: &PathBuf,
+    rules_path: &PathBuf,
+    dict_label: &Option<String>,
+    dict: &mut Dictionary,
+) -> Result<(), Box<dyn Error>> {
+    let dict_label = &dict_label.as_ref().expect("dict_label is missing.");
 
-                examples: Vec::new(),
+    info! {"=== Begin processing {:?} ===", source_root};
 
-                root_language: "".to_string(),
-                root_groups: Vec::new(),
-                root_sign: "".to_string(),
-                root_numbered_group: "".to_string(),
+    let s = fs::read_to_string(rules_path)?;
+    let rules: HtmlImportRules = match toml::from_str(&s) {
+        Ok(x) => x,
+        Err(e) => {
+            let msg = format!("🔥 Can't parse the rules file: {:?}\n{:?}", rules_path, e);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    };
 
-                // dict.add_word will increment meaning_order if needed
-                url_id: DictWordMarkdown::gen_url_id(&e.word, &dict_label, 1),
-            },
-            definition_md: html_to_markdown(&e.text),
-        };
+    let file_re = glob_to_regex(&rules.file_glob)?;
 
-        dict.add_word(new_word)
+    let headword_re = if rules.headword_regex.is_empty() {
+        None
+    } else {
+        Some(Regex::new(&rules.headword_regex)?)
+    };
+
+    let entry_selector = parse_rule_selector(&rules.entry_selector)?;
+    let headword_selector = parse_rule_selector(&rules.headword_selector)?;
+    let definition_selector = parse_rule_selector(&rules.definition_selector)?;
+    let see_also_selector = parse_rule_selector(&rules.see_also_selector)?;
+
+    let mut drop_selectors: Vec<Selector> = Vec::new();
+    for x in rules.drop_selectors.iter() {
+        if let Some(sel) = parse_rule_selector(x)? {
+            drop_selectors.push(sel);
+        }
+    }
+
+    // Collect and sort the paths, so that the meaning_order of repeated headwords doesn't depend
+    // on the file system order.
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    info!("Walking '{:?}'", source_root);
+    let walker = WalkDir::new(&source_root).into_iter();
+    for entry in walker.filter_entry(|e| !is_hidden(e)) {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            continue;
+        }
+
+        let rel_path = entry.path().strip_prefix(&source_root)?;
+        let rel_path = rel_path.to_str().unwrap_or("").replace('\\', "/");
+        if file_re.is_match(&rel_path) {
+            paths.push(entry.path().to_path_buf());
+        }
+    }
+
+    paths.sort();
+
+    if paths.is_empty() {
+        warn! {"No files matched '{}'", &rules.file_glob};
+    }
+
+    for path in paths.iter() {
+        let file_name = path.file_name().unwrap().to_str().unwrap_or("");
+        let text = fs::read_to_string(&path)?;
+        let mut document = Html::parse_document(&text);
+
+        // (word, see_also, definition node ids)
+        let mut entries = Vec::new();
+
+        {
+            let entry_roots: Vec<ElementRef<'_>> = match entry_selector {
+                Some(ref sel) => document.select(sel).collect(),
+                None => vec![document.root_element()],
+            };
+
+            for root in entry_roots.iter() {
+                let mut word = match headword_selector {
+                    Some(ref sel) => match root.select(sel).next() {
+                        Some(el) => el.text().collect::<String>(),
+                        None => "".to_string(),
+                    },
+                    None => file_name.to_string(),
+                };
+
+                if let Some(ref re) = headword_re {
+                    word = match re.captures(&word) {
+                        Some(caps) => match caps.get(1) {
+                            Some(m) => m.as_str().to_string(),
+                            None => caps[0].to_string(),
+                        },
+                        None => "".to_string(),
+                    };
+                }
+
+                let mut word = word.trim().to_string();
+                if rules.lowercase_headword {
+                    word = word.to_lowercase();
+                }
+
+                if word.is_empty() {
+                    warn! {"Headword not found, skipping an entry in: {:?}", path};
+                    continue;
+                }
+
+                let mut see_also: Vec<String> = Vec::new();
+                if let Some(ref sel) = see_also_selector {
+                    for el in root.select(sel) {
+                        let mut w = el.text().collect::<String>().trim().to_string();
+                        if rules.lowercase_headword {
+                            w = w.to_lowercase();
+                        }
+                        if !w.is_empty() && w != word && !see_also.contains(&w) {
+                            see_also.push(w);
+                        }
+                    }
+                }
+
+                let definition_ids: Vec<_> = match definition_selector {
+                    Some(ref sel) => root.select(sel).map(|el| el.id()).collect(),
+                    None => vec![root.id()],
+                };
+
+                entries.push((word, see_also, definition_ids));
+            }
+        }
+
+        // Remove the dropped elements from the tree, after the headwords and links were read.
+        let mut drop_ids = Vec::new();
+        for sel in drop_selectors.iter() {
+            drop_ids.extend(document.select(sel).map(|el| el.id()));
+        }
+        for id in drop_ids.into_iter() {
+            if let Some(mut node) = document.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+
+        for (word, see_also, definition_ids) in entries.into_iter() {
+            let html: Vec<String> = definition_ids
+                .iter()
+                .filter_map(|id| document.tree.get(*id))
+                .filter_map(ElementRef::wrap)
+                .map(|el| el.html())
+                .collect();
+
+            let mut word_header = new_word_header(&word, dict_label);
+            word_header.see_also = see_also;
+
+            let new_word = DictWordMarkdown {
+                word_header,
+                definition_md: html_to_markdown(&html.join("\n")),
+            };
+
+            dict.add_word(new_word);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a file glob to an anchored regex. `**` matches across folders, `*` and `?` within one.
+fn glob_to_regex(glob: &str) -> Result<Regex, Box<dyn Error>> {
+    let mut s = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                    // Let '**/' also match zero folders.
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        s.push_str("(.*/)?");
+                    } else {
+                        s.push_str(".*");
+                    }
+                } else {
+                    s.push_str("[^/]*");
+                }
+            }
+            '?' => s.push_str("[^/]"),
+            _ => s.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    s.push('$');
+    Ok(Regex::new(&s)?)
+}
+
+/// Empty selectors in the rules file are not used.
+fn parse_rule_selector(s: &str) -> Result<Option<Selector>, Box<dyn Error>> {
+    if s.trim().is_empty() {
+        return Ok(None);
+    }
+    match Selector::parse(s) {
+        Ok(sel) => Ok(Some(sel)),
+        Err(_) => {
+            let msg = format!("🔥 Can't parse the selector: '{}'", s);
+            Err(Box::new(ToolError::Exit(msg)))
+        }
+    }
+}
+
+/// Read an EPUB dictionary which was generated by this tool (or a compatible one), and recover the
+/// `DictWordMarkdown` entries from the `entries-NN.xhtml` pages.
+///
+/// Returns the metadata found in `package.opf`.
+pub fn process_epub_entries(
+    epub_path: &PathBuf,
+    dict_label: &Option<String>,
+    dict: &mut Dictionary,
+) -> Result<DictMetadata, Box<dyn Error>> {
+    info! {"=== Begin processing {:?} ===", epub_path};
+
+    let file = File::open(epub_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    // The OPF path is declared in container.xml, but fall back to the path this tool uses.
+    let opf_path = match read_zip_entry(&mut archive, "META-INF/container.xml") {
+        Ok(s) => {
+            let re = Regex::new(r#"full-path="([^"]+)""#).unwrap();
+            match re.captures(&s) {
+                Some(caps) => caps[1].to_string(),
+                None => "OEBPS/package.opf".to_string(),
+            }
+        }
+        Err(_) => "OEBPS/package.opf".to_string(),
+    };
+
+    // Hrefs in the OPF are relative to its folder.
+    let opf_dir = match opf_path.rfind('/') {
+        Some(n) => opf_path[..=n].to_string(),
+        None => "".to_string(),
+    };
+
+    let opf = read_zip_entry(&mut archive, &opf_path)?;
+    let meta = epub_opf_to_metadata(&opf);
+
+    // Collect the entries pages in spine order.
+
+    let re_item = Regex::new(r#"<item [^>]*?id="([^"]+)"[^>]*?href="([^"]+)"[^>]*>"#).unwrap();
+    let re_itemref = Regex::new(r#"<itemref [^>]*?idref="([^"]+)"[^>]*>"#).unwrap();
+    let re_entries = Regex::new(r"^entries-[0-9]+\.xhtml$").unwrap();
+
+    let mut manifest: BTreeMap<String, String> = BTreeMap::new();
+    for caps in re_item.captures_iter(&opf) {
+        manifest.insert(caps[1].to_string(), caps[2].to_string());
+    }
+
+    let mut pages: Vec<(String, String)> = Vec::new();
+    for caps in re_itemref.captures_iter(&opf) {
+        if let Some(href) = manifest.get(&caps[1]) {
+            if re_entries.is_match(href) {
+                let content = read_zip_entry(&mut archive, &format!("{}{}", opf_dir, href))?;
+                pages.push((href.clone(), content));
+            }
+        }
+    }
+
+    if pages.is_empty() {
+        let msg = "🔥 No entries-NN.xhtml pages found in the EPUB spine.".to_string();
+        return Err(Box::new(ToolError::Exit(msg)));
+    }
+
+    info!("Found {} entries pages.", pages.len());
+
+    // First pass: map the heading ids to the words, so that links between entries can be
+    // reconstructed as /define/ links.
+
+    let h4_selector = Selector::parse("h4[id]").unwrap();
+
+    let mut url_to_word: BTreeMap<String, String> = BTreeMap::new();
+    for (_, content) in pages.iter() {
+        let doc = Html::parse_document(content);
+        for h4 in doc.select(&h4_selector) {
+            let id = h4.value().attr("id").unwrap().to_string();
+            let word = epub_heading_to_word(&h4.text().collect::<String>());
+            url_to_word.insert(id, word);
+        }
+    }
+
+    // Second pass: parse the entries.
+
+    let re_href = Regex::new(r#"href="(?:entries-[0-9]+\.xhtml)?#([^"]+)""#).unwrap();
+    let re_list = Regex::new(r"^(Also written as|Synonyms|Antonyms|See also):").unwrap();
+
+    for (href, content) in pages.iter() {
+        info!("Processing: {}", href);
+
+        // Intra-book anchors become /define/ links.
+        let content = re_href.replace_all(content, |caps: &regex::Captures| {
+            match url_to_word.get(&caps[1]) {
+                Some(w) => format!("href=\"/define/{}\"", w),
+                None => caps[0].to_string(),
+            }
+        }).to_string();
+
+        let doc = Html::parse_document(&content);
+
+        for h4 in doc.select(&h4_selector) {
+            let word = epub_heading_to_word(&h4.text().collect::<String>());
+
+            let mut label = match dict_label {
+                Some(x) => x.clone(),
+                None => "".to_string(),
+            };
+            let mut phonetic = String::new();
+            let mut transliteration = String::new();
+            let mut also_written_as: Vec<String> = Vec::new();
+            let mut synonyms: Vec<String> = Vec::new();
+            let mut antonyms: Vec<String> = Vec::new();
+            let mut see_also: Vec<String> = Vec::new();
+            let mut definition_html = String::new();
+
+            for node in h4.next_siblings() {
+                let el = match ElementRef::wrap(node) {
+                    Some(x) => x,
+                    None => continue,
+                };
+
+                let tag = el.value().name();
+                if tag == "h1" || tag == "h2" || tag == "h4" {
+                    break;
+                }
+
+                let text = el.text().collect::<String>().trim().to_string();
+
+                // [ncped]
+                if tag == "p" && text.starts_with('[') && text.ends_with(']') && definition_html.is_empty() && !text.contains(' ') {
+                    if label.is_empty() {
+                        label = text.trim_start_matches('[').trim_end_matches(']').to_string();
+                    }
+                    continue;
+                }
+
+                // <p><span>phonetic</span> | <span>transliteration</span></p>
+                if tag == "p" && definition_html.is_empty() && epub_is_span_paragraph(&el) {
+                    let velthuis = pali::to_velthuis(&word);
+                    let parts: Vec<String> = text.split(" | ")
+                        .map(|i| i.trim().to_string())
+                        .filter(|i| !i.is_empty() && *i != velthuis)
+                        .collect();
+                    if let Some(x) = parts.get(0) {
+                        phonetic = x.clone();
+                    }
+                    if let Some(x) = parts.get(1) {
+                        transliteration = x.clone();
+                    }
+                    continue;
+                }
+
+                if tag == "p" {
+                    if let Some(caps) = re_list.captures(&text) {
+                        let items: Vec<String> = text[caps[0].len()..]
+                            .split(',')
+                            .map(|i| i.trim().to_string())
+                            .filter(|i| !i.is_empty())
+                            .collect();
+
+                        match &caps[1] {
+                            "Also written as" => also_written_as = items,
+                            "Synonyms" => synonyms = items,
+                            "Antonyms" => antonyms = items,
+                            _ => see_also = items,
+                        }
+                        continue;
+                    }
+                }
+
+                definition_html.push_str(&el.html());
+                definition_html.push('\n');
+            }
+
+            let mut header = new_word_header(&word, &label);
+            header.phonetic = phonetic;
+            header.transliteration = transliteration;
+            header.also_written_as = also_written_as;
+            header.synonyms = synonyms;
+            header.antonyms = antonyms;
+            header.see_also = see_also;
+
+            let new_word = DictWordMarkdown {
+                word_header: header,
+                definition_md: html_to_markdown(&definition_html).trim().to_string(),
+            };
+
+            dict.add_word(new_word);
+        }
+    }
+
+    Ok(meta)
+}
+
+fn read_zip_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<String, Box<dyn Error>> {
+    let mut f = archive.by_name(name)?;
+    let mut s = String::new();
+    f.read_to_string(&mut s)?;
+    Ok(s)
+}
+
+fn epub_opf_to_metadata(opf: &str) -> DictMetadata {
+    let get_tag = |tag: &str| -> String {
+        let re = Regex::new(&format!(r"<dc:{}[^>]*>([^<]*)</dc:{}>", tag, tag)).unwrap();
+        match re.captures(opf) {
+            Some(caps) => caps[1].trim().replace("&amp;", "&"),
+            None => "".to_string(),
+        }
+    };
+
+    let mut meta = DictMetadata::default();
+
+    let title = get_tag("title");
+    if !title.is_empty() {
+        meta.title = title;
+    }
+    meta.description = get_tag("description");
+    meta.creator = get_tag("creator");
+    meta.source = get_tag("source");
+
+    let book_id = get_tag("identifier");
+    if !book_id.is_empty() {
+        meta.book_id = book_id;
+    }
+
+    let language = get_tag("language");
+    if !language.is_empty() {
+        meta.target_language = language;
+    }
+
+    // The MOBI x-metadata has the source language too.
+    let re_in_lang = Regex::new(r"<DictionaryInLanguage>([^<]*)</DictionaryInLanguage>").unwrap();
+    if let Some(caps) = re_in_lang.captures(opf) {
+        if !caps[1].trim().is_empty() {
+            meta.source_language = caps[1].trim().to_string();
+        }
+    }
+
+    meta
+}
+
+/// The heading may be rendered with `word_title` as 'velthuis - word', or with the display scripts
+/// before the word. The word is the last part.
+fn epub_heading_to_word(heading: &str) -> String {
+    let s = heading.trim();
+    match s.rfind(" - ") {
+        Some(n) => s[n + 3..].trim().to_string(),
+        None => s.to_string(),
+    }
+}
+
+fn epub_is_span_paragraph(el: &ElementRef<'_>) -> bool {
+    let mut has_span = false;
+    for child in el.children() {
+        match ElementRef::wrap(child) {
+            Some(x) => {
+                if x.value().name() == "span" {
+                    has_span = true;
+                } else {
+                    return false;
+                }
+            }
+            None => {
+                if let Some(t) = child.value().as_text() {
+                    if !t.trim().is_empty() && t.trim() != "|" {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    has_span
+}
+
+/// A word header with only the word and dict_label filled in.
+pub fn new_word_header(word: &str, dict_label: &str) -> DictWordHeader {
+    DictWordHeader {
+        word: word.to_string(),
+        meaning_order: 1,
+        word_nom_sg: "".to_string(),
+        is_root: false,
+        dict_label: dict_label.to_string(),
+
+        inflections: Vec::new(),
+        phonetic: "".to_string(),
+        transliteration: "".to_string(),
+
+        summary: "".to_string(),
+
+        synonyms: Vec::new(),
+        antonyms: Vec::new(),
+        homonyms: Vec::new(),
+        also_written_as: Vec::new(),
+        see_also: Vec::new(),
+        comment: "".to_string(),
+
+        grammar_roots: Vec::new(),
+        grammar_prefix_and_root: "".to_string(),
+
+        grammar_construction: "".to_string(),
+        grammar_base_construction: "".to_string(),
+        grammar_compound_type: "".to_string(),
+        grammar_compound_construction: "".to_string(),
+
+        grammar_comment: "".to_string(),
+        grammar_speech: "".to_string(),
+        grammar_case: "".to_string(),
+        grammar_num: "".to_string(),
+        grammar_gender: "".to_string(),
+        grammar_person: "".to_string(),
+        grammar_voice: "".to_string(),
+        grammar_object: "".to_string(),
+        grammar_transitive: "".to_string(),
+        grammar_negative: "".to_string(),
+        grammar_verb: "".to_string(),
+
+        examples: Vec::new(),
+
+        root_language: "".to_string(),
+        root_groups: Vec::new(),
+        root_sign: "".to_string(),
+        root_numbered_group: "".to_string(),
+
+        // dict.add_word will increment meaning_order if needed
+        url_id: DictWordMarkdown::gen_url_id(word, dict_label, 1),
     }
 }
 
@@ -1761,7 +2881,7 @@ pub fn process_markdown(source_path: &PathBuf, dict: &mut Dictionary) -> Result<
 
     let (meta_txt, entries_txt) = split_metadata_and_entries(&source_path)?;
 
-    dict.meta = parse_str_to_metadata(&meta_txt)?;
+    dict.add_source(parse_str_to_metadata(&meta_txt)?);
 
     let entries: Vec<Result<DictWordMarkdown, Box<dyn Error>>> = entries_txt
         .split("``` toml")
@@ -1861,7 +2981,7 @@ pub fn process_xlsx(source_path: &PathBuf, dict: &mut Dictionary) -> Result<(),
                 meta.created_date_human = Utc::now().to_rfc2822(); // Fri, 28 Nov 2014 12:00:09 +0000
                 meta.created_date_opf = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
 
-                dict.meta = meta;
+                dict.add_source(meta);
             },
             None => {
                 let msg = "Expected at least one row in the Metadata sheet.".to_string();
@@ -1987,3 +3107,84 @@ fn html_to_plain(html: &str) -> String {
     plain
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epub_metadata() {
+        let opf = r#"<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <dc:title>Pali-English Dictionary</dc:title>
+  <dc:creator opf:role="aut">Rhys Davids &amp; Stede</dc:creator>
+  <dc:identifier id="BookId">PTS-PED</dc:identifier>
+  <dc:language>en</dc:language>
+  <x-metadata>
+    <DictionaryInLanguage>pli</DictionaryInLanguage>
+    <DictionaryOutLanguage>en</DictionaryOutLanguage>
+  </x-metadata>
+</metadata>"#;
+
+        let meta = epub_opf_to_metadata(opf);
+        assert_eq!(meta.title, "Pali-English Dictionary");
+        assert_eq!(meta.creator, "Rhys Davids & Stede");
+        assert_eq!(meta.book_id, "PTS-PED");
+        assert_eq!(meta.target_language, "en");
+        assert_eq!(meta.source_language, "pli");
+        assert_eq!(meta.description, "");
+    }
+
+    #[test]
+    fn epub_metadata_defaults() {
+        let meta = epub_opf_to_metadata("<metadata><dc:title> </dc:title></metadata>");
+        let default = DictMetadata::default();
+        assert_eq!(meta.title, default.title);
+        assert_eq!(meta.book_id, default.book_id);
+        assert_eq!(meta.target_language, default.target_language);
+        assert_eq!(meta.source_language, "");
+    }
+
+    #[test]
+    fn epub_heading_word() {
+        assert_eq!(epub_heading_to_word(" dhamma "), "dhamma");
+        assert_eq!(epub_heading_to_word("dhamma - dhamma"), "dhamma");
+        assert_eq!(epub_heading_to_word("ධම්ම - dhamma"), "dhamma");
+        assert_eq!(epub_heading_to_word("a.m.sa - aṃsa"), "aṃsa");
+        assert_eq!(epub_heading_to_word("kusala-akusala"), "kusala-akusala");
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let re = glob_to_regex("*.html").unwrap();
+        assert!(re.is_match("entries.html"));
+        assert!(!re.is_match("text/entries.html"));
+        assert!(!re.is_match("entries.xhtml"));
+
+        let re = glob_to_regex("**/entry-?.html").unwrap();
+        assert!(re.is_match("entry-a.html"));
+        assert!(re.is_match("a/b/entry-1.html"));
+        assert!(!re.is_match("a/entry-10.html"));
+
+        let re = glob_to_regex("text/**").unwrap();
+        assert!(re.is_match("text/a/b.html"));
+        assert!(!re.is_match("texts/a.html"));
+
+        // Regex characters are literal.
+        let re = glob_to_regex("a+(b).html").unwrap();
+        assert!(re.is_match("a+(b).html"));
+        assert!(!re.is_match("aa(b).html"));
+    }
+
+    #[test]
+    fn rule_selectors() {
+        assert!(parse_rule_selector("").unwrap().is_none());
+        assert!(parse_rule_selector("  ").unwrap().is_none());
+
+        let sel = parse_rule_selector("div.entry > h3").unwrap().unwrap();
+        let html = Html::parse_fragment(r#"<div class="entry"><h3>dhamma</h3></div><h3>sacca</h3>"#);
+        let found: Vec<String> = html.select(&sel).map(|x| x.inner_html()).collect();
+        assert_eq!(found, vec!["dhamma"]);
+
+        assert!(parse_rule_selector("div[").is_err());
+    }
+}
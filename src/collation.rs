@@ -0,0 +1,296 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use unicode_normalization::UnicodeNormalization;
+
+use pali_dict_core::dict_word::DictWordMarkdown;
+use pali_dict_core::letter_groups;
+
+/// The Pali alphabet in the traditional order.
+const PALI_ALPHABET: &str = "a ā i ī u ū e o ṃ k kh g gh ṅ c ch j jh ñ ṭ ṭh ḍ ḍh ṇ t th d dh n p ph b bh m y r l v s h ḷ";
+
+/// The Sanskrit alphabet in the order of the dictionaries (Monier-Williams).
+const SANSKRIT_ALPHABET: &str = "a ā i ī u ū ṛ ṝ ḷ ḹ e ai o au ṃ ḥ k kh g gh ṅ c ch j jh ñ ṭ ṭh ḍ ḍh ṇ t th d dh n p ph b bh m y r l v ś ṣ s h";
+
+/// Letters which are not in the alphabet are sorted after it, by their code point.
+const UNKNOWN_LETTER: u32 = 1000;
+
+/// The sort order and the letter groups of the headwords.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collation {
+    Pali,
+    Sanskrit,
+    /// An approximation of the Unicode root collation: the base letters first, then the accents,
+    /// then the case. For English and other Roman script dictionaries.
+    Unicode,
+    /// The letters of an alphabet, in order. A letter may have more characters, such as `kh`.
+    Custom(Vec<String>),
+}
+
+impl Collation {
+    /// The collation of `DictMetadata.collation`: `pali` (the default), `sanskrit`, `unicode`, or
+    /// `custom` with the letters in `collation_alphabet` separated by spaces.
+    pub fn from_meta(collation: &str, alphabet: &str) -> Collation {
+        match collation.trim().to_lowercase().as_str() {
+            "" | "pali" => Collation::Pali,
+            "sanskrit" => Collation::Sanskrit,
+            "unicode" => Collation::Unicode,
+            "custom" => {
+                let letters = parse_alphabet(alphabet);
+                if letters.is_empty() {
+                    warn!("The custom collation has no collation_alphabet, using unicode.");
+                    Collation::Unicode
+                } else {
+                    Collation::Custom(letters)
+                }
+            }
+            x => {
+                warn!("Unknown collation: {}, using pali.", x);
+                Collation::Pali
+            }
+        }
+    }
+
+    fn alphabet(&self) -> Option<&[String]> {
+        lazy_static! {
+            static ref PALI: Vec<String> = parse_alphabet(PALI_ALPHABET);
+            static ref SANSKRIT: Vec<String> = parse_alphabet(SANSKRIT_ALPHABET);
+        }
+
+        match self {
+            Collation::Pali => Some(&PALI),
+            Collation::Sanskrit => Some(&SANSKRIT),
+            Collation::Unicode => None,
+            Collation::Custom(letters) => Some(letters),
+        }
+    }
+
+    /// The key to sort a word with. Punctuation, spaces and hyphens are ignored.
+    pub fn sort_key(&self, word: &str) -> Vec<u32> {
+        match self.alphabet() {
+            Some(letters) => letters_of(word, letters).into_iter().map(|(_, n)| n).collect(),
+            None => unicode_key(word),
+        }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.sort_key(a).cmp(&self.sort_key(b)).then_with(|| a.cmp(b))
+    }
+
+    /// The first letter of a word, which is the title of its letter group.
+    pub fn group_letter(&self, word: &str) -> String {
+        match self.alphabet() {
+            Some(letters) => letters_of(word, letters)
+                .into_iter()
+                .next()
+                .map(|(x, _)| x)
+                .unwrap_or_default(),
+            None => word.nfd()
+                .find(|c| c.is_alphanumeric())
+                .map(|c| c.to_lowercase().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The entries split in groups by their first letter, as in the EPUB and MOBI `entries-NN.xhtml`
+/// files.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LetterGroup {
+    pub title: String,
+    /// The number in the file name, `entries-{:02}.xhtml`.
+    pub letter_index: usize,
+    pub group_letter: String,
+    pub dict_words: Vec<DictWordMarkdown>,
+}
+
+pub struct LetterGroups {
+    /// The map key is the letter index.
+    pub groups: BTreeMap<usize, LetterGroup>,
+    /// The link targets of the words: `entries-03.xhtml#kusala-ncped`
+    pub words_to_url: BTreeMap<String, String>,
+}
+
+impl LetterGroups {
+    /// Split the words in letter groups. The Pali groups are the ones of `pali_dict_core`, so that
+    /// the file names stay the same. With the other collations, the groups are numbered in order.
+    pub fn new_from_dict_words(dict_words: &[DictWordMarkdown], collation: &Collation) -> LetterGroups {
+        if let Collation::Pali = collation {
+            let x = letter_groups::LetterGroups::new_from_dict_words(dict_words);
+            return LetterGroups {
+                groups: x.groups.iter()
+                    .map(|(k, g)| (*k, LetterGroup {
+                        title: g.title.clone(),
+                        letter_index: g.letter_index,
+                        group_letter: g.group_letter.clone(),
+                        dict_words: g.dict_words.clone(),
+                    }))
+                    .collect(),
+                words_to_url: x.words_to_url,
+            };
+        }
+
+        let mut words: Vec<&DictWordMarkdown> = dict_words.iter().collect();
+        sort_dict_words(&mut words, collation);
+
+        let mut groups: BTreeMap<usize, LetterGroup> = BTreeMap::new();
+        let mut words_to_url: BTreeMap<String, String> = BTreeMap::new();
+
+        for w in words.into_iter() {
+            let letter = collation.group_letter(&w.word_header.word);
+
+            // The words are sorted, a new letter starts the next group.
+            let letter_index = match groups.values().last() {
+                Some(g) if g.group_letter == letter => g.letter_index,
+                Some(g) => g.letter_index + 1,
+                None => 0,
+            };
+
+            let group = groups.entry(letter_index).or_insert_with(|| LetterGroup {
+                title: "".to_string(),
+                letter_index,
+                group_letter: letter.clone(),
+                dict_words: Vec::new(),
+            });
+            group.dict_words.push(w.clone());
+
+            words_to_url
+                .entry(w.word_header.word.clone())
+                .or_insert_with(|| format!("entries-{:02}.xhtml#{}", letter_index, w.word_header.url_id));
+        }
+
+        LetterGroups {
+            groups,
+            words_to_url,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// Sort the entries by their headword, then by the meaning order.
+pub fn sort_dict_words(dict_words: &mut Vec<&DictWordMarkdown>, collation: &Collation) {
+    dict_words.sort_by_cached_key(|w| (
+        collation.sort_key(&w.word_header.word),
+        w.word_header.word.clone(),
+        w.word_header.meaning_order,
+        w.word_header.url_id.clone(),
+    ));
+}
+
+fn parse_alphabet(alphabet: &str) -> Vec<String> {
+    alphabet.split_whitespace()
+        .map(|x| x.nfc().collect::<String>().to_lowercase())
+        .collect()
+}
+
+/// The letters of a word with their number in the alphabet, the longest letter is matched first.
+fn letters_of(word: &str, letters: &[String]) -> Vec<(String, u32)> {
+    let word: String = word.nfc().collect::<String>().to_lowercase().replace('ṁ', "ṃ");
+    let mut res: Vec<(String, u32)> = Vec::new();
+    let mut rest: &str = &word;
+
+    while let Some(c) = rest.chars().next() {
+        let found = letters.iter()
+            .enumerate()
+            .filter(|(_, x)| rest.starts_with(x.as_str()))
+            .max_by_key(|(_, x)| x.len());
+
+        match found {
+            Some((n, x)) => {
+                res.push((x.clone(), n as u32 + 1));
+                rest = &rest[x.len()..];
+            }
+            None => {
+                if c.is_alphanumeric() {
+                    res.push((c.to_string(), UNKNOWN_LETTER + c as u32));
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    res
+}
+
+/// The base letters, the accents and the case, separated with 0.
+fn unicode_key(word: &str) -> Vec<u32> {
+    let mut base: Vec<u32> = Vec::new();
+    let mut accents: Vec<u32> = Vec::new();
+    let mut case: Vec<u32> = Vec::new();
+
+    for c in word.nfd() {
+        if c.is_alphanumeric() {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            base.push(lower as u32);
+            accents.push(1);
+            case.push(if c.is_uppercase() { 2 } else { 1 });
+        } else if is_combining_mark(c) {
+            if let Some(x) = accents.last_mut() {
+                *x += c as u32;
+            }
+        }
+    }
+
+    let mut res = base;
+    res.push(0);
+    res.extend(accents);
+    res.push(0);
+    res.extend(case);
+    res
+}
+
+fn is_combining_mark(c: char) -> bool {
+    ('\u{0300}'..='\u{036F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: &Collation, words: &[&str]) -> Vec<String> {
+        let mut words: Vec<String> = words.iter().map(|x| x.to_string()).collect();
+        words.sort_by(|a, b| collation.compare(a, b));
+        words
+    }
+
+    #[test]
+    fn pali_and_unicode_order() {
+        let words = ["kusala", "khandha", "ādi", "attha", "ṭhāna", "tathā", "ñāṇa"];
+
+        assert_eq!(sorted(&Collation::Pali, &words),
+                   vec!["attha", "ādi", "kusala", "khandha", "ñāṇa", "ṭhāna", "tathā"]);
+
+        assert_eq!(sorted(&Collation::Unicode, &words),
+                   vec!["ādi", "attha", "khandha", "kusala", "ñāṇa", "tathā", "ṭhāna"]);
+    }
+
+    #[test]
+    fn unicode_accents_and_case_after_the_letters() {
+        assert_eq!(Collation::Unicode.compare("buddha", "Buddha"), Ordering::Less);
+        assert_eq!(Collation::Unicode.compare("Buddha", "bodhi"), Ordering::Greater);
+        assert_eq!(Collation::Unicode.compare("resume", "résumé"), Ordering::Less);
+    }
+
+    #[test]
+    fn punctuation_is_ignored() {
+        assert_eq!(Collation::Pali.sort_key("kusala-kamma"), Collation::Pali.sort_key("kusalakamma"));
+    }
+
+    #[test]
+    fn group_letters() {
+        assert_eq!(Collation::Pali.group_letter("khandha"), "kh");
+        assert_eq!(Collation::Sanskrit.group_letter("śīla"), "ś");
+        assert_eq!(Collation::Unicode.group_letter("Ārāma"), "a");
+
+        let custom = Collation::from_meta("custom", "a b c ch d");
+        assert_eq!(custom.group_letter("chair"), "ch");
+        assert_eq!(custom.compare("cz", "ch"), Ordering::Less);
+    }
+}
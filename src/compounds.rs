@@ -0,0 +1,318 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::app;
+use crate::db_schema;
+use crate::deinflect;
+use crate::dictionary::{Dictionary, OutputFormat};
+
+/// The number of segmentations returned by `CompoundSplitter::split()`.
+pub const MAX_SEGMENTATIONS: usize = 5;
+
+/// Consonants inserted between the words of a compound or a sandhi: `punar-eva`, `idha-m-āhu`.
+const JOINERS: [char; 6] = ['m', 'r', 'y', 'v', 'd', 't'];
+
+/// Final vowels which are dropped before a word starting with a vowel: `ten-āha` = `tena āha`.
+const ELIDED: [char; 6] = ['a', 'ā', 'i', 'ī', 'u', 'o'];
+
+/// A part of a compound or sandhi form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundPart {
+    /// The part as written in the form: `cā`
+    pub text: String,
+    /// The dictionary word: `ca`
+    pub headword: String,
+    /// The consonant inserted after this part, `m` in `idha-m-āhu`.
+    pub joiner: Option<char>,
+}
+
+/// A proposed split of a word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segmentation {
+    pub parts: Vec<CompoundPart>,
+    /// Lower is better: the number of parts, and the sandhi rules used.
+    pub cost: usize,
+}
+
+impl Segmentation {
+    /// `ca + ahaṃ`
+    pub fn headwords(&self) -> String {
+        self.parts.iter().map(|x| x.headword.clone()).collect::<Vec<String>>().join(" + ")
+    }
+}
+
+/// Splits compounds and sandhi forms into the words of a dictionary.
+///
+/// The parts are the dictionary's headwords. Only the last part may be inflected, it is looked up
+/// with `deinflect()`. At the boundaries, these sandhi rules are tried:
+///
+/// - vowel coalescence: `cāhaṃ` = `ca + ahaṃ`, `upeti` = `upa + eti`
+/// - elision of a final vowel: `tenāha` = `tena + āha`
+/// - niggahīta assimilation: `evameva` = `evaṃ + eva`, `tañca` = `taṃ + ca`
+/// - inserted consonants: `punareva` = `puna + eva`, `idhamāhu` = `idha + āhu`
+pub struct CompoundSplitter {
+    words: BTreeSet<String>,
+    /// Word forms already looked up with `deinflect()`.
+    inflected: RefCell<BTreeMap<String, Option<String>>>,
+}
+
+type Candidates = Vec<(usize, Vec<CompoundPart>)>;
+
+/// A reading of a part at a boundary: (the word, next position, next prefix, joiner, cost of the rule)
+type Boundary = (String, usize, Option<char>, Option<char>, usize);
+
+impl CompoundSplitter {
+    pub fn new(words: &[String]) -> Self {
+        CompoundSplitter {
+            words: words.iter().map(|x| x.to_lowercase()).collect(),
+            inflected: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The best segmentations of a word with at least two parts, the best first.
+    pub fn split(&self, word: &str) -> Vec<Segmentation> {
+        let chars: Vec<char> = word.trim().to_lowercase().chars().collect();
+        if chars.len() < 4 {
+            return vec![];
+        }
+
+        let mut memo: BTreeMap<(usize, Option<char>), Candidates> = BTreeMap::new();
+        self.best(&chars, 0, None, &mut memo)
+            .into_iter()
+            .filter(|(_, parts)| parts.len() > 1)
+            .map(|(cost, parts)| Segmentation { parts, cost })
+            .collect()
+    }
+
+    /// The best segmentations of `chars[pos..]`. `prefix` is the initial vowel of the next part,
+    /// when it was merged with the final vowel of the previous part.
+    fn best(
+        &self,
+        chars: &[char],
+        pos: usize,
+        prefix: Option<char>,
+        memo: &mut BTreeMap<(usize, Option<char>), Candidates>,
+    ) -> Candidates {
+        if pos == chars.len() {
+            return if prefix.is_none() { vec![(0, vec![])] } else { vec![] };
+        }
+        if let Some(x) = memo.get(&(pos, prefix)) {
+            return x.clone();
+        }
+
+        let mut res: Candidates = Vec::new();
+
+        for end in (pos + 1)..=chars.len() {
+            let text: String = chars[pos..end].iter().collect();
+            let mut base: String = prefix.map(|c| c.to_string()).unwrap_or_default();
+            base.push_str(&text);
+
+            let mut options: Vec<Boundary> = Vec::new();
+
+            if end == chars.len() {
+                options.push((base.clone(), end, None, None, 0));
+            } else {
+                let next = chars[end];
+                let last = chars[end - 1];
+                let without_last: String = {
+                    let mut x = base.clone();
+                    x.pop();
+                    x
+                };
+
+                options.push((base.clone(), end, None, None, 0));
+
+                if is_vowel(next) && !is_vowel(last) {
+                    for v in ELIDED.iter() {
+                        options.push((format!("{}{}", base, v), end, None, None, 1));
+                    }
+                }
+
+                if end - pos > 1 {
+                    for (l, r) in coalesced_vowels(last).iter() {
+                        options.push((format!("{}{}", without_last, l), end, Some(*r), None, 1));
+                    }
+
+                    let nasal_before_consonant = "ṅñṇnm".contains(last) && !is_vowel(next);
+                    let nasal_before_vowel = (last == 'm' || last == 'd') && is_vowel(next);
+                    if nasal_before_consonant || nasal_before_vowel {
+                        options.push((format!("{}ṃ", without_last), end, None, None, 1));
+                    }
+                }
+
+                if JOINERS.contains(&next) && end + 1 < chars.len() && is_vowel(chars[end + 1]) && is_vowel(last) {
+                    options.push((base.clone(), end + 1, None, Some(next), 1));
+                }
+            }
+
+            for (word, next_pos, next_prefix, joiner, rule_cost) in options.into_iter() {
+                let headword = match self.headword(&word, next_pos == chars.len()) {
+                    Some(x) => x,
+                    None => continue,
+                };
+
+                for (cost, rest) in self.best(chars, next_pos, next_prefix, memo).into_iter() {
+                    let mut parts = vec![CompoundPart {
+                        text: text.clone(),
+                        headword: headword.clone(),
+                        joiner,
+                    }];
+                    parts.extend(rest);
+                    res.push((10 + rule_cost + cost, parts));
+                }
+            }
+        }
+
+        // Fewer parts and rules first, then the longer first part.
+        res.sort_by(|a, b| a.0.cmp(&b.0).then(b.1[0].text.len().cmp(&a.1[0].text.len())));
+        let mut seen: BTreeSet<Vec<String>> = BTreeSet::new();
+        res.retain(|(_, parts)| seen.insert(parts.iter().map(|x| x.headword.clone()).collect()));
+        res.truncate(MAX_SEGMENTATIONS);
+
+        memo.insert((pos, prefix), res.clone());
+        res
+    }
+
+    /// The dictionary word of a part. The last part can be an inflected form.
+    fn headword(&self, word: &str, is_last: bool) -> Option<String> {
+        if word.chars().count() < 2 {
+            return None;
+        }
+        if self.words.contains(word) {
+            return Some(word.to_string());
+        }
+        if !is_last {
+            return None;
+        }
+
+        if let Some(x) = self.inflected.borrow().get(word) {
+            return x.clone();
+        }
+
+        let res = deinflect::deinflect(word)
+            .into_iter()
+            .map(|x| x.headword)
+            .find(|x| self.words.contains(x));
+
+        self.inflected.borrow_mut().insert(word.to_string(), res.clone());
+        res
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    "aāiīuūeo".contains(c)
+}
+
+/// The final and initial vowels which give a long vowel in sandhi.
+fn coalesced_vowels(c: char) -> Vec<(char, char)> {
+    match c {
+        'ā' => vec![('a', 'a'), ('a', 'ā'), ('ā', 'a'), ('ā', 'ā')],
+        'ī' => vec![('i', 'i'), ('i', 'ī'), ('ī', 'i')],
+        'ū' => vec![('u', 'u'), ('u', 'ū'), ('ū', 'u')],
+        'e' => vec![('a', 'i'), ('a', 'e'), ('ā', 'i')],
+        'o' => vec![('a', 'u'), ('a', 'o'), ('ā', 'u')],
+        _ => vec![],
+    }
+}
+
+/// Print the segmentations of a word, for the `split_compound` command. The source is an Sqlite
+/// database or a Markdown dictionary.
+pub fn split_compound(source_path: &Path, word: &str) -> Result<(), Box<dyn Error>> {
+    let is_db = match source_path.extension().and_then(|x| x.to_str()) {
+        Some(ext) => ext == "sqlite" || ext == "sqlite3" || ext == "db",
+        None => false,
+    };
+
+    let words: Vec<String> = if is_db {
+        use db_schema::dict_words;
+
+        let conn = SqliteConnection::establish(source_path.to_str().unwrap())
+            .expect("Error connecting to database.");
+
+        dict_words::table
+            .select(dict_words::word)
+            .load(&conn)?
+    } else {
+        let source_dir = source_path.parent().unwrap_or_else(|| Path::new("."));
+        let mut dict = Dictionary::new(OutputFormat::Epub, false, source_dir, source_path, None);
+        app::process_markdown_list(vec![PathBuf::from(source_path)], &mut dict)?;
+        dict.valid_words.clone()
+    };
+
+    let splitter = CompoundSplitter::new(&words);
+    let res = splitter.split(word);
+
+    if res.is_empty() {
+        println!("No segmentation found: {}", word);
+        return Ok(());
+    }
+
+    for s in res.iter() {
+        println!("{}", s.headwords());
+        for p in s.parts.iter() {
+            match p.joiner {
+                Some(j) => println!("  {} -> {} (-{}-)", p.text, p.headword, j),
+                None => println!("  {} -> {}", p.text, p.headword),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn splitter() -> CompoundSplitter {
+        let words: Vec<String> = ["ca", "ahaṃ", "tena", "āha", "evaṃ", "eva", "taṃ", "puna", "idha", "āhu"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        CompoundSplitter::new(&words)
+    }
+
+    fn best(word: &str) -> String {
+        splitter().split(word).first().map(|x| x.headwords()).unwrap_or_default()
+    }
+
+    #[test]
+    fn vowel_coalescence() {
+        assert_eq!(best("cāhaṃ"), "ca + ahaṃ");
+    }
+
+    #[test]
+    fn elision() {
+        assert_eq!(best("tenāha"), "tena + āha");
+    }
+
+    #[test]
+    fn niggahita_assimilation() {
+        assert_eq!(best("evameva"), "evaṃ + eva");
+        assert_eq!(best("tañca"), "taṃ + ca");
+    }
+
+    #[test]
+    fn inserted_consonants() {
+        assert_eq!(best("punareva"), "puna + eva");
+
+        let res = splitter().split("idhamāhu");
+        assert_eq!(res[0].headwords(), "idha + āhu");
+        assert_eq!(res[0].parts[0].joiner, Some('m'));
+    }
+
+    #[test]
+    fn single_words_are_not_split() {
+        assert!(splitter().split("tena").is_empty());
+        assert!(splitter().split("xyzxyz").is_empty());
+    }
+}
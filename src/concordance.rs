@@ -0,0 +1,361 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use regex::Regex;
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::db_schema;
+use crate::db_models::DbPtsConcordance;
+use crate::error::ToolError;
+use crate::sutta_refs::{self, roman_to_int, CitationStyle};
+
+/// The edition of the rows from the `volpage` in sc-data's `structure/sutta.json`. They have the
+/// first page of each sutta, without segments.
+pub const EDITION_VOLPAGE: &str = "volpage";
+
+/// A PTS volume and page: `MN i 23`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PtsRef {
+    /// dn, mn, sn, an, vin
+    pub collection: String,
+    pub volume: i32,
+    pub page: i32,
+}
+
+impl PtsRef {
+    /// Parse `MN i 23`, `M I 23`, `D.ii.45`, `Vin iii 5`. Text after the page is ignored, so that
+    /// `volpage` values with ranges (`SN i 1–2`) give their first page.
+    pub fn parse(s: &str) -> Option<PtsRef> {
+        lazy_static! {
+            static ref RE_PTS: Regex = Regex::new(
+                r"^(?P<col>[A-Za-z]+)(?:\. ?| )(?P<vol>[ivxIVX]+)(?:\. ?|, ?| )(?P<page>[0-9]+)").unwrap();
+        }
+
+        let caps = RE_PTS.captures(s.trim())?;
+        let collection = match &caps["col"] {
+            "D" | "DN" => "dn".to_string(),
+            "M" | "MN" => "mn".to_string(),
+            "S" | "SN" => "sn".to_string(),
+            "A" | "AN" => "an".to_string(),
+            x => x.to_lowercase(),
+        };
+
+        Some(PtsRef {
+            collection,
+            volume: roman_to_int(&caps["vol"].to_uppercase())? as i32,
+            page: caps["page"].parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for PtsRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", collection_acronym(&self.collection), int_to_roman(self.volume), self.page)
+    }
+}
+
+/// A PTS page marker from the comment of a PO message, such as
+/// `<a class="pts1ed" id="pts1ed4.12"></a>` or `<a class="pts" id="pts23"></a>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PtsMarker {
+    /// pts1ed, pts2ed, pts
+    pub edition: String,
+    /// The volume, if the marker id has it.
+    pub volume: Option<i32>,
+    pub page: i32,
+}
+
+/// The `pts-cs` markers are skipped, their ids are chapter and section numbers (`pts-cs2.2.1`),
+/// not pages.
+pub fn parse_pts_markers(comment: &str) -> Vec<PtsMarker> {
+    lazy_static! {
+        static ref RE_TAG: Regex = Regex::new(r"<a [^>]*>").unwrap();
+        static ref RE_CLASS: Regex = Regex::new(r#"class="(?P<class>pts-cs|pts1ed|pts2ed|pts)""#).unwrap();
+        static ref RE_ID: Regex = Regex::new(r#"id="(?P<id>[^"]*)""#).unwrap();
+        // 1.23 or 23 at the end of the id
+        static ref RE_VOL_PAGE: Regex = Regex::new(r"(?:(?P<vol>[0-9]+)\.)?(?P<page>[0-9]+)$").unwrap();
+    }
+
+    let mut markers: Vec<PtsMarker> = Vec::new();
+
+    for tag in RE_TAG.find_iter(comment) {
+        let edition = match RE_CLASS.captures(tag.as_str()) {
+            Some(caps) if &caps["class"] != "pts-cs" => caps["class"].to_string(),
+            _ => continue,
+        };
+        let id = match RE_ID.captures(tag.as_str()) {
+            Some(caps) => caps["id"].to_string(),
+            None => continue,
+        };
+        let caps = match RE_VOL_PAGE.captures(&id) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        markers.push(PtsMarker {
+            edition,
+            volume: caps.name("vol").and_then(|x| x.as_str().parse().ok()),
+            page: caps["page"].parse().unwrap_or(0),
+        });
+    }
+
+    markers
+}
+
+/// The PTS page markers of a segment in a bilara-data reference file, from the comma separated
+/// ids, such as `bj7.1, pts-cs1.1, pts-vp-pli1ed1.1, pts-vp-pli2ed1.1`. The `pts-vp-pli1ed` ids are
+/// the pages of the first edition, `pts-vp-pli2ed` of the second, and `pts-vp-pli` without the
+/// edition are `pts` markers.
+pub fn parse_bilara_pts_references(references: &str) -> Vec<PtsMarker> {
+    lazy_static! {
+        static ref RE_PTS_VP: Regex = Regex::new(
+            r"^pts-vp-pli(?:(?P<ed>[12])ed)?(?:(?P<vol>[0-9]+)\.)?(?P<page>[0-9]+)$").unwrap();
+    }
+
+    references.split(',')
+        .filter_map(|x| RE_PTS_VP.captures(x.trim()))
+        .map(|caps| PtsMarker {
+            edition: match caps.name("ed") {
+                Some(ed) => format!("pts{}ed", ed.as_str()),
+                None => "pts".to_string(),
+            },
+            volume: caps.name("vol").and_then(|x| x.as_str().parse().ok()),
+            page: caps["page"].parse().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// A reference given to `resolve_reference`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    /// MN i 23
+    Pts(PtsRef),
+    /// mn3, or mn3:4.2 with the segment id
+    Sc { sutta_uid: String, segment_id: Option<String> },
+}
+
+/// Parse a PTS reference (`MN i 23`), a SuttaCentral uid (`mn3`, `mn3:4.2`) or a sutta number
+/// (`MN 3`, `AN 4.67`).
+pub fn parse_reference(s: &str) -> Option<Reference> {
+    lazy_static! {
+        static ref RE_SC: Regex = Regex::new(
+            r"^(?P<uid>[a-z][a-z-]*[0-9][0-9\.-]*)(?::(?P<seg>[0-9][0-9\.]*))?$").unwrap();
+    }
+
+    let s = s.trim();
+
+    if let Some(caps) = RE_SC.captures(s) {
+        return Some(Reference::Sc {
+            sutta_uid: caps["uid"].to_string(),
+            segment_id: caps.name("seg").map(|_| s.to_string()),
+        });
+    }
+
+    if let Some(x) = PtsRef::parse(s) {
+        return Some(Reference::Pts(x));
+    }
+
+    let refs = sutta_refs::find_sutta_refs(s, &[CitationStyle::SuttaNumber]);
+    match refs.first().and_then(|r| r.sutta_uid.clone()) {
+        Some(uid) => Some(Reference::Sc { sutta_uid: uid, segment_id: None }),
+        None => None,
+    }
+}
+
+/// The SuttaCentral locations of a PTS page: for each edition, the page on or before it. The
+/// `volpage` row gives the sutta which starts on or before the page, the marker rows give the
+/// segment range of the page.
+pub fn pts_to_sc(conn: &SqliteConnection, pts: &PtsRef) -> Vec<DbPtsConcordance> {
+    use db_schema::pts_concordance::dsl::*;
+
+    let items = match pts_concordance
+        .filter(collection.eq(&pts.collection))
+        .filter(volume.eq(pts.volume))
+        .filter(page.le(pts.page))
+        .order((page.desc(), id.asc()))
+        .load::<DbPtsConcordance>(conn)
+    {
+        Ok(x) => x,
+        Err(_) => return vec![],
+    };
+
+    let mut res: Vec<DbPtsConcordance> = Vec::new();
+    for i in items.into_iter() {
+        if !res.iter().any(|r| r.edition == i.edition) {
+            res.push(i);
+        }
+    }
+
+    res.sort_by(|a, b| edition_order(&a.edition).cmp(&edition_order(&b.edition)));
+    res
+}
+
+/// The PTS pages of a sutta, or the pages which contain a segment. A page can start in an earlier
+/// sutta of the same text, so segments are looked up by their order in the text.
+pub fn sc_to_pts(conn: &SqliteConnection, text_sutta_uid: &str, segment_id: Option<&str>) -> Vec<DbPtsConcordance> {
+    use db_schema::pts_concordance::dsl::*;
+
+    let mut res = match pts_concordance
+        .filter(sutta_uid.eq(text_sutta_uid))
+        .order(id.asc())
+        .load::<DbPtsConcordance>(conn)
+    {
+        Ok(x) => x,
+        Err(_) => return vec![],
+    };
+
+    if let Some(seg) = segment_id {
+        let orders: Vec<(String, i32)> = {
+            use db_schema::text_segments;
+            text_segments::table
+                .filter(text_segments::segment_id.eq(seg))
+                .select((text_segments::root_text_uid, text_segments::segment_order))
+                .load(conn)
+                .unwrap_or_default()
+        };
+
+        res.retain(|i| i.edition == EDITION_VOLPAGE);
+
+        for (text_uid, order) in orders.iter() {
+            let items = pts_concordance
+                .filter(root_text_uid.eq(text_uid))
+                .filter(segment_start_order.le(order))
+                .filter(segment_end_order.ge(order))
+                .order(id.asc())
+                .load::<DbPtsConcordance>(conn)
+                .unwrap_or_default();
+            res.extend(items);
+        }
+    }
+
+    res.sort_by(|a, b| {
+        edition_order(&a.edition).cmp(&edition_order(&b.edition))
+            .then((a.volume, a.page).cmp(&(b.volume, b.page)))
+    });
+    res
+}
+
+/// `MN i 23`
+pub fn format_pts(row: &DbPtsConcordance) -> String {
+    PtsRef {
+        collection: row.collection.clone(),
+        volume: row.volume,
+        page: row.page,
+    }.to_string()
+}
+
+/// `mn3:4.2–mn3:6.1 (pts1ed, mn/3/pli/ms)` or `mn3 (volpage)`
+pub fn format_sc(row: &DbPtsConcordance) -> String {
+    if row.segment_start.is_empty() {
+        format!("{} ({})", row.sutta_uid, row.edition)
+    } else if row.segment_start == row.segment_end {
+        format!("{} ({}, {})", row.segment_start, row.edition, row.root_text_uid)
+    } else {
+        format!("{}–{} ({}, {})", row.segment_start, row.segment_end, row.edition, row.root_text_uid)
+    }
+}
+
+fn edition_order(edition: &str) -> usize {
+    match edition {
+        EDITION_VOLPAGE => 0,
+        "pts1ed" => 1,
+        "pts2ed" => 2,
+        _ => 3,
+    }
+}
+
+fn collection_acronym(col: &str) -> String {
+    match col {
+        "vin" => "Vin".to_string(),
+        x => x.to_uppercase(),
+    }
+}
+
+/// Lowercase roman numeral, as in the PTS references.
+fn int_to_roman(n: i32) -> String {
+    let numerals = [(50, "l"), (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i")];
+    let mut n = n;
+    let mut res = String::new();
+    for (value, s) in numerals.iter() {
+        while n >= *value {
+            res.push_str(s);
+            n -= value;
+        }
+    }
+    res
+}
+
+/// Print the other citations of a reference, for the `resolve_reference` command.
+pub fn resolve_reference(sqlite_db_path: &Path, reference: &str) -> Result<(), Box<dyn Error>> {
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
+
+    let rows = match parse_reference(reference) {
+        Some(Reference::Pts(pts)) => {
+            println!("{}", pts);
+            let rows = pts_to_sc(&conn, &pts);
+            for r in rows.iter() {
+                println!("  {}: {}", format_pts(r), format_sc(r));
+            }
+            rows
+        }
+
+        Some(Reference::Sc { sutta_uid, segment_id }) => {
+            println!("{}", segment_id.as_ref().unwrap_or(&sutta_uid));
+            let rows = sc_to_pts(&conn, &sutta_uid, segment_id.as_deref());
+            for r in rows.iter() {
+                println!("  {}: {}", format_sc(r), format_pts(r));
+            }
+            rows
+        }
+
+        None => {
+            let msg = format!("🔥 Can't parse the reference: {}", reference);
+            return Err(Box::new(ToolError::Exit(msg)));
+        }
+    };
+
+    if rows.is_empty() {
+        println!("  Not found.");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(edition: &str, volume: Option<i32>, page: i32) -> PtsMarker {
+        PtsMarker { edition: edition.to_string(), volume, page }
+    }
+
+    #[test]
+    fn page_markers() {
+        let comment = r#"<a class="pts1ed" id="pts1ed4.12"></a><a class="pts" id="pts23"></a>"#;
+        assert_eq!(parse_pts_markers(comment), vec![
+            marker("pts1ed", Some(4), 12),
+            marker("pts", None, 23),
+        ]);
+    }
+
+    #[test]
+    fn chapter_section_markers_are_skipped() {
+        let comment = r#"<a class="pts-cs" id="pts-cs2.2.1"></a><a class="pts2ed" id="pts2ed1.5"></a>"#;
+        assert_eq!(parse_pts_markers(comment), vec![marker("pts2ed", Some(1), 5)]);
+    }
+
+    #[test]
+    fn bilara_references() {
+        let refs = "bj7.1, cck12.1, pts-cs1.1, pts-vp-pli1ed1.1, pts-vp-pli2ed2.3, sya1ed1.1, pts-vp-pli23";
+        assert_eq!(parse_bilara_pts_references(refs), vec![
+            marker("pts1ed", Some(1), 1),
+            marker("pts2ed", Some(2), 3),
+            marker("pts", None, 23),
+        ]);
+        assert!(parse_bilara_pts_references("").is_empty());
+    }
+}
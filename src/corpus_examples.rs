@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use pali_dict_core::dict_word::DictWordExample;
+
+use crate::app;
+use crate::db_models::NewExample;
+use crate::db_schema;
+use crate::dictionary::{Dictionary, OutputFormat};
+use crate::error::ToolError;
+use crate::helpers;
+
+/// A candidate example sentence from `text_segments`.
+struct Segment {
+    segment_id: String,
+    root_text_uid: String,
+    root_text: String,
+    translation: String,
+}
+
+/// Add example sentences to the dictionary words from the aligned root and English segments of the
+/// texts in the same database. Run it after `suttacentral_po_texts_to_sqlite` and the dictionary
+/// imports.
+///
+/// For each headword, the segments which contain the word or one of its `inflections` are scored,
+/// and the best `examples_count` are added to the first meaning. Meanings which already have
+/// examples are skipped, so the command can be run again.
+///
+/// `markdown_path` is an optional Markdown dictionary, which is rewritten with the examples of this
+/// run added to its entries without examples.
+pub fn process_corpus_examples_to_sqlite(
+    sqlite_db_path: &Path,
+    dict_label: &Option<String>,
+    examples_count: usize,
+    markdown_path: &Option<PathBuf>,
+    ) -> Result<(), Box<dyn Error>>
+{
+    info!("process_corpus_examples_to_sqlite()");
+
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
+
+    // === Segments with an English translation ===
+
+    let segments: Vec<Segment> = {
+        use db_schema::text_segments::dsl::*;
+
+        let items: Vec<(String, String, String, String)> = text_segments
+            .filter(translated_text_uid.like("%/en/%"))
+            .filter(root_text.ne(""))
+            .filter(translation.ne(""))
+            .order(id.asc())
+            .select((segment_id, root_text_uid, root_text, translation))
+            .load(&conn)?;
+
+        items.into_iter()
+            .map(|(a, b, c, d)| Segment { segment_id: a, root_text_uid: b, root_text: c, translation: d })
+            .collect()
+    };
+
+    if segments.is_empty() {
+        let msg = "🔥 There are no translated text segments in the database.".to_string();
+        return Err(Box::new(ToolError::Exit(msg)));
+    }
+
+    // word form -> segment indexes
+    let mut word_index: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (n, seg) in segments.iter().enumerate() {
+        let words: BTreeSet<String> = helpers::lowercase_words(&seg.root_text).into_iter().collect();
+        for w in words.into_iter() {
+            word_index.entry(w).or_insert_with(Vec::new).push(n);
+        }
+    }
+
+    let text_titles: BTreeMap<String, String> = {
+        use db_schema::root_texts::dsl::*;
+        root_texts.select((uid, title)).load::<(String, String)>(&conn)?.into_iter().collect()
+    };
+
+    // === Dictionary words ===
+
+    let dict_labels: BTreeMap<i32, String> = {
+        use db_schema::dictionaries::dsl::*;
+        dictionaries.select((id, label)).load::<(i32, String)>(&conn)?.into_iter().collect()
+    };
+
+    let words: Vec<(i32, i32, String, String, String, String)> = {
+        use db_schema::{dict_words, dictionaries};
+
+        let mut q = dict_words::table
+            .select((dict_words::id, dict_words::dictionary_id, dict_words::word,
+                     dict_words::word_nom_sg, dict_words::inflections, dict_words::url_id))
+            .order(dict_words::id.asc())
+            .into_boxed();
+
+        if let Some(d_label) = dict_label {
+            let d_id: i32 = match dictionaries::table
+                .filter(dictionaries::label.eq(d_label))
+                .select(dictionaries::id)
+                .first(&conn)
+            {
+                Ok(x) => x,
+                Err(_) => {
+                    let msg = format!("🔥 Dictionary not found: {}", d_label);
+                    return Err(Box::new(ToolError::Exit(msg)));
+                }
+            };
+            q = q.filter(dict_words::dictionary_id.eq(d_id));
+        }
+
+        q.load(&conn)?
+    };
+
+    info!("Words: {}, segments: {}", words.len(), segments.len());
+
+    // (dict_label, url_id) -> examples, for the Markdown entries
+    let mut found: BTreeMap<(String, String), Vec<DictWordExample>> = BTreeMap::new();
+    let mut added_count = 0;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        use db_schema::{examples, meanings};
+
+        for (word_id, dictionary_id, word, word_nom_sg, inflections, url_id) in words.iter() {
+            let meaning_id: i32 = match meanings::table
+                .filter(meanings::dict_word_id.eq(word_id))
+                .order(meanings::meaning_order.asc())
+                .select(meanings::id)
+                .first(&conn)
+            {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+
+            let has_examples: i64 = examples::table
+                .filter(examples::meaning_id.eq(meaning_id))
+                .count()
+                .get_result(&conn)?;
+
+            if has_examples > 0 {
+                continue;
+            }
+
+            let headword = word.to_lowercase();
+            let mut forms: BTreeSet<String> = BTreeSet::new();
+            forms.insert(headword.clone());
+            forms.insert(word_nom_sg.to_lowercase());
+            for i in inflections.split(',') {
+                forms.insert(i.trim().to_lowercase());
+            }
+
+            let best = best_segments(&segments, &word_index, &headword, &forms, examples_count);
+
+            for n in best.iter() {
+                let seg = &segments[*n];
+                let source_title = text_titles.get(&seg.root_text_uid).cloned().unwrap_or_default();
+
+                diesel::insert_into(examples::table)
+                    .values(&NewExample {
+                        meaning_id: &meaning_id,
+                        source_ref: &seg.segment_id,
+                        source_title: &source_title,
+                        text_md: &seg.root_text,
+                        translation_md: &seg.translation,
+                    })
+                    .execute(&conn)?;
+
+                added_count += 1;
+
+                let key = (dict_labels.get(dictionary_id).cloned().unwrap_or_default(), url_id.clone());
+                found.entry(key).or_insert_with(Vec::new).push(DictWordExample {
+                    source_ref: seg.segment_id.clone(),
+                    source_title,
+                    text_md: seg.root_text.clone(),
+                    translation_md: seg.translation.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    })?;
+
+    info!("Added examples: {}", added_count);
+
+    // === Markdown ===
+
+    if let Some(p) = markdown_path {
+        let source_dir = p.parent().unwrap_or_else(|| Path::new("."));
+        let mut dict = Dictionary::new(OutputFormat::Epub, false, source_dir, p, None);
+
+        app::process_markdown_list(vec![p.clone()], &mut dict)?;
+
+        for (_, w) in dict.dict_words_input.iter_mut() {
+            if !w.word_header.examples.is_empty() {
+                continue;
+            }
+            let key = (w.word_header.dict_label.clone(), w.word_header.url_id.clone());
+            if let Some(x) = found.get(&key) {
+                w.word_header.examples = x.clone();
+            }
+        }
+
+        dict.write_markdown()?;
+    }
+
+    Ok(())
+}
+
+/// Pick the best segments for a word. A segment with the headword scores higher than one with an
+/// inflected form, and sentences of a moderate length are preferred. Only one segment is taken
+/// from a sutta.
+fn best_segments(
+    segments: &[Segment],
+    word_index: &BTreeMap<String, Vec<usize>>,
+    headword: &str,
+    forms: &BTreeSet<String>,
+    count: usize,
+) -> Vec<usize> {
+    let mut scores: BTreeMap<usize, f64> = BTreeMap::new();
+
+    for f in forms.iter() {
+        if f.is_empty() || f.contains(' ') {
+            continue;
+        }
+        let bonus = if f == headword { 2.0 } else { 1.0 };
+        if let Some(idxs) = word_index.get(f) {
+            for n in idxs.iter() {
+                let x = scores.entry(*n).or_insert(0.0);
+                if *x < bonus {
+                    *x = bonus;
+                }
+            }
+        }
+    }
+
+    let mut ranked: Vec<(usize, f64)> = scores.into_iter()
+        .map(|(n, s)| {
+            let len = segments[n].root_text.chars().count() as f64;
+            (n, s - (len - 80.0).abs() / 80.0)
+        })
+        .collect();
+
+    // Higher score first, then the order in the texts.
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+
+    let mut res: Vec<usize> = Vec::new();
+    let mut suttas: BTreeSet<String> = BTreeSet::new();
+
+    for (n, _) in ranked.into_iter() {
+        if res.len() >= count {
+            break;
+        }
+        let sutta_uid = segments[n].segment_id.split(':').next().unwrap_or("").to_string();
+        if suttas.insert(sutta_uid) {
+            res.push(n);
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_segment(segment_id: &str, root_text: &str) -> Segment {
+        Segment {
+            segment_id: segment_id.to_string(),
+            root_text_uid: "".to_string(),
+            root_text: root_text.to_string(),
+            translation: "".to_string(),
+        }
+    }
+
+    fn best(segments: &[Segment], headword: &str, forms: &[&str], count: usize) -> Vec<usize> {
+        let mut word_index: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (n, seg) in segments.iter().enumerate() {
+            let words: BTreeSet<String> = helpers::lowercase_words(&seg.root_text).into_iter().collect();
+            for w in words.into_iter() {
+                word_index.entry(w).or_default().push(n);
+            }
+        }
+        let forms: BTreeSet<String> = forms.iter().map(|x| x.to_string()).collect();
+        best_segments(segments, &word_index, headword, &forms, count)
+    }
+
+    #[test]
+    fn headword_scores_higher() {
+        let segments = vec![
+            new_segment("mn2:1.1", &format!("{} dhammo.", "a".repeat(72))),
+            new_segment("mn1:1.1", "Dhamma ca."),
+            new_segment("sn1.1:1.1", "Rūpaṃ aniccaṃ."),
+        ];
+        assert_eq!(best(&segments, "dhamma", &["dhamma", "dhammo"], 3), vec![1, 0]);
+        assert_eq!(best(&segments, "dhamma", &["dhamma", "dhammo"], 1), vec![1]);
+    }
+
+    #[test]
+    fn moderate_length_is_preferred() {
+        let segments = vec![
+            new_segment("mn1:1.1", "Dhammo."),
+            new_segment("mn2:1.1", &format!("{} dhammo.", "a".repeat(72))),
+            new_segment("mn3:1.1", &format!("{} dhammo.", "a".repeat(400))),
+        ];
+        assert_eq!(best(&segments, "dhamma", &["dhamma", "dhammo"], 3), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn one_segment_from_a_sutta() {
+        let segments = vec![
+            new_segment("mn1:1.1", "Dhamma ca."),
+            new_segment("mn1:2.1", "Dhamma ca."),
+            new_segment("mn2:1.1", "Dhammo ca."),
+        ];
+        assert_eq!(best(&segments, "dhamma", &["dhamma", "dhammo"], 3), vec![0, 2]);
+    }
+
+    #[test]
+    fn forms_with_spaces_are_skipped() {
+        let segments = vec![
+            new_segment("mn1:1.1", "Dhamma vinaya."),
+        ];
+        assert!(best(&segments, "dhammavinaya", &["dhammavinaya", "dhamma vinaya"], 3).is_empty());
+    }
+}
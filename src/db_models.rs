@@ -31,6 +31,7 @@ pub struct DbRootText {
     pub content_language: String,
     pub content_plain:    String,
     pub content_html:     String,
+    pub sutta_index_id:   Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -66,6 +67,7 @@ pub struct DbTranslatedText {
     pub content_language: String,
     pub content_plain:    String,
     pub content_html:     String,
+    pub sutta_index_id:   Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -92,16 +94,34 @@ pub struct FtsTranslatedText {
 #[derive(Serialize, Queryable, QueryableByName)]
 #[table_name = "dictionaries"]
 pub struct DbDictionary {
-    pub id:    i32,
-    pub label: String,
-    pub title: String,
+    pub id:                i32,
+    pub label:             String,
+    pub title:             String,
+    pub source_language:   String,
+    pub target_language:   String,
+    pub license:           String,
+    pub license_name:      String,
+    pub license_url:       String,
+    pub copyright_holder:  String,
+    pub copyright_year:    String,
+    pub attribution:       String,
+    pub source_url:        String,
 }
 
 #[derive(Insertable)]
 #[table_name="dictionaries"]
 pub struct NewDictionary<'a> {
-    pub label: &'a str,
-    pub title: &'a str,
+    pub label:             &'a str,
+    pub title:             &'a str,
+    pub source_language:   &'a str,
+    pub target_language:   &'a str,
+    pub license:           &'a str,
+    pub license_name:      &'a str,
+    pub license_url:       &'a str,
+    pub copyright_holder:  &'a str,
+    pub copyright_year:    &'a str,
+    pub attribution:       &'a str,
+    pub source_url:        &'a str,
 }
 
 #[derive(Serialize, Queryable, QueryableByName, Clone)]
@@ -178,6 +198,27 @@ pub struct FtsMeaning {
     pub summary:       String,
 }
 
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name = "meaning_references"]
+pub struct DbMeaningReference {
+    pub id:             i32,
+    pub meaning_id:     i32,
+    pub ref_text:       String,
+    pub normalized_ref: String,
+    pub sutta_uid:      String,
+    pub text_uid:       String,
+}
+
+#[derive(Insertable)]
+#[table_name="meaning_references"]
+pub struct NewMeaningReference<'a> {
+    pub meaning_id:     &'a i32,
+    pub ref_text:       &'a str,
+    pub normalized_ref: &'a str,
+    pub sutta_uid:      &'a str,
+    pub text_uid:       &'a str,
+}
+
 #[derive(Serialize, Queryable, QueryableByName)]
 #[table_name = "grammars"]
 pub struct DbGrammar {
@@ -253,3 +294,175 @@ pub struct FtsExample {
     pub text_md: String,
     pub translation_md: String,
 }
+
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name = "text_segments"]
+pub struct DbTextSegment {
+    pub id:                  i32,
+    pub segment_id:          String,
+    pub root_text_uid:       String,
+    pub translated_text_uid: String,
+    pub segment_order:       i32,
+    pub root_text:           String,
+    pub translation:         String,
+    pub comment_html:        String,
+}
+
+#[derive(Insertable)]
+#[table_name="text_segments"]
+pub struct NewTextSegment<'a> {
+    pub segment_id:          &'a str,
+    pub root_text_uid:       &'a str,
+    pub translated_text_uid: &'a str,
+    pub segment_order:       &'a i32,
+    pub root_text:           &'a str,
+    pub translation:         &'a str,
+    pub comment_html:        &'a str,
+}
+
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name="fts_text_segments"]
+pub struct FtsTextSegment {
+    pub rowid:       i32,
+    pub root_text:   String,
+    pub translation: String,
+}
+
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name = "variant_readings"]
+pub struct DbVariantReading {
+    pub id:            i32,
+    pub segment_id:    String,
+    pub root_text_uid: String,
+    pub lemma:         String,
+    pub reading:       String,
+    pub editions:      String,
+}
+
+#[derive(Insertable)]
+#[table_name="variant_readings"]
+pub struct NewVariantReading<'a> {
+    pub segment_id:    &'a str,
+    pub root_text_uid: &'a str,
+    pub lemma:         &'a str,
+    pub reading:       &'a str,
+    pub editions:      &'a str,
+}
+
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name = "collections"]
+pub struct DbCollection {
+    pub id:               i32,
+    pub uid:              String,
+    pub parent_id:        Option<i32>,
+    pub collection_type:  String,
+    pub collection_order: i32,
+    pub title_pali:       String,
+    pub title_trans:      String,
+}
+
+#[derive(Insertable)]
+#[table_name="collections"]
+pub struct NewCollection<'a> {
+    pub uid:              &'a str,
+    pub parent_id:        Option<i32>,
+    pub collection_type:  &'a str,
+    pub collection_order: &'a i32,
+    pub title_pali:       &'a str,
+    pub title_trans:      &'a str,
+}
+
+#[derive(Serialize, Queryable, QueryableByName)]
+#[table_name = "sutta_index"]
+pub struct DbSuttaIndex {
+    pub id:              i32,
+    pub uid:             String,
+    pub collection_id:   i32,
+    pub division:        String,
+    pub subdivision:     String,
+    pub vagga_number:    String,
+    pub number_in_vagga: String,
+    pub sutta_order:     i32,
+    pub acronym:         String,
+    pub volpage:         String,
+    pub title_pali:      String,
+    pub title_trans:     String,
+}
+
+#[derive(Insertable)]
+#[table_name="sutta_index"]
+pub struct NewSuttaIndex<'a> {
+    pub uid:             &'a str,
+    pub collection_id:   &'a i32,
+    pub division:        &'a str,
+    pub subdivision:     &'a str,
+    pub vagga_number:    &'a str,
+    pub number_in_vagga: &'a str,
+    pub sutta_order:     &'a i32,
+    pub acronym:         &'a str,
+    pub volpage:         &'a str,
+    pub title_pali:      &'a str,
+    pub title_trans:     &'a str,
+}
+
+#[derive(Serialize, Queryable, QueryableByName, Clone)]
+#[table_name = "pts_concordance"]
+pub struct DbPtsConcordance {
+    pub id:                  i32,
+    pub collection:          String,
+    pub volume:              i32,
+    pub page:                i32,
+    pub edition:             String,
+    pub sutta_uid:           String,
+    pub root_text_uid:       String,
+    pub segment_start:       String,
+    pub segment_end:         String,
+    pub segment_start_order: i32,
+    pub segment_end_order:   i32,
+}
+
+#[derive(Insertable)]
+#[table_name="pts_concordance"]
+pub struct NewPtsConcordance<'a> {
+    pub collection:          &'a str,
+    pub volume:              &'a i32,
+    pub page:                &'a i32,
+    pub edition:             &'a str,
+    pub sutta_uid:           &'a str,
+    pub root_text_uid:       &'a str,
+    pub segment_start:       &'a str,
+    pub segment_end:         &'a str,
+    pub segment_start_order: &'a i32,
+    pub segment_end_order:   &'a i32,
+}
+
+#[derive(Serialize, Queryable, QueryableByName, Clone)]
+#[table_name = "word_frequencies"]
+pub struct DbWordFrequency {
+    pub id:           i32,
+    pub dict_word_id: i32,
+    pub word:         String,
+    pub total:        i32,
+    pub texts_count:  i32,
+    pub dn:           i32,
+    pub mn:           i32,
+    pub sn:           i32,
+    pub an:           i32,
+    pub kn:           i32,
+    pub vinaya:       i32,
+}
+
+#[derive(Insertable)]
+#[table_name="word_frequencies"]
+pub struct NewWordFrequency<'a> {
+    pub dict_word_id: &'a i32,
+    pub word:         &'a str,
+    pub total:        &'a i32,
+    pub texts_count:  &'a i32,
+    pub dn:           &'a i32,
+    pub mn:           &'a i32,
+    pub sn:           &'a i32,
+    pub an:           &'a i32,
+    pub kn:           &'a i32,
+    pub vinaya:       &'a i32,
+}
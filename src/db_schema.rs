@@ -19,6 +19,7 @@ table! {
         content_language -> Text,
         content_plain -> Text,
         content_html -> Text,
+        sutta_index_id -> Nullable<Integer>,
     }
 }
 
@@ -41,6 +42,7 @@ table! {
         content_language -> Text,
         content_plain -> Text,
         content_html -> Text,
+        sutta_index_id -> Nullable<Integer>,
     }
 }
 
@@ -56,6 +58,15 @@ table! {
         id -> Integer,
         label -> Text,
         title -> Text,
+        source_language -> Text,
+        target_language -> Text,
+        license -> Text,
+        license_name -> Text,
+        license_url -> Text,
+        copyright_holder -> Text,
+        copyright_year -> Text,
+        attribution -> Text,
+        source_url -> Text,
     }
 }
 
@@ -144,6 +155,110 @@ table! {
     }
 }
 
+table! {
+    text_segments (id) {
+        id                  -> Integer,
+        segment_id          -> Text,
+        root_text_uid       -> Text,
+        translated_text_uid -> Text,
+        segment_order       -> Integer,
+        root_text           -> Text,
+        translation         -> Text,
+        comment_html        -> Text,
+    }
+}
+
+table! {
+    fts_text_segments (rowid) {
+        rowid -> Integer,
+        root_text -> Text,
+        translation -> Text,
+    }
+}
+
+table! {
+    variant_readings (id) {
+        id            -> Integer,
+        segment_id    -> Text,
+        root_text_uid -> Text,
+        lemma         -> Text,
+        reading       -> Text,
+        editions      -> Text,
+    }
+}
+
+
+table! {
+    collections (id) {
+        id -> Integer,
+        uid -> Text,
+        parent_id -> Nullable<Integer>,
+        collection_type -> Text,
+        collection_order -> Integer,
+        title_pali -> Text,
+        title_trans -> Text,
+    }
+}
+
+table! {
+    sutta_index (id) {
+        id -> Integer,
+        uid -> Text,
+        collection_id -> Integer,
+        division -> Text,
+        subdivision -> Text,
+        vagga_number -> Text,
+        number_in_vagga -> Text,
+        sutta_order -> Integer,
+        acronym -> Text,
+        volpage -> Text,
+        title_pali -> Text,
+        title_trans -> Text,
+    }
+}
+
+table! {
+    meaning_references (id) {
+        id -> Integer,
+        meaning_id -> Integer,
+        ref_text -> Text,
+        normalized_ref -> Text,
+        sutta_uid -> Text,
+        text_uid -> Text,
+    }
+}
+
+table! {
+    pts_concordance (id) {
+        id -> Integer,
+        collection -> Text,
+        volume -> Integer,
+        page -> Integer,
+        edition -> Text,
+        sutta_uid -> Text,
+        root_text_uid -> Text,
+        segment_start -> Text,
+        segment_end -> Text,
+        segment_start_order -> Integer,
+        segment_end_order -> Integer,
+    }
+}
+
+table! {
+    word_frequencies (id) {
+        id -> Integer,
+        dict_word_id -> Integer,
+        word -> Text,
+        total -> Integer,
+        texts_count -> Integer,
+        dn -> Integer,
+        mn -> Integer,
+        sn -> Integer,
+        an -> Integer,
+        kn -> Integer,
+        vinaya -> Integer,
+    }
+}
 
 allow_tables_to_appear_in_same_query!(
     authors,
@@ -158,4 +273,12 @@ allow_tables_to_appear_in_same_query!(
     meanings,
     grammars,
     examples,
+    text_segments,
+    fts_text_segments,
+    variant_readings,
+    collections,
+    sutta_index,
+    meaning_references,
+    pts_concordance,
+    word_frequencies,
 );
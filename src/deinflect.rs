@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::app;
+use crate::db_models::{DbDictWord, DbMeaning};
+use crate::db_schema;
+use crate::dictionary::{Dictionary, OutputFormat};
+use crate::helpers::uppercase_first_letter;
+use crate::morphology::{self, EndingRule};
+
+/// A headword which a word form may be an inflection of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deinflection {
+    pub headword: String,
+    /// The paradigm, case and number, or tense and person, of the form. Empty when the form is the
+    /// headword.
+    pub analyses: Vec<String>,
+}
+
+/// The possible headwords of an inflected form, by removing the endings of the regular paradigms:
+/// `bhikkhūnaṃ` -> `bhikkhu`, `gacchissati` -> `gacchati`. The form itself is the first candidate.
+/// The candidates are not checked against a dictionary, see `lookup_in_words()` and
+/// `lookup_in_db()`.
+pub fn deinflect(form: &str) -> Vec<Deinflection> {
+    lazy_static! {
+        static ref RULES: Vec<EndingRule> = morphology::ending_rules();
+    }
+
+    let form = form.trim().to_lowercase();
+    let mut res: Vec<Deinflection> = Vec::new();
+
+    if form.is_empty() {
+        return res;
+    }
+
+    res.push(Deinflection {
+        headword: form.clone(),
+        analyses: vec![],
+    });
+
+    for rule in RULES.iter() {
+        let base = match form.strip_suffix(&rule.ending) {
+            Some(x) if x.chars().count() >= 2 => x,
+            _ => continue,
+        };
+
+        for c in rule.citations.iter() {
+            let headword = format!("{}{}", base, c);
+            match res.iter_mut().find(|x| x.headword == headword) {
+                Some(x) => {
+                    if !x.analyses.contains(&rule.label) {
+                        x.analyses.push(rule.label.clone());
+                    }
+                }
+                None => res.push(Deinflection {
+                    headword,
+                    analyses: vec![rule.label.clone()],
+                }),
+            }
+        }
+    }
+
+    res
+}
+
+/// The candidates of `deinflect()` which are in a word list, such as `Dictionary.valid_words`.
+pub fn lookup_in_words(form: &str, words: &[String]) -> Vec<Deinflection> {
+    let words: BTreeSet<String> = words.iter().map(|x| x.to_lowercase()).collect();
+    deinflect(form)
+        .into_iter()
+        .filter(|x| words.contains(&x.headword))
+        .collect()
+}
+
+/// The dictionary words of the candidates of `deinflect()`. Words which list the form in their
+/// `inflections` are matched too.
+pub fn lookup_in_db(conn: &SqliteConnection, form: &str) -> Vec<(Deinflection, Vec<DbDictWord>)> {
+    use db_schema::dict_words::dsl::*;
+
+    let mut res: Vec<(Deinflection, Vec<DbDictWord>)> = Vec::new();
+
+    for d in deinflect(form).into_iter() {
+        let items: Vec<DbDictWord> = dict_words
+            .filter(word.eq(&d.headword).or(word.eq(uppercase_first_letter(&d.headword))))
+            .order(id.asc())
+            .load(conn)
+            .unwrap_or_default();
+        if !items.is_empty() {
+            res.push((d, items));
+        }
+    }
+
+    let form = form.trim().to_lowercase();
+    let items: Vec<DbDictWord> = dict_words
+        .filter(inflections.like(format!("%{}%", form)))
+        .order(id.asc())
+        .load(conn)
+        .unwrap_or_default();
+
+    for i in items.into_iter() {
+        if !i.inflections.split(',').any(|x| x.trim().to_lowercase() == form) {
+            continue;
+        }
+        if res.iter().any(|(_, xs)| xs.iter().any(|x| x.id == i.id)) {
+            continue;
+        }
+        let d = Deinflection {
+            headword: i.word.to_lowercase(),
+            analyses: vec!["listed in inflections".to_string()],
+        };
+        match res.iter_mut().find(|(x, _)| *x == d) {
+            Some((_, xs)) => xs.push(i),
+            None => res.push((d, vec![i])),
+        }
+    }
+
+    res
+}
+
+/// Print the dictionary entries of a word form, for the `lookup` command. The source is an Sqlite
+/// database or a Markdown dictionary.
+pub fn lookup(source_path: &Path, form: &str) -> Result<(), Box<dyn Error>> {
+    let is_db = match source_path.extension().and_then(|x| x.to_str()) {
+        Some(ext) => ext == "sqlite" || ext == "sqlite3" || ext == "db",
+        None => false,
+    };
+
+    if is_db {
+        lookup_print_db(source_path, form)
+    } else {
+        lookup_print_markdown(source_path, form)
+    }
+}
+
+fn lookup_print_db(sqlite_db_path: &Path, form: &str) -> Result<(), Box<dyn Error>> {
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
+
+    let found = lookup_in_db(&conn, form);
+    if found.is_empty() {
+        println!("Not found: {}", form);
+        return Ok(());
+    }
+
+    for (d, items) in found.iter() {
+        print_deinflection(form, d);
+
+        for i in items.iter() {
+            let label: String = {
+                use db_schema::dictionaries::dsl::*;
+                dictionaries
+                    .filter(id.eq(i.dictionary_id))
+                    .select(label)
+                    .first(&conn)
+                    .unwrap_or_default()
+            };
+
+            let meanings: Vec<DbMeaning> = {
+                use db_schema::meanings::dsl::*;
+                meanings
+                    .filter(dict_word_id.eq(i.id))
+                    .order(meaning_order.asc())
+                    .load(&conn)
+                    .unwrap_or_default()
+            };
+
+            println!("  [{}] {}", label, i.word);
+            for m in meanings.iter() {
+                let text = if m.summary.is_empty() { &m.definition_md } else { &m.summary };
+                println!("    {}", short_text(text));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn lookup_print_markdown(markdown_path: &Path, form: &str) -> Result<(), Box<dyn Error>> {
+    let source_dir = markdown_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut dict = Dictionary::new(OutputFormat::Epub, false, source_dir, markdown_path, None);
+
+    app::process_markdown_list(vec![PathBuf::from(markdown_path)], &mut dict)?;
+
+    let found = lookup_in_words(form, &dict.valid_words);
+    if found.is_empty() {
+        println!("Not found: {}", form);
+        return Ok(());
+    }
+
+    for d in found.iter() {
+        print_deinflection(form, d);
+
+        for w in dict.dict_words_input.values() {
+            if w.word_header.word.to_lowercase() != d.headword {
+                continue;
+            }
+            println!("  [{}] {}", w.word_header.dict_label, w.word_header.word);
+            let text = if w.word_header.summary.is_empty() { &w.definition_md } else { &w.word_header.summary };
+            println!("    {}", short_text(text));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_deinflection(form: &str, d: &Deinflection) {
+    if d.analyses.is_empty() {
+        println!("{}", d.headword);
+    } else {
+        println!("{} -> {} ({})", form, d.headword, d.analyses.join("; "));
+    }
+}
+
+/// The first line of a definition, at most 200 characters.
+fn short_text(text: &str) -> String {
+    let line = text.lines().find(|x| !x.trim().is_empty()).unwrap_or("").trim();
+    if line.chars().count() > 200 {
+        format!("{} ...", line.chars().take(200).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(xs: &[&str]) -> Vec<String> {
+        xs.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn bhikkhunam_to_bhikkhu() {
+        let res = lookup_in_words("bhikkhūnaṃ", &words(&["bhikkhu", "bhikkhunī", "dhamma"]));
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].headword, "bhikkhu");
+        assert!(res[0].analyses.contains(&"Masculine u-stem, Genitive plural".to_string()));
+    }
+
+    #[test]
+    fn gacchissati_to_gacchati() {
+        let res = lookup_in_words("gacchissati", &words(&["gacchati", "gaccha"]));
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].headword, "gacchati");
+        assert!(res[0].analyses.contains(&"Future, 3rd singular".to_string()));
+    }
+
+    #[test]
+    fn the_form_is_the_first_candidate() {
+        let res = deinflect("Dhammo");
+        assert_eq!(res[0].headword, "dhammo");
+        assert!(res[0].analyses.is_empty());
+        assert!(res.iter().any(|x| x.headword == "dhamma"));
+    }
+
+    #[test]
+    fn short_base_has_no_candidate() {
+        // -ena on `kena` would leave a one letter base, `k`.
+        let res = deinflect("kena");
+        assert_eq!(res[0].headword, "kena");
+        assert!(!res.iter().any(|x| x.headword == "ka"));
+
+        assert!(lookup_in_words("kena", &words(&["ka"])).is_empty());
+    }
+}
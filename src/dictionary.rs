@@ -3,7 +3,7 @@ use std::fs::{self, File};
 use std::io::{self, Write};
 use std::process::Command;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 use std::convert::TryInto;
 
@@ -21,11 +21,19 @@ use crate::app::{self, AppStartParams, ZipWith};
 use pali_dict_core::dict_word::{DictWord, DictWordMarkdown, DictWordXlsx};
 use crate::error::ToolError;
 use crate::helpers::{self, is_hidden, md2html, uppercase_first_letter};
-use pali_dict_core::letter_groups::{LetterGroups, LetterGroup};
 use pali_dict_core::pali;
 use crate::db_schema;
 use crate::db_models::{DbDictionary, NewDictionary, DbDictWord, NewDictWord, DbMeaning, NewMeaning,
-DbGrammar, NewGrammar, DbExample, NewExample};
+DbGrammar, NewGrammar, DbExample, NewExample, DbVariantReading, NewMeaningReference};
+use crate::sc_data;
+use crate::sutta_refs::{self, CitationStyle, SuttaRef, VolpageIndex, DEFAULT_SUTTA_LINK_TEMPLATE};
+use crate::word_frequencies::{self, WordFrequency};
+use crate::collation::{self, Collation, LetterGroups, LetterGroup};
+use crate::morphology;
+use crate::normalize;
+use crate::license::LicenseNotice;
+use crate::scripts::{self, Romanization, Script};
+use crate::compounds::CompoundSplitter;
 
 pub const DICTIONARY_METADATA_SEP: &str = "--- DICTIONARY METADATA ---";
 pub const DICTIONARY_WORD_ENTRIES_SEP: &str = "--- DICTIONARY WORD ENTRIES ---";
@@ -41,6 +49,11 @@ pub struct Dictionary {
     /// Words as processed for rendering in the templates. The map key is `word_header.url_id`.
     pub dict_words_render: BTreeMap<String, DictWord>,
 
+    /// Occurrences of the words in the root texts, when a database with the counts is given. The
+    /// map key is the word.
+    #[serde(default)]
+    pub word_frequencies: BTreeMap<String, WordFrequency>,
+
     /// Collects the list of valid word names which can be linked to.
     #[serde(skip)]
     pub valid_words: Vec<String>,
@@ -48,6 +61,32 @@ pub struct Dictionary {
     #[serde(skip)]
     pub words_to_url: BTreeMap<String, String>,
 
+    /// Headwords changed by the normalization of the input, as (source, normalized).
+    #[serde(skip)]
+    pub normalized_headwords: Vec<(String, String)>,
+
+    /// The sutta references which were turned into links, as they were written in the input. The
+    /// map key is `word_header.url_id`.
+    #[serde(skip)]
+    pub sutta_refs: BTreeMap<String, Vec<SuttaRef>>,
+
+    /// The license notice of the outputs, from the license fields of the metadata.
+    #[serde(default)]
+    pub license: LicenseNotice,
+
+    /// The source dictionaries, in the order of the input files. A combined build has more than
+    /// one, and `meta` is the metadata of the combined build.
+    #[serde(default)]
+    pub sources: Vec<SourceDictionary>,
+
+    /// There is more than one source dictionary.
+    #[serde(default)]
+    pub combined: bool,
+
+    /// `meta` was read from the `--combined_metadata` file, the sources don't replace it.
+    #[serde(skip)]
+    pub has_combined_metadata: bool,
+
     pub entries_manifest: Vec<EntriesManifest>,
     pub asset_files_string: BTreeMap<String, String>,
     pub asset_files_byte: BTreeMap<String, Vec<u8>>,
@@ -113,6 +152,111 @@ pub struct DictMetadata {
     pub allow_raw_html: bool,
     #[serde(default)]
     pub dont_generate_synonyms: bool,
+    #[serde(default)]
+    pub dont_generate_inflections: bool,
+    #[serde(default)]
+    pub add_paradigm_tables: bool,
+    #[serde(default)]
+    pub split_compounds: bool,
+    /// Merge the entries of the same headword from the sources of a combined build into one entry,
+    /// with a section for each source.
+    #[serde(default)]
+    pub merge_entries: bool,
+    /// The order of the sources in a merged entry, by `dict_label`: `["NCPED", "PTS", "DPPN"]`.
+    /// The sources which are not listed follow in the order of `source_paths_list`.
+    #[serde(default)]
+    pub source_order: Vec<String>,
+    /// Scripts to show the headword in, before the Roman headword: `["sinhala", "thai"]`
+    #[serde(default)]
+    pub display_scripts: Vec<String>,
+    /// Scripts to add the headword in to the inflections, as lookup keys.
+    #[serde(default)]
+    pub search_scripts: Vec<String>,
+    /// Romanization conventions to add the headword and the inflections in to the inflections, as
+    /// lookup keys: `["iast", "iso15919", "eng", "harvard_kyoto", "velthuis", "ascii"]`
+    #[serde(default)]
+    pub lookup_conventions: Vec<String>,
+    /// The citation styles of the sutta references in the definitions: `["volume_page",
+    /// "sutta_number"]`. Chosen by `dict_label` when empty.
+    #[serde(default)]
+    pub citation_styles: Vec<String>,
+    /// The niggahīta to write the words with, `ṃ`, `ṁ` or `ŋ`. Empty keeps the niggahīta of the
+    /// source.
+    #[serde(default)]
+    pub niggahita: String,
+    /// The sort order of the entries: `pali` (the default), `sanskrit`, `unicode`, or `custom` with
+    /// `collation_alphabet`.
+    #[serde(default)]
+    pub collation: String,
+    /// The letters of the `custom` collation, in order and separated by spaces: `a b c ch d`
+    #[serde(default)]
+    pub collation_alphabet: String,
+    /// The language of the headwords, as an ISO 639-1 code when there is one. When empty, `pi` is
+    /// used, except in the MOBI x-metadata, which keeps `en` as in the earlier MOBI builds.
+    #[serde(default)]
+    pub source_language: String,
+    /// The language of the definitions.
+    #[serde(default = "default_target_language")]
+    pub target_language: String,
+    /// The SPDX id of the license: `CC0-1.0`, `CC-BY-4.0`, `LicenseRef-PublicDomain`. The source
+    /// URL of the attribution is `source`.
+    /// When empty, the license is unknown and the outputs don't claim one.
+    #[serde(default)]
+    pub license: String,
+    /// The name of the license, when it is not one of the known SPDX ids.
+    #[serde(default)]
+    pub license_name: String,
+    #[serde(default)]
+    pub license_url: String,
+    /// Empty uses `creator`.
+    #[serde(default)]
+    pub copyright_holder: String,
+    #[serde(default)]
+    pub copyright_year: String,
+    /// The attribution text the license asks for.
+    #[serde(default)]
+    pub attribution: String,
+}
+
+/// A source dictionary of the build, with the metadata of its input file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SourceDictionary {
+    /// The `dict_label` of the entries from this source.
+    pub label: String,
+    pub meta: DictMetadata,
+    pub license: LicenseNotice,
+    /// `Source: Title, Creator (CC-BY-4.0)`, for the entries of a combined build.
+    pub attribution: String,
+    pub word_count: usize,
+}
+
+impl SourceDictionary {
+    pub fn new(meta: DictMetadata) -> Self {
+        SourceDictionary {
+            label: meta.dict_label.trim().to_string(),
+            meta,
+            license: LicenseNotice::default(),
+            attribution: "".to_string(),
+            word_count: 0,
+        }
+    }
+
+    fn process_license(&mut self) {
+        self.license = LicenseNotice::from_meta(&self.meta);
+
+        let mut s = format!("Source: {}", self.meta.title.trim());
+        if !self.meta.creator.trim().is_empty() {
+            s.push_str(&format!(", {}", self.meta.creator.trim()));
+        }
+        if !self.license.spdx_id.is_empty() {
+            s.push_str(&format!(" ({})", self.license.spdx_id));
+        }
+        self.attribution = s;
+    }
+}
+
+fn default_target_language() -> String {
+    "en".to_string()
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone)]
@@ -139,6 +283,7 @@ pub struct EntriesManifest {
 pub struct LetterGroupTemplateData {
     group: LetterGroup,
     meta: DictMetadata,
+    sources: Vec<SourceDictionary>,
 }
 
 impl Dictionary {
@@ -161,6 +306,7 @@ impl Dictionary {
         h.register_helper("markdown", Box::new(helpers::markdown_helper));
         h.register_helper("countitems", Box::new(helpers::countitems));
         h.register_helper("to_velthuis", Box::new(helpers::to_velthuis));
+        h.register_helper("to_script", Box::new(helpers::to_script));
         h.register_helper("word_title", Box::new(helpers::word_title));
         h.register_helper("cover_media_type", Box::new(helpers::cover_media_type));
         h.register_helper("headword_plain", Box::new(helpers::headword_plain));
@@ -171,6 +317,11 @@ impl Dictionary {
         h.register_helper("grammar_text_plain", Box::new(helpers::grammar_text_plain));
         h.register_helper("phonetic_transliteration", Box::new(helpers::phonetic_transliteration));
         h.register_helper("phonetic_transliteration_plain", Box::new(helpers::phonetic_transliteration_plain));
+        h.register_helper("word_frequency", Box::new(helpers::word_frequency));
+        h.register_helper("word_frequency_plain", Box::new(helpers::word_frequency_plain));
+        h.register_helper("source_attribution", Box::new(helpers::source_attribution));
+        h.register_helper("source_attribution_plain", Box::new(helpers::source_attribution_plain));
+        h.register_helper("paradigm_tables", Box::new(helpers::paradigm_tables));
 
         // Can't loop because the arg of include_str! must be a string literal.
 
@@ -323,8 +474,15 @@ impl Dictionary {
             output_format,
             dict_words_input: BTreeMap::new(),
             dict_words_render: BTreeMap::new(),
+            word_frequencies: BTreeMap::new(),
             valid_words: Vec::new(),
             words_to_url: BTreeMap::new(),
+            normalized_headwords: Vec::new(),
+            sutta_refs: BTreeMap::new(),
+            license: LicenseNotice::default(),
+            sources: Vec::new(),
+            combined: false,
+            has_combined_metadata: false,
             entries_manifest: Vec::new(),
             asset_files_string: afs,
             asset_files_byte: afb,
@@ -349,6 +507,8 @@ impl Dictionary {
     /// - given with the transliteration attribute
     /// - velthuis
     /// - ascii
+    /// - the scripts in `search_scripts`
+    /// - the conventions in `lookup_conventions`, of the word and its inflections
     pub fn process_add_transliterations(&mut self) {
         if self.meta.dont_generate_synonyms {
             return;
@@ -356,7 +516,12 @@ impl Dictionary {
 
         info!("process_add_transliterations()");
 
-        for (_, dict_word) in self.dict_words_input.iter_mut() {
+        let search_scripts = Script::from_names(&self.meta.search_scripts);
+        let lookup_conventions = Romanization::from_names(&self.meta.lookup_conventions);
+
+        for dict_word in self.dict_words_input.values_mut() {
+            let mut forms: Vec<String> = vec![dict_word.word_header.word.clone()];
+            forms.extend(dict_word.word_header.inflections.iter().cloned());
 
             if !dict_word.word_header.transliteration.is_empty() {
                 dict_word.word_header.inflections.push(dict_word.word_header.transliteration.clone());
@@ -375,6 +540,274 @@ impl Dictionary {
                     dict_word.word_header.inflections.push(s);
                 }
             }
+
+            for script in search_scripts.iter() {
+                let s = scripts::to_script(&dict_word.word_header.word, *script);
+                if !dict_word.word_header.inflections.contains(&s) && s != dict_word.word_header.word {
+                    dict_word.word_header.inflections.push(s);
+                }
+            }
+
+            // With the paradigm inflections there can be many forms, the new ones are collected in
+            // a set and added once.
+            let mut romanized: BTreeSet<String> = BTreeSet::new();
+            for form in forms.iter() {
+                for r in lookup_conventions.iter() {
+                    let s = scripts::to_romanization(form, *r);
+                    if !s.contains("[?]") && s != dict_word.word_header.word {
+                        romanized.insert(s);
+                    }
+                }
+            }
+            let existing: BTreeSet<&String> = dict_word.word_header.inflections.iter().collect();
+            let new_forms: Vec<String> = romanized.into_iter().filter(|x| !existing.contains(x)).collect();
+            dict_word.word_header.inflections.extend(new_forms);
+        }
+    }
+
+    /// The license notices for the templates, of the build and of the sources.
+    pub fn process_license(&mut self) {
+        self.license = LicenseNotice::from_meta(&self.meta);
+        for x in self.sources.iter_mut() {
+            x.process_license();
+        }
+    }
+
+    /// Read the metadata of a combined build. The metadata of the source files is kept in
+    /// `sources`.
+    pub fn use_combined_metadata(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let s = match fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) => {
+                let msg = format!("🔥 Can't read the combined metadata: {:?} {:?}", path, e);
+                return Err(Box::new(ToolError::Exit(msg)));
+            }
+        };
+
+        let meta_txt = s
+            .replace(DICTIONARY_METADATA_SEP, "")
+            .replace("``` toml", "")
+            .replace("```", "");
+
+        self.meta = app::parse_str_to_metadata(&meta_txt)?;
+        self.has_combined_metadata = true;
+        Ok(())
+    }
+
+    /// Add the metadata of a source file, before its entries. The first source gives the metadata
+    /// of the build, unless there is a combined metadata file.
+    pub fn add_source(&mut self, meta: DictMetadata) {
+        if self.sources.is_empty() && !self.has_combined_metadata {
+            self.meta = meta.clone();
+        } else if self.sources.len() == 1 && !self.has_combined_metadata {
+            warn!("Combining more than one source without --combined_metadata, using the metadata of the first source for the build.");
+        }
+
+        self.sources.push(SourceDictionary::new(meta));
+        self.combined = self.sources.len() > 1;
+    }
+
+    /// The source of the entries with this label, when there is more than one source.
+    pub fn source_by_label(&self, dict_label: &str) -> Option<&SourceDictionary> {
+        if !self.combined {
+            return None;
+        }
+        self.sources.iter().find(|x| x.label == dict_label)
+    }
+
+    /// The citation styles of the sutta references in the entries with this label, from the
+    /// metadata of their source.
+    fn citation_styles(&self, dict_label: &str) -> Vec<CitationStyle> {
+        let meta = match self.source_by_label(dict_label) {
+            Some(x) => &x.meta,
+            None => &self.meta,
+        };
+        sutta_refs::citation_styles(dict_label, &meta.citation_styles)
+    }
+
+    /// The sources of an entry. A merged entry has the labels of its sources: `NCPED, PTS`
+    pub fn sources_by_label(&self, dict_label: &str) -> Vec<&SourceDictionary> {
+        dict_label.split(',')
+            .filter_map(|x| self.source_by_label(x.trim()))
+            .collect()
+    }
+
+    /// The entries are merged by headword in a combined build with `merge_entries`.
+    pub fn is_merging_entries(&self) -> bool {
+        self.combined && self.meta.merge_entries
+    }
+
+    /// The position of a source in a merged entry, from `source_order`, then the order of the
+    /// sources.
+    fn source_rank(&self, dict_label: &str) -> usize {
+        let n = self.meta.source_order.len();
+        if let Some(i) = self.meta.source_order.iter().position(|x| x == dict_label) {
+            return i;
+        }
+        match self.sources.iter().position(|x| x.label == dict_label) {
+            Some(i) => n + i,
+            None => n + self.sources.len(),
+        }
+    }
+
+    /// The input entries grouped by their headword, ignoring the case. The entries of a group are
+    /// in the source order, then in their meaning order.
+    fn merge_groups(&self) -> Vec<Vec<&DictWordMarkdown>> {
+        let mut groups: BTreeMap<String, Vec<&DictWordMarkdown>> = BTreeMap::new();
+        for w in self.dict_words_input.values() {
+            groups.entry(w.word_header.word.to_lowercase())
+                .or_default()
+                .push(w);
+        }
+
+        groups.into_values()
+            .map(|mut words| {
+                words.sort_by_key(|w| (
+                    self.source_rank(&w.word_header.dict_label),
+                    w.word_header.dict_label.clone(),
+                    w.word_header.meaning_order,
+                ));
+                words
+            })
+            .collect()
+    }
+
+    /// The input entries of the EPUB, MOBI and Babylon outputs. With `merge_entries`, the entries of
+    /// a headword from more than one source are merged in one entry. The definition of each source
+    /// is a section starting with its label, and the word lists are unified.
+    pub fn merged_words_input(&self) -> Vec<DictWordMarkdown> {
+        if !self.is_merging_entries() {
+            return self.dict_words_input.values().cloned().collect();
+        }
+
+        let mut res: Vec<DictWordMarkdown> = Vec::new();
+
+        for words in self.merge_groups().into_iter() {
+            let labels = merged_labels(&words);
+            if labels.len() < 2 {
+                res.extend(words.into_iter().cloned());
+                continue;
+            }
+
+            let mut merged = words[0].clone();
+            merged.word_header.dict_label = labels.join(", ");
+
+            let mut sections: Vec<String> = Vec::new();
+            for (n, w) in words.iter().enumerate() {
+                let h = &w.word_header;
+                sections.push(format!("[{}] {}", h.dict_label, w.definition_md.trim()));
+
+                if n == 0 {
+                    continue;
+                }
+
+                let m = &mut merged.word_header;
+                if h.word != m.word {
+                    push_unique(&mut m.inflections, std::slice::from_ref(&h.word));
+                }
+                push_unique(&mut m.inflections, &h.inflections);
+                push_unique(&mut m.synonyms, &h.synonyms);
+                push_unique(&mut m.antonyms, &h.antonyms);
+                push_unique(&mut m.also_written_as, &h.also_written_as);
+                push_unique(&mut m.see_also, &h.see_also);
+                m.examples.extend(h.examples.iter().cloned());
+            }
+            merged.definition_md = sections.join("\n\n");
+
+            res.push(merged);
+        }
+
+        res
+    }
+
+    /// The rendered entries with `merge_entries`. The meanings of the sources follow each other,
+    /// marked with their label. The inflections and the see also words are unified, the see also
+    /// words of the entry are listed after its last meaning.
+    fn merged_words_render(&self) -> Vec<DictWord> {
+        let mut res: Vec<DictWord> = Vec::new();
+
+        for words in self.merge_groups().into_iter() {
+            let labels = merged_labels(&words);
+            if labels.len() < 2 {
+                res.extend(words.into_iter().map(DictWord::from_dict_word_markdown));
+                continue;
+            }
+
+            let mut merged = DictWord::from_dict_word_markdown(words[0]);
+            merged.dict_label = labels.join(", ");
+            merged.meanings = Vec::new();
+
+            let mut see_also: Vec<String> = Vec::new();
+
+            for w in words.into_iter() {
+                let dwr = DictWord::from_dict_word_markdown(w);
+
+                if dwr.word != merged.word {
+                    push_unique(&mut merged.inflections, std::slice::from_ref(&dwr.word));
+                }
+                push_unique(&mut merged.inflections, &dwr.inflections);
+
+                for m in dwr.meanings.into_iter() {
+                    let mut m = m;
+                    m.definition_md = format!("[{}] {}", dwr.dict_label, m.definition_md.trim());
+                    push_unique(&mut see_also, &m.see_also);
+                    m.see_also = Vec::new();
+                    merged.meanings.push(m);
+                }
+            }
+
+            if let Some(m) = merged.meanings.last_mut() {
+                m.see_also = see_also;
+            }
+
+            res.push(merged);
+        }
+
+        res
+    }
+
+    /// Write the headwords which were changed by the normalization of the input next to the
+    /// output: `dict.epub` → `dict.normalized.txt`
+    pub fn process_normalization_report(&self) {
+        if !self.meta.niggahita.is_empty() && normalize::parse_niggahita(&self.meta.niggahita).is_none() {
+            warn!("Unknown niggahita: {}, expected ṃ, ṁ or ŋ", self.meta.niggahita);
+        }
+
+        if self.normalized_headwords.is_empty() {
+            return;
+        }
+
+        let report_path = self.output_path.with_extension("normalized.txt");
+        let report: String = self.normalized_headwords.iter()
+            .map(|(source_word, word)| format!("{} -> {}\n", source_word, word))
+            .collect();
+
+        match fs::write(&report_path, report) {
+            Ok(_) => println!("NOTE: Normalized headwords: {}, see {}",
+                              self.normalized_headwords.len(),
+                              report_path.display()),
+            Err(e) => error!("🔥 Can't write the normalization report to {}: {:?}", report_path.display(), e),
+        }
+    }
+
+    /// Add the inflected forms of nouns, adjectives and verbs, generated from their grammar with
+    /// the regular paradigms.
+    pub fn process_add_paradigm_inflections(&mut self) {
+        if self.meta.dont_generate_inflections {
+            return;
+        }
+
+        info!("process_add_paradigm_inflections()");
+
+        for (_, dict_word) in self.dict_words_input.iter_mut() {
+            let h = &dict_word.word_header;
+            let tables = morphology::paradigm_tables(&h.word, &h.word_nom_sg, &h.grammar_speech, &h.grammar_gender);
+
+            for form in morphology::inflected_forms(&tables).into_iter() {
+                if form != dict_word.word_header.word && !dict_word.word_header.inflections.contains(&form) {
+                    dict_word.word_header.inflections.push(form);
+                }
+            }
         }
     }
 
@@ -395,8 +828,25 @@ impl Dictionary {
         self.meta.allow_raw_html = app_params.allow_raw_html;
         self.meta.dont_generate_synonyms = app_params.dont_generate_synonyms;
 
+        // These can be set in the metadata too.
+        if app_params.dont_generate_inflections {
+            self.meta.dont_generate_inflections = true;
+        }
+        if app_params.add_paradigm_tables {
+            self.meta.add_paradigm_tables = true;
+        }
+        if app_params.split_compounds {
+            self.meta.split_compounds = true;
+        }
+        if app_params.merge_entries {
+            self.meta.merge_entries = true;
+        }
+
         if let Some(ref dict_label) = app_params.dict_label {
             self.meta.dict_label = dict_label.clone();
+            for x in self.sources.iter_mut() {
+                x.label = dict_label.clone();
+            }
             for (_key, word) in self.dict_words_input.iter_mut() {
                 word.word_header.dict_label = dict_label.clone();
             }
@@ -406,6 +856,24 @@ impl Dictionary {
     pub fn add_word(&mut self, new_word: DictWordMarkdown) {
         let mut new_word = new_word;
 
+        // The entries are added after the metadata of their source. A source without a dict_label
+        // in its metadata gets the label of its first entry.
+        if let Some(x) = self.sources.last_mut() {
+            if x.label.is_empty() {
+                x.label = new_word.word_header.dict_label.clone();
+            }
+            x.word_count += 1;
+        }
+
+        // Normalize the Unicode, the niggahīta, the apostrophes and the hyphens, so that the same
+        // word gets the same url_id and the links match. The metadata is parsed before the entries.
+
+        let source_word = new_word.word_header.word.trim().to_string();
+        normalize::normalize_dict_word(&mut new_word, normalize::parse_niggahita(&self.meta.niggahita));
+        if new_word.word_header.word != source_word {
+            self.normalized_headwords.push((source_word, new_word.word_header.word.clone()));
+        }
+
         // Sanitize the word name.
 
         // Trim whitespace.
@@ -581,8 +1049,8 @@ impl Dictionary {
     pub fn write_entries(&mut self) -> Result<(), Box<dyn Error>> {
         info!("write_entries()");
 
-        let w: Vec<DictWordMarkdown> = self.dict_words_input.values().cloned().collect();
-        let mut letter_groups = LetterGroups::new_from_dict_words(&w);
+        let w: Vec<DictWordMarkdown> = self.merged_words_input();
+        let mut letter_groups = LetterGroups::new_from_dict_words(&w, &self.collation());
 
         info!("Writing {} letter groups ...", letter_groups.len());
 
@@ -604,6 +1072,7 @@ impl Dictionary {
             let data = LetterGroupTemplateData {
                 group: group.clone(),
                 meta: self.meta.clone(),
+                sources: self.sources.clone(),
             };
 
             let content_html = match self.templates.render(template_name, &data) {
@@ -620,7 +1089,8 @@ impl Dictionary {
             let file_content = self.templates.render("content-page.xhtml", &d)?;
 
             // The file names are not sequential (00, 01, 02 ...), they are identified by the index
-            // number of the Pali letter from pali::romanized_pali_letter_index().
+            // number of the Pali letter from pali::romanized_pali_letter_index(), or the number of
+            // the letter group with the other collations.
 
             let group_file_name = format!("entries-{:02}.xhtml", group.letter_index);
             self.entries_manifest.push(EntriesManifest {
@@ -1120,6 +1590,47 @@ impl Dictionary {
         Ok(())
     }
 
+    pub fn collation(&self) -> Collation {
+        Collation::from_meta(&self.meta.collation, &self.meta.collation_alphabet)
+    }
+
+    /// The input entries in the order of the collation. These are not merged, so that the XLSX
+    /// output can be read again as its sources.
+    pub fn sorted_words_input(&self) -> Vec<&DictWordMarkdown> {
+        let mut words: Vec<&DictWordMarkdown> = self.dict_words_input.values().collect();
+        collation::sort_dict_words(&mut words, &self.collation());
+        words
+    }
+
+    /// The input entries in the order of the collation, merged by headword when
+    /// `is_merging_entries()`, for the outputs which are read as a dictionary.
+    pub fn sorted_merged_words_input(&self) -> Vec<DictWordMarkdown> {
+        let words = self.merged_words_input();
+        let mut sorted: Vec<&DictWordMarkdown> = words.iter().collect();
+        collation::sort_dict_words(&mut sorted, &self.collation());
+        sorted.into_iter().cloned().collect()
+    }
+
+    /// The rendered entries in the order of the collation.
+    pub fn sorted_words_render(&self) -> Vec<&DictWord> {
+        let c = self.collation();
+        let mut words: Vec<&DictWord> = self.dict_words_render.values().collect();
+        words.sort_by_cached_key(|w| (c.sort_key(&w.word), w.word.clone(), w.url_id.clone()));
+        words
+    }
+
+    /// The data of the StarDict, C5, LaTeX and TEI templates. The entries are sorted with the
+    /// collation, `dict_words_render` is a list instead of a map by url_id, and `dict_words` is the
+    /// list of input entries. The entries of a combined build are merged in both lists.
+    pub fn template_data(&self) -> Result<Value, Box<dyn Error>> {
+        let mut data = serde_json::to_value(self)?;
+        if let Some(obj) = data.as_object_mut() {
+            obj.insert("dict_words_render".to_string(), serde_json::to_value(self.sorted_words_render())?);
+            obj.insert("dict_words".to_string(), serde_json::to_value(self.sorted_merged_words_input())?);
+        }
+        Ok(data)
+    }
+
     pub fn write_babylon_source(&self) -> Result<(), Box<dyn Error>> {
 
         let mut content = String::new();
@@ -1139,12 +1650,12 @@ impl Dictionary {
 &self.meta.title,
 &self.meta.creator,
 &self.meta.email,
-&self.meta.description,
+format!("{} {}", self.meta.description, self.license.summary).trim(),
 &self.meta.source,
 &self.meta.created_date_opf));
 
         // Write the entries.
-        for (_, word) in self.dict_words_input.iter() {
+        for word in self.sorted_merged_words_input().iter() {
             // Blank line before each entry, including the first.
             content.push_str("\n\n");
 
@@ -1202,6 +1713,11 @@ impl Dictionary {
                 text.push_str(&format!("<p>See also: {}</p>", &s));
             }
 
+            // Source
+            for x in self.sources_by_label(&word.word_header.dict_label).iter() {
+                text.push_str(&format!("<p><i>{}</i></p>", x.attribution));
+            }
+
             content.push_str(&text.replace('\n', ""));
         }
 
@@ -1235,7 +1751,7 @@ impl Dictionary {
                 let mut h = Handlebars::new();
                 h.set_strict_mode(true);
                 h.register_escape_fn(helpers::light_html_escape);
-                match h.render_template(&template_source, &self) {
+                match h.render_template(&template_source, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template: {:?}", e);
@@ -1253,7 +1769,7 @@ impl Dictionary {
                         return Err(Box::new(ToolError::Exit(msg)));
                     }
                 };
-                match self.templates.render(&template, &self) {
+                match self.templates.render(&template, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template {}, {:?}", template, e);
@@ -1297,7 +1813,7 @@ impl Dictionary {
                 let mut h = Handlebars::new();
                 h.set_strict_mode(true);
                 h.register_escape_fn(helpers::light_html_escape);
-                match h.render_template(&template_source, &self) {
+                match h.render_template(&template_source, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template: {:?}", e);
@@ -1308,7 +1824,7 @@ impl Dictionary {
 
             None => {
                 let template = "latex_plain.tex".to_string();
-                match self.templates.render(&template, &self) {
+                match self.templates.render(&template, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template {}, {:?}", template, e);
@@ -1353,7 +1869,7 @@ impl Dictionary {
                 let mut h = Handlebars::new();
                 h.set_strict_mode(true);
                 h.register_escape_fn(helpers::light_html_escape);
-                match h.render_template(&template_source, &self) {
+                match h.render_template(&template_source, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template: {:?}", e);
@@ -1372,7 +1888,7 @@ impl Dictionary {
                     }
                 };
 
-                match self.templates.render(&template, &self) {
+                match self.templates.render(&template, &self.template_data()?) {
                     Ok(x) => x,
                     Err(e) => {
                         let msg = format!("Can't render template {}, {:?}", template, e);
@@ -1411,7 +1927,7 @@ impl Dictionary {
             }
         };
 
-        let mut content = match self.templates.render(&template, &self) {
+        let mut content = match self.templates.render(&template, &self.template_data()?) {
             Ok(x) => x,
             Err(e) => {
                 error!("Can't render template {}, {:?}", template, e);
@@ -1490,19 +2006,44 @@ impl Dictionary {
             return Ok(());
         }
 
-        // NOTE This is assuming that we are processing a single (markdown or json) file, which
-        // represents a single dictionary.
+        // One dictionaries row per source. The words of a single source, or with a label which is
+        // not one of the sources, are in the row of the build metadata.
+        let mut db_dictionaries: BTreeMap<String, DbDictionary> = BTreeMap::new();
 
-        let (_, w) = self.dict_words_render.first_key_value().unwrap();
-        let db_dictionary = Dictionary::get_or_insert_dictionary(&conn, &w.dict_label, &self.meta.title);
+        // Variant readings of the texts in the same database are added as extra lookup keys.
+        let variant_forms = Dictionary::get_variant_forms(&conn);
+
+        // Sutta references in the definitions are resolved with the texts in the same database.
+        let volpage_index = VolpageIndex::from_db(&conn);
+        let sutta_text_uids = Dictionary::get_sutta_text_uids(&conn);
+        let re_link = Regex::new(r"\[([^\]]*)\]\([^\)]*\)").unwrap();
 
         for (_, w) in self.dict_words_render.iter() {
 
+            let db_dictionary = db_dictionaries
+                .entry(w.dict_label.clone())
+                .or_insert_with(|| {
+                    let meta = match self.source_by_label(&w.dict_label) {
+                        Some(x) => &x.meta,
+                        None => &self.meta,
+                    };
+                    Dictionary::get_or_insert_dictionary(&conn, &w.dict_label, meta)
+                });
+
+            let mut inflections = w.inflections.clone();
+            if let Some(forms) = variant_forms.get(&w.word.to_lowercase()) {
+                for f in forms.iter() {
+                    if !inflections.contains(f) && *f != w.word {
+                        inflections.push(f.clone());
+                    }
+                }
+            }
+
             let new_word = NewDictWord {
                 dictionary_id:    &db_dictionary.id,
                 word:             &w.word,
                 word_nom_sg:      &w.word_nom_sg,
-                inflections:      &w.inflections.join(", "),
+                inflections:      &inflections.join(", "),
                 phonetic:         &w.phonetic,
                 transliteration:  &w.transliteration,
                 url_id:           &w.url_id,
@@ -1571,16 +2112,114 @@ impl Dictionary {
 
                     let _db_example = Dictionary::insert_new_example(&conn, &new_example);
                 }
+
+                // References which were turned into links are found in the link text, which is
+                // the normalized reference. The text as written is kept from before the linking.
+                let text = re_link.replace_all(&m.definition_md, "$1");
+                for r in sutta_refs::find_sutta_refs(&text, &self.citation_styles(&w.dict_label)).iter() {
+                    let ref_text = self.sutta_refs.get(&w.url_id)
+                        .and_then(|refs| refs.iter().find(|x| x.normalized_ref == r.normalized_ref))
+                        .map(|x| x.ref_text.as_str())
+                        .unwrap_or(&r.ref_text);
+                    let sutta_uid = volpage_index.resolve(r).unwrap_or_default();
+                    let text_uid = match sutta_text_uids.get(&sutta_uid) {
+                        Some(x) => x.to_string(),
+                        None => "".to_string(),
+                    };
+
+                    let new_reference = NewMeaningReference {
+                        meaning_id: &db_meaning.id,
+                        ref_text,
+                        normalized_ref: &r.normalized_ref,
+                        sutta_uid: &sutta_uid,
+                        text_uid: &text_uid,
+                    };
+
+                    Dictionary::insert_new_meaning_reference(&conn, &new_reference);
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Map the single-word lemmas of the variant readings to their readings, lowercase and without
+    /// punctuation.
+    ///
+    /// The readings are only lookup keys of the database entries whose headword is the lemma. The
+    /// lemmas of more than one word (`Na hi nūna`) and the inflected lemmas are not matched, and the
+    /// other outputs, such as StarDict and MOBI, don't get the readings, since they are built
+    /// without the texts.
+    fn get_variant_forms(conn: &SqliteConnection) -> BTreeMap<String, Vec<String>> {
+        use db_schema::variant_readings::dsl::*;
+
+        let mut forms: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        let items = match variant_readings.load::<DbVariantReading>(conn) {
+            Ok(x) => x,
+            // The table may not exist when the database was created with an earlier schema.
+            Err(_) => return forms,
+        };
+
+        let clean = |s: &str| -> String {
+            s.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+        };
+
+        for v in items.iter() {
+            let l = clean(&v.lemma);
+            let r = clean(&v.reading);
+            if l.is_empty() || r.is_empty() || l == r || l.contains(' ') || r.contains(' ') {
+                continue;
+            }
+            let x = forms.entry(l).or_insert_with(Vec::new);
+            if !x.contains(&r) {
+                x.push(r);
+            }
+        }
+
+        forms
+    }
+
+    /// Map the sutta uids to the uid of a root text linked to them in `sutta_index`.
+    fn get_sutta_text_uids(conn: &SqliteConnection) -> BTreeMap<String, String> {
+        use db_schema::{sutta_index, root_texts};
+
+        let mut text_uids: BTreeMap<String, String> = BTreeMap::new();
+
+        // The tables may not exist when the database was created with an earlier schema.
+        let suttas: Vec<(i32, String)> = match sutta_index::table
+            .select((sutta_index::id, sutta_index::uid))
+            .load(conn)
+        {
+            Ok(x) => x,
+            Err(_) => return text_uids,
+        };
+
+        let texts: Vec<(Option<i32>, String)> = match root_texts::table
+            .select((root_texts::sutta_index_id, root_texts::uid))
+            .filter(root_texts::sutta_index_id.is_not_null())
+            .order(root_texts::id)
+            .load(conn)
+        {
+            Ok(x) => x,
+            Err(_) => return text_uids,
+        };
+
+        let sutta_uids: BTreeMap<i32, String> = suttas.into_iter().collect();
+
+        for (sutta_id, text_uid) in texts.into_iter() {
+            if let Some(sutta_uid) = sutta_id.and_then(|x| sutta_uids.get(&x)) {
+                text_uids.entry(sutta_uid.clone()).or_insert(text_uid);
+            }
+        }
+
+        text_uids
+    }
+
     fn get_or_insert_dictionary(
         conn: &SqliteConnection,
         d_label: &str,
-        d_title: &str)
+        meta: &DictMetadata)
         -> DbDictionary
     {
         use db_schema::dictionaries::dsl::*;
@@ -1595,9 +2234,20 @@ impl Dictionary {
             return items.pop().unwrap();
         }
 
+        let notice = LicenseNotice::from_meta(meta);
+
         let new_dictionary = NewDictionary {
             label: d_label,
-            title: d_title,
+            title: &meta.title,
+            source_language: meta.source_language(),
+            target_language: &meta.target_language,
+            license: &notice.spdx_id,
+            license_name: &notice.name,
+            license_url: &notice.url,
+            copyright_holder: &notice.holder,
+            copyright_year: &meta.copyright_year,
+            attribution: &notice.attribution,
+            source_url: &notice.source_url,
         };
 
         diesel::insert_into(dictionaries::table)
@@ -1694,14 +2344,32 @@ impl Dictionary {
             .expect("Error loading the inserted example.")
     }
 
+    fn insert_new_meaning_reference<'a>(
+        conn: &SqliteConnection,
+        new_reference: &'a NewMeaningReference)
+    {
+        use db_schema::meaning_references;
+
+        diesel::insert_into(meaning_references::table)
+            .values(new_reference)
+            .execute(conn)
+            .expect("Error inserting the meaning reference.");
+    }
+
     pub fn create_render_json(&mut self) -> Result<(), Box<dyn Error>> {
         info!("create_render_json()");
 
         {
-            let entries = &self.dict_words_render
-                .values()
-                .cloned()
-                .collect::<Vec<DictWord>>();
+            let mut entries: Vec<Value> = Vec::new();
+            for w in self.dict_words_render.values() {
+                let mut entry = serde_json::to_value(w)?;
+                if let Some(freq) = self.word_frequencies.get(&w.word) {
+                    if let Some(obj) = entry.as_object_mut() {
+                        obj.insert("frequency".to_string(), serde_json::to_value(freq)?);
+                    }
+                }
+                entries.push(entry);
+            }
             let content = serde_json::to_string(&entries)?;
 
             let mut file = File::create(&self.output_path)?;
@@ -1741,10 +2409,9 @@ impl Dictionary {
         // worksheets.
 
         {
-            let entries_xlsx = &self.dict_words_input
-                .values()
-                .cloned()
-                .map(|i| DictWordXlsx::from_dict_word_markdown(&i))
+            let entries_xlsx = &self.sorted_words_input()
+                .into_iter()
+                .map(|i| DictWordXlsx::from_dict_word_markdown(i))
                 .collect::<Vec<DictWordXlsx>>();
 
             let entries_json: Vec<Value> = serde_json::to_value(&entries_xlsx)?
@@ -1844,6 +2511,14 @@ impl Dictionary {
                 "cover_path".to_string(),
                 "book_id".to_string(),
                 "add_velthuis".to_string(),
+                "source_language".to_string(),
+                "target_language".to_string(),
+                "license".to_string(),
+                "license_name".to_string(),
+                "license_url".to_string(),
+                "copyright_holder".to_string(),
+                "copyright_year".to_string(),
+                "attribution".to_string(),
             ];
 
             // The Words sheet should include all fields, except 5:
@@ -1918,7 +2593,11 @@ impl Dictionary {
     }
 
     pub fn process_text(&mut self) {
+        self.process_normalization_report();
+        self.process_license();
         self.process_strip_html_for_plaintext();
+        // The generated inflections get the lookup conventions too.
+        self.process_add_paradigm_inflections();
         self.process_add_transliterations();
         self.process_links();
         self.process_define_links();
@@ -2227,6 +2906,85 @@ impl Dictionary {
         }
     }
 
+    /// Link the sutta references in the definitions to the sutta uid, with the normalized reference
+    /// as the link text: `M I 23` to `[MN i 23](ssp://suttas/mn23)`. It runs when
+    /// `--sc_data_path` or `--sutta_link_template` is given. The references are checked with the
+    /// suttas of sc-data's `structure/sutta.json`, by their `volpage` or their uid, and the ones
+    /// which are not found are not changed. The plain text outputs have no links.
+    pub fn process_sutta_references(&mut self, app_params: &AppStartParams) -> Result<(), Box<dyn Error>> {
+        match self.output_format {
+            OutputFormat::StardictXmlPlain | OutputFormat::C5Plain | OutputFormat::TeiPlain | OutputFormat::LaTeXPlain => {
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let sc_data_path = match (&app_params.sc_data_path, &app_params.sutta_link_template) {
+            (Some(p), _) => p,
+            (None, Some(_)) => {
+                warn!("The sutta references are only linked when they are found in --sc_data_path.");
+                return Ok(());
+            }
+            (None, None) => return Ok(()),
+        };
+
+        info!("process_sutta_references()");
+
+        let index = VolpageIndex::from_structure_suttas(&sc_data::read_structure_suttas(sc_data_path)?);
+
+        let link_template = match &app_params.sutta_link_template {
+            Some(x) => x.clone(),
+            None => DEFAULT_SUTTA_LINK_TEMPLATE.to_string(),
+        };
+
+        let mut styles: BTreeMap<String, Vec<CitationStyle>> = BTreeMap::new();
+        for w in self.dict_words_input.values() {
+            let label = &w.word_header.dict_label;
+            if !styles.contains_key(label) {
+                styles.insert(label.clone(), self.citation_styles(label));
+            }
+        }
+
+        for (url_id, dict_word) in self.dict_words_input.iter_mut() {
+            let word_styles = &styles[&dict_word.word_header.dict_label];
+
+            let linked: Vec<SuttaRef> = sutta_refs::find_sutta_refs(&dict_word.definition_md, word_styles)
+                .into_iter()
+                .filter(|r| index.resolve(r).is_some())
+                .collect();
+            if linked.is_empty() {
+                continue;
+            }
+
+            dict_word.definition_md = sutta_refs::sutta_refs_to_links(
+                &dict_word.definition_md, word_styles, &index, &link_template);
+            self.sutta_refs.entry(url_id.clone()).or_insert_with(Vec::new).extend(linked);
+        }
+
+        Ok(())
+    }
+
+    /// Read the occurrences of the words from the database given with `--frequency_db_path`.
+    pub fn process_word_frequencies(&mut self, app_params: &AppStartParams) {
+        let p = match &app_params.frequency_db_path {
+            Some(x) => x,
+            None => return,
+        };
+
+        info!("process_word_frequencies()");
+
+        self.word_frequencies = word_frequencies::load_word_frequencies(p, &self.meta.dict_label);
+
+        let missing = self.dict_words_render
+            .values()
+            .filter(|w| !self.word_frequencies.contains_key(&w.word))
+            .count();
+
+        if missing > 0 {
+            warn!("No frequency counts for {} words. Run word_frequencies_to_sqlite after importing the dictionary.", missing);
+        }
+    }
+
     pub fn process_strip_html_for_plaintext(&mut self) {
         info!("process_strip_html_for_plaintext()");
 
@@ -2249,8 +3007,9 @@ impl Dictionary {
         // [abhuṃ](/define/abhuṃ)
         let re_define = Regex::new(r"\[[^0-9\]\(\)]+\]\(/define/(?P<define>[^\(\)]+)\)").unwrap();
 
-        let w: Vec<DictWordMarkdown> = self.dict_words_input.values().cloned().collect();
-        let letter_groups = LetterGroups::new_from_dict_words(&w);
+        // The links go to the merged entries with `merge_entries`.
+        let w: Vec<DictWordMarkdown> = self.merged_words_input();
+        let letter_groups = LetterGroups::new_from_dict_words(&w, &self.collation());
         let words_to_url = letter_groups.words_to_url;
 
         for (_, dict_word) in self.dict_words_input.iter_mut() {
@@ -2306,10 +3065,21 @@ impl Dictionary {
     pub fn process_input_to_render(&mut self) {
         info!("process_input_to_render()");
 
-        // dict_words_input is sorted by key 'word-label-meaning_order'
-        for dwi in self.dict_words_input.values() {
-            let dwr: DictWord = DictWord::from_dict_word_markdown(dwi);
+        self.dict_words_render.clear();
+
+        let words: Vec<DictWord> = if self.is_merging_entries() {
+            for x in self.meta.source_order.iter() {
+                if !self.sources.iter().any(|s| s.label == *x) {
+                    warn!("source_order: there is no source with the label {}", x);
+                }
+            }
+            self.merged_words_render()
+        } else {
+            // dict_words_input is sorted by key 'word-label-meaning_order'
+            self.dict_words_input.values().map(DictWord::from_dict_word_markdown).collect()
+        };
 
+        for dwr in words.into_iter() {
             // If the url_id already exist, append to the meanings.
             // Otherwise, insert as new.
             if let Some(word) = self.dict_words_render.get_mut(&dwr.url_id) {
@@ -2376,7 +3146,7 @@ impl Dictionary {
             }
 
             if !dict_word.word_header.summary.is_empty() {
-                return Ok(());
+                continue;
             }
 
             let mut summary = dict_word.definition_md.trim().to_string();
@@ -2594,6 +3364,7 @@ impl Dictionary {
         valid_words: &[String],
         words_to_url: &BTreeMap<String, String>,
         output_format: OutputFormat,
+        splitter: Option<&CompoundSplitter>,
         text: &str
         )
         -> String
@@ -2609,7 +3380,19 @@ impl Dictionary {
             let sep = caps.get(2).unwrap().as_str().to_string();
 
             let w = word.trim_start_matches('√').to_string();
-            let link = Dictionary::word_to_link(valid_words, &words_to_url, output_format, &w);
+            let mut link = Dictionary::word_to_link(valid_words, &words_to_url, output_format, &w);
+
+            // An unknown word is followed by its linked compound parts: sabbaññu (sabba + aññu)
+            if let Some(splitter) = splitter {
+                if !valid_words.contains(&w) {
+                    if let Some(s) = splitter.split(&w).first() {
+                        let parts: Vec<String> = s.parts.iter()
+                            .map(|p| Dictionary::word_to_link(valid_words, &words_to_url, output_format, &p.headword))
+                            .collect();
+                        link = format!("{} ({})", link, parts.join(" + "));
+                    }
+                }
+            }
 
             linked_text.push_str(&word.replace(&w, &link));
             linked_text.push_str(&sep);
@@ -2624,10 +3407,16 @@ impl Dictionary {
     pub fn process_links(&mut self) {
         info!("process_links()");
 
-        let w: Vec<DictWordMarkdown> = self.dict_words_input.values().cloned().collect();
-        let letter_groups = LetterGroups::new_from_dict_words(&w);
+        let w: Vec<DictWordMarkdown> = self.merged_words_input();
+        let letter_groups = LetterGroups::new_from_dict_words(&w, &self.collation());
         let words_to_url = letter_groups.words_to_url;
 
+        let splitter = if self.meta.split_compounds {
+            Some(CompoundSplitter::new(&self.valid_words))
+        } else {
+            None
+        };
+
         for (_key, dict_word) in self.dict_words_input.iter_mut() {
             for w in dict_word.word_header.synonyms.iter_mut() {
                 *w = Dictionary::word_to_link(&self.valid_words, &words_to_url, self.output_format, w);
@@ -2665,6 +3454,7 @@ impl Dictionary {
                 &self.valid_words,
                 &words_to_url,
                 self.output_format,
+                splitter.as_ref(),
                 &dict_word.word_header.grammar_construction
             );
 
@@ -2672,6 +3462,7 @@ impl Dictionary {
                 &self.valid_words,
                 &words_to_url,
                 self.output_format,
+                None,
                 &dict_word.word_header.grammar_base_construction
             );
 
@@ -2679,6 +3470,7 @@ impl Dictionary {
                 &self.valid_words,
                 &words_to_url,
                 self.output_format,
+                None,
                 &dict_word.word_header.grammar_compound_construction
             );
 
@@ -2702,6 +3494,17 @@ impl Default for Dictionary {
     }
 }
 
+impl DictMetadata {
+    /// The language of the headwords, `pi` when `source_language` is not given.
+    pub fn source_language(&self) -> &str {
+        if self.source_language.is_empty() {
+            "pi"
+        } else {
+            &self.source_language
+        }
+    }
+}
+
 impl Default for DictMetadata {
     fn default() -> Self {
         DictMetadata {
@@ -2721,6 +3524,26 @@ impl Default for DictMetadata {
             add_velthuis: false,
             allow_raw_html: false,
             dont_generate_synonyms: false,
+            dont_generate_inflections: false,
+            add_paradigm_tables: false,
+            split_compounds: false,
+            merge_entries: false,
+            source_order: Vec::new(),
+            display_scripts: Vec::new(),
+            search_scripts: Vec::new(),
+            lookup_conventions: Vec::new(),
+            citation_styles: Vec::new(),
+            niggahita: "".to_string(),
+            collation: "".to_string(),
+            collation_alphabet: "".to_string(),
+            source_language: "".to_string(),
+            target_language: default_target_language(),
+            license: "".to_string(),
+            license_name: "".to_string(),
+            license_url: "".to_string(),
+            copyright_holder: "".to_string(),
+            copyright_year: "".to_string(),
+            attribution: "".to_string(),
         }
     }
 }
@@ -2744,3 +3567,149 @@ fn clean_output_content(text: &str) -> String {
     content
 }
 
+
+/// The labels of the sources of a merge group, in order.
+fn merged_labels(words: &[&DictWordMarkdown]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for w in words.iter() {
+        if !labels.contains(&w.word_header.dict_label) {
+            labels.push(w.word_header.dict_label.clone());
+        }
+    }
+    labels
+}
+
+/// Append the items which are not in the list yet.
+fn push_unique(list: &mut Vec<String>, items: &[String]) {
+    for x in items.iter() {
+        if !x.is_empty() && !list.contains(x) {
+            list.push(x.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_word(word: &str, dict_label: &str, meaning_order: usize, definition_md: &str) -> DictWordMarkdown {
+        let mut word_header = app::new_word_header(word, dict_label);
+        word_header.meaning_order = meaning_order;
+        DictWordMarkdown {
+            word_header,
+            definition_md: definition_md.to_string(),
+        }
+    }
+
+    fn new_merging_dict(source_order: &[&str], words: Vec<DictWordMarkdown>) -> Dictionary {
+        let mut dict = Dictionary::new(OutputFormat::Epub, false, Path::new("."), Path::new("."), None);
+        for label in ["NCPED", "PTS"].iter() {
+            let mut meta = DictMetadata::default();
+            meta.dict_label = label.to_string();
+            dict.add_source(meta);
+        }
+        dict.meta.merge_entries = true;
+        dict.meta.source_order = source_order.iter().map(|x| x.to_string()).collect();
+
+        for w in words.into_iter() {
+            let h = &w.word_header;
+            let k = format!("{}-{}-{}", h.word, h.dict_label, h.meaning_order);
+            dict.dict_words_input.insert(k, w);
+        }
+        dict
+    }
+
+    fn group_labels(dict: &Dictionary) -> Vec<Vec<String>> {
+        dict.merge_groups().iter()
+            .map(|words| words.iter().map(|w| format!("{} {}", w.word_header.dict_label, w.word_header.meaning_order)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn merge_groups_follow_source_order() {
+        let words = vec![
+            new_word("dhamma", "NCPED", 1, "nature"),
+            new_word("dhamma", "PTS", 2, "doctrine"),
+            new_word("Dhamma", "PTS", 1, "norm"),
+            new_word("sacca", "NCPED", 1, "true"),
+        ];
+
+        let dict = new_merging_dict(&[], words.clone());
+        assert!(dict.is_merging_entries());
+        assert_eq!(group_labels(&dict), vec![
+            vec!["NCPED 1", "PTS 1", "PTS 2"],
+            vec!["NCPED 1"],
+        ]);
+
+        let dict = new_merging_dict(&["PTS"], words);
+        assert_eq!(group_labels(&dict), vec![
+            vec!["PTS 1", "PTS 2", "NCPED 1"],
+            vec!["NCPED 1"],
+        ]);
+    }
+
+    #[test]
+    fn merged_words_input_unites_the_word_lists() {
+        let mut a = new_word("dhamma", "NCPED", 1, "nature");
+        a.word_header.inflections = vec!["dhammo".to_string()];
+        a.word_header.see_also = vec!["sacca".to_string()];
+        let mut b = new_word("Dhamma", "PTS", 1, "norm\n");
+        b.word_header.inflections = vec!["dhammo".to_string(), "dhammaṃ".to_string()];
+        b.word_header.see_also = vec!["sacca".to_string(), "".to_string(), "sīla".to_string()];
+
+        let dict = new_merging_dict(&[], vec![a, b, new_word("sacca", "PTS", 1, "true")]);
+        let res = dict.merged_words_input();
+        assert_eq!(res.len(), 2);
+
+        let h = &res[0].word_header;
+        assert_eq!(h.word, "dhamma");
+        assert_eq!(h.dict_label, "NCPED, PTS");
+        assert_eq!(h.inflections, vec!["dhammo", "Dhamma", "dhammaṃ"]);
+        assert_eq!(h.see_also, vec!["sacca", "sīla"]);
+        assert_eq!(res[0].definition_md, "[NCPED] nature\n\n[PTS] norm");
+
+        assert_eq!(res[1].word_header.dict_label, "PTS");
+        assert_eq!(res[1].definition_md, "true");
+    }
+
+    #[test]
+    fn merged_words_render_labels_each_meaning() {
+        let mut a = new_word("dhamma", "NCPED", 1, "nature");
+        a.word_header.see_also = vec!["sacca".to_string()];
+        let mut b = new_word("dhamma", "PTS", 1, "norm");
+        b.word_header.inflections = vec!["dhammo".to_string()];
+        b.word_header.see_also = vec!["sīla".to_string(), "sacca".to_string()];
+        let c = new_word("dhamma", "PTS", 2, "doctrine");
+
+        let dict = new_merging_dict(&["PTS"], vec![a, b, c]);
+        let res = dict.merged_words_render();
+        assert_eq!(res.len(), 1);
+
+        let w = &res[0];
+        assert_eq!(w.dict_label, "PTS, NCPED");
+        assert_eq!(w.inflections, vec!["dhammo"]);
+
+        let defs: Vec<&str> = w.meanings.iter().map(|m| m.definition_md.as_str()).collect();
+        assert_eq!(defs, vec!["[PTS] norm", "[PTS] doctrine", "[NCPED] nature"]);
+
+        assert!(w.meanings[0].see_also.is_empty());
+        assert!(w.meanings[1].see_also.is_empty());
+        assert_eq!(w.meanings[2].see_also, vec!["sīla", "sacca"]);
+    }
+
+    #[test]
+    fn merged_labels_in_order() {
+        let a = new_word("dhamma", "PTS", 1, "");
+        let b = new_word("dhamma", "PTS", 2, "");
+        let c = new_word("dhamma", "NCPED", 1, "");
+        assert_eq!(merged_labels(&[&a, &b, &c]), vec!["PTS", "NCPED"]);
+        assert_eq!(merged_labels(&[&a, &b]), vec!["PTS"]);
+    }
+
+    #[test]
+    fn push_unique_skips_empty_and_known_items() {
+        let mut list = vec!["a".to_string()];
+        push_unique(&mut list, &["b".to_string(), "".to_string(), "a".to_string(), "b".to_string()]);
+        assert_eq!(list, vec!["a", "b"]);
+    }
+}
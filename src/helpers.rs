@@ -11,6 +11,10 @@ use comrak::{markdown_to_html, ComrakOptions};
 
 use pali_dict_core::pali;
 
+use crate::morphology;
+use crate::scripts::{self, Script};
+use crate::word_frequencies::WordFrequency;
+
 pub fn markdown_helper(
     h: &Helper<'_, '_>,
     _: &Handlebars,
@@ -56,6 +60,25 @@ pub fn to_velthuis(
     Ok(())
 }
 
+/// `{{to_script word "sinhala"}}`, see `Script::from_name()` for the script names. Unknown scripts
+/// leave the word as it is.
+pub fn to_script(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let param = h.param(0).unwrap();
+    let script_name = h.param(1).unwrap().value().render();
+    let word = param.value().render();
+    match Script::from_name(&script_name) {
+        Some(script) => out.write(&scripts::to_script(&word, script))?,
+        None => out.write(&word)?,
+    }
+    Ok(())
+}
+
 pub fn word_title(
     h: &Helper<'_, '_>,
     _: &Handlebars,
@@ -81,6 +104,17 @@ pub fn word_title(
         text.push_str(&format!("{} - ", word_velthuis));
     }
 
+    if let Some(names) = meta.get("display_scripts").and_then(|x| x.as_array()) {
+        for name in names.iter().filter_map(|x| x.as_str()) {
+            if let Some(script) = Script::from_name(name) {
+                let s = scripts::to_script(&word, script);
+                if s != word {
+                    text.push_str(&format!("{} - ", s));
+                }
+            }
+        }
+    }
+
     text.push_str(&word);
 
     out.write(&text)?;
@@ -407,6 +441,134 @@ pub fn phonetic_transliteration_plain(
     Ok(())
 }
 
+/// The frequency line of a word, if `word_frequencies` has it.
+fn format_word_frequency(word: &serde_json::Value, word_frequencies: &serde_json::Value) -> Option<String> {
+    let freq = word_frequencies.get(word.render())?;
+    let freq: WordFrequency = serde_json::from_value(freq.clone()).ok()?;
+    Some(format!("Frequency: {}", freq.summary()))
+}
+
+pub fn word_frequency(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+
+    let word = h.param(0).unwrap().value();
+    let word_frequencies = h.param(1).unwrap().value();
+
+    if let Some(x) = format_word_frequency(word, word_frequencies) {
+        out.write(&format!("<p>{}</p>", x))?;
+    }
+    Ok(())
+}
+
+pub fn word_frequency_plain(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+
+    let word = h.param(0).unwrap().value();
+    let word_frequencies = h.param(1).unwrap().value();
+
+    if let Some(x) = format_word_frequency(word, word_frequencies) {
+        out.write(&format!("\n{}", x))?;
+    }
+    Ok(())
+}
+
+/// The attribution lines of an entry in a combined build, from the sources with the entry's
+/// `dict_label`. A merged entry has more labels: `NCPED, PTS`. A single source has no attribution
+/// line.
+fn format_source_attribution(dict_label: &serde_json::Value, sources: &serde_json::Value) -> Vec<String> {
+    let sources = match sources.as_array() {
+        Some(x) if x.len() > 1 => x,
+        _ => return vec![],
+    };
+    let labels = dict_label.as_str().unwrap_or_default();
+
+    labels.split(',')
+        .filter_map(|label| sources.iter()
+            .find(|x| x.get("label").and_then(|l| l.as_str()) == Some(label.trim())))
+        .filter_map(|x| x.get("attribution").and_then(|a| a.as_str()).map(|a| a.to_string()))
+        .collect()
+}
+
+pub fn source_attribution(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+
+    let dict_label = h.param(0).unwrap().value();
+    let sources = h.param(1).unwrap().value();
+
+    for x in format_source_attribution(dict_label, sources).iter() {
+        out.write(&format!("<p><i>{}</i></p>", light_html_escape(x)))?;
+    }
+    Ok(())
+}
+
+pub fn source_attribution_plain(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+
+    let dict_label = h.param(0).unwrap().value();
+    let sources = h.param(1).unwrap().value();
+
+    let lines = format_source_attribution(dict_label, sources);
+    if !lines.is_empty() {
+        out.write(&format!("\n{}\n", lines.join("\n")))?;
+    }
+    Ok(())
+}
+
+/// The declension or conjugation tables of a word, from the grammar fields of a `word_header`, or of
+/// the first meaning of a rendered word.
+pub fn format_paradigm_tables_html(dict_word: &serde_json::Value) -> String {
+    let field = |k: &str| dict_word.get(k).map(|x| x.render()).unwrap_or_default();
+
+    let (speech, gender) = match dict_word.get("grammar_speech") {
+        Some(x) => (x.render(), field("grammar_gender")),
+        None => {
+            let grammar = dict_word.pointer("/meanings/0/grammar");
+            let g = |k: &str| grammar.and_then(|x| x.get(k)).map(|x| x.render()).unwrap_or_default();
+            (g("speech"), g("gender"))
+        }
+    };
+
+    let tables = morphology::paradigm_tables(&field("word"), &field("word_nom_sg"), &speech, &gender);
+    morphology::format_paradigm_tables_html(&tables)
+}
+
+pub fn paradigm_tables(
+    h: &Helper<'_, '_>,
+    _: &Handlebars,
+    _: &Context,
+    _rc: &mut RenderContext<'_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+
+    let dict_word = h.param(0).unwrap().value();
+    let add_paradigm_tables = h.param(1).unwrap().value().as_bool().unwrap_or(false);
+
+    if add_paradigm_tables {
+        out.write(&format_paradigm_tables_html(dict_word))?;
+    }
+    Ok(())
+}
+
 pub fn md2html(markdown: &str, allow_raw_html: bool) -> String {
     let mut opts = ComrakOptions::default();
     opts.smart = true;
@@ -458,6 +620,14 @@ pub fn uppercase_first_letter(s: &str) -> String {
     }
 }
 
+/// Lowercase words of a Pali text, without punctuation.
+pub fn lowercase_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_lowercase())
+        .collect()
+}
+
 pub fn light_html_escape(data: &str) -> String {
     //lazy_static! {
     //    static ref RE_AMP: Regex = Regex::new(r"&\b").unwrap();
@@ -27,14 +27,27 @@ extern crate chrono;
 extern crate comrak;
 extern crate handlebars;
 extern crate deunicode;
+extern crate unicode_normalization;
 
 extern crate pali_dict_core;
 
 pub mod app;
+pub mod collation;
+pub mod concordance;
+pub mod corpus_examples;
+pub mod word_frequencies;
+pub mod morphology;
+pub mod deinflect;
+pub mod compounds;
+pub mod scripts;
+pub mod normalize;
+pub mod license;
 pub mod dictionary;
 pub mod error;
 pub mod helpers;
+pub mod po;
 pub mod sc_data;
+pub mod sutta_refs;
 pub mod db_models;
 pub mod db_schema;
 
@@ -0,0 +1,188 @@
+use crate::dictionary::DictMetadata;
+
+/// The licenses which are known by their SPDX id: (id, name, URL, is a free license).
+const LICENSES: [(&str, &str, &str, bool); 13] = [
+    ("CC0-1.0", "CC0 1.0 Universal (CC0 1.0) Public Domain Dedication", "https://creativecommons.org/publicdomain/zero/1.0/", true),
+    ("CC-PDDC", "Creative Commons Public Domain Dedication and Certification", "https://creativecommons.org/licenses/publicdomain/", true),
+    ("LicenseRef-PublicDomain", "Public Domain", "", true),
+    ("CC-BY-3.0", "Creative Commons Attribution 3.0 Unported", "https://creativecommons.org/licenses/by/3.0/", true),
+    ("CC-BY-4.0", "Creative Commons Attribution 4.0 International", "https://creativecommons.org/licenses/by/4.0/", true),
+    ("CC-BY-SA-3.0", "Creative Commons Attribution-ShareAlike 3.0 Unported", "https://creativecommons.org/licenses/by-sa/3.0/", true),
+    ("CC-BY-SA-4.0", "Creative Commons Attribution-ShareAlike 4.0 International", "https://creativecommons.org/licenses/by-sa/4.0/", true),
+    ("CC-BY-NC-4.0", "Creative Commons Attribution-NonCommercial 4.0 International", "https://creativecommons.org/licenses/by-nc/4.0/", false),
+    ("CC-BY-NC-SA-4.0", "Creative Commons Attribution-NonCommercial-ShareAlike 4.0 International", "https://creativecommons.org/licenses/by-nc-sa/4.0/", false),
+    ("CC-BY-NC-ND-4.0", "Creative Commons Attribution-NonCommercial-NoDerivatives 4.0 International", "https://creativecommons.org/licenses/by-nc-nd/4.0/", false),
+    ("GPL-2.0-or-later", "GNU General Public License ver. 2.0 and any later version", "https://www.gnu.org/licenses/gpl-2.0.html", true),
+    ("GPL-3.0-or-later", "GNU General Public License ver. 3.0 and any later version", "https://www.gnu.org/licenses/gpl-3.0.html", true),
+    ("MIT", "MIT License", "https://opensource.org/licenses/MIT", true),
+];
+
+/// The licenses which put the text in the public domain, these have no copyright line.
+const PUBLIC_DOMAIN: [&str; 3] = ["CC0-1.0", "CC-PDDC", "LicenseRef-PublicDomain"];
+
+/// The license and the attribution as they are rendered in the outputs, from the license fields of
+/// `DictMetadata`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LicenseNotice {
+    pub spdx_id: String,
+    pub name: String,
+    pub url: String,
+    /// The TEI availability status: `free`, `restricted` or `unknown`.
+    pub status: String,
+    /// The copyright holder, or the creator when it is not given.
+    pub holder: String,
+    /// `Copyright (C) 2021 Buddhist Publication Society.`, or the public domain dedication.
+    pub copyright: String,
+    pub attribution: String,
+    pub source_url: String,
+    /// The notice in one line, for the StarDict and Babylon descriptions.
+    pub summary: String,
+}
+
+impl LicenseNotice {
+    pub fn from_meta(meta: &DictMetadata) -> LicenseNotice {
+        let spdx_id = meta.license.trim().to_string();
+
+        let known = LICENSES.iter().find(|x| x.0.eq_ignore_ascii_case(&spdx_id));
+        if known.is_none() && !spdx_id.is_empty() {
+            warn!("Unknown SPDX license id: {}", spdx_id);
+        }
+
+        // The name and the URL in the metadata override the ones of the known licenses.
+        let name = match (meta.license_name.trim(), known) {
+            ("", Some(x)) => x.1.to_string(),
+            ("", None) => spdx_id.clone(),
+            (x, _) => x.to_string(),
+        };
+
+        let url = match (meta.license_url.trim(), known) {
+            ("", Some(x)) => x.2.to_string(),
+            (x, _) => x.to_string(),
+        };
+
+        let status = match known {
+            Some(x) if x.3 => "free",
+            Some(_) => "restricted",
+            None => "unknown",
+        };
+
+        let holder = if meta.copyright_holder.trim().is_empty() {
+            meta.creator.trim().to_string()
+        } else {
+            meta.copyright_holder.trim().to_string()
+        };
+
+        let is_public_domain = PUBLIC_DOMAIN.iter().any(|x| x.eq_ignore_ascii_case(&spdx_id));
+
+        // Without a license, there is no claim about the copyright either.
+        let copyright = if is_public_domain {
+            "This dictionary file is dedicated to the Public Domain.".to_string()
+        } else if name.is_empty() || holder.is_empty() {
+            "".to_string()
+        } else if meta.copyright_year.trim().is_empty() {
+            format!("Copyright (C) {}.", holder)
+        } else {
+            format!("Copyright (C) {} {}.", meta.copyright_year.trim(), holder)
+        };
+
+        let attribution = meta.attribution.trim().to_string();
+
+        let mut parts: Vec<String> = Vec::new();
+        if !copyright.is_empty() {
+            parts.push(copyright.clone());
+        }
+        match (name.is_empty(), url.is_empty()) {
+            (false, false) => parts.push(format!("License: {} ({}).", name, url)),
+            (false, true) => parts.push(format!("License: {}.", name)),
+            (true, false) => parts.push(format!("License: {}.", url)),
+            (true, true) => {}
+        }
+        if !attribution.is_empty() {
+            parts.push(attribution.clone());
+        }
+
+        LicenseNotice {
+            spdx_id,
+            name,
+            url,
+            status: status.to_string(),
+            holder,
+            copyright,
+            attribution,
+            source_url: meta.source.trim().to_string(),
+            summary: parts.join(" "),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cc0_is_public_domain() {
+        let meta = DictMetadata {
+            creator: "Ānandajoti Bhikkhu".to_string(),
+            license: "CC0-1.0".to_string(),
+            ..Default::default()
+        };
+
+        let x = LicenseNotice::from_meta(&meta);
+        assert_eq!(x.status, "free");
+        assert_eq!(x.holder, "Ānandajoti Bhikkhu");
+        assert_eq!(x.copyright, "This dictionary file is dedicated to the Public Domain.");
+        assert_eq!(x.summary, "This dictionary file is dedicated to the Public Domain. \
+License: CC0 1.0 Universal (CC0 1.0) Public Domain Dedication (https://creativecommons.org/publicdomain/zero/1.0/).");
+    }
+
+    #[test]
+    fn cc_by_has_copyright_and_attribution() {
+        let meta = DictMetadata {
+            creator: "Editor".to_string(),
+            license: "cc-by-4.0".to_string(),
+            copyright_holder: "Buddhist Publication Society".to_string(),
+            copyright_year: "2021".to_string(),
+            attribution: "Based on the BPS edition.".to_string(),
+            ..Default::default()
+        };
+
+        let x = LicenseNotice::from_meta(&meta);
+        assert_eq!(x.spdx_id, "cc-by-4.0");
+        assert_eq!(x.name, "Creative Commons Attribution 4.0 International");
+        assert_eq!(x.status, "free");
+        assert_eq!(x.holder, "Buddhist Publication Society");
+        assert_eq!(x.summary, "Copyright (C) 2021 Buddhist Publication Society. \
+License: Creative Commons Attribution 4.0 International (https://creativecommons.org/licenses/by/4.0/). \
+Based on the BPS edition.");
+    }
+
+    #[test]
+    fn no_license_is_unknown() {
+        let meta = DictMetadata {
+            creator: "Editor".to_string(),
+            ..Default::default()
+        };
+
+        let x = LicenseNotice::from_meta(&meta);
+        assert_eq!(x.status, "unknown");
+        assert_eq!(x.name, "");
+        assert_eq!(x.copyright, "");
+        assert_eq!(x.summary, "");
+    }
+
+    #[test]
+    fn non_commercial_and_unknown_licenses() {
+        let mut meta = DictMetadata {
+            license: "CC-BY-NC-SA-4.0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(LicenseNotice::from_meta(&meta).status, "restricted");
+
+        meta.license = "LicenseRef-Custom".to_string();
+        meta.license_name = "Custom license".to_string();
+        let x = LicenseNotice::from_meta(&meta);
+        assert_eq!(x.status, "unknown");
+        assert_eq!(x.name, "Custom license");
+        assert_eq!(x.url, "");
+    }
+}
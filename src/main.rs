@@ -30,16 +30,29 @@ extern crate chrono;
 extern crate comrak;
 extern crate handlebars;
 extern crate deunicode;
+extern crate unicode_normalization;
 
 extern crate pali_dict_core;
 
 use clap::App;
 
 pub mod app;
+pub mod collation;
+pub mod concordance;
+pub mod corpus_examples;
+pub mod word_frequencies;
+pub mod morphology;
+pub mod deinflect;
+pub mod compounds;
+pub mod scripts;
+pub mod normalize;
+pub mod license;
 pub mod dictionary;
 pub mod error;
 pub mod helpers;
+pub mod po;
 pub mod sc_data;
+pub mod sutta_refs;
 pub mod db_models;
 pub mod db_schema;
 
@@ -130,22 +143,130 @@ fn main() {
                 dict.process_grammar_note();
                 dict.process_see_also_from_definition(app_params.dont_remove_see_also);
                 ok_or_exit(app_params.used_first_arg, dict.process_summary());
+                ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
             }
 
             ok_or_exit(app_params.used_first_arg, dict.write_markdown());
         }
 
+        RunCommand::EpubToMarkdown => {
+            let (i_p, o_p) = get_input_output(&app_params);
+            let mut dict = Dictionary::new(
+                app_params.output_format,
+                app_params.allow_raw_html,
+                &i_p,
+                &o_p,
+                app_params.entries_template.clone());
+
+            let s = app_params.clone().source_paths.expect("source_paths is missing");
+            let s_p = PathBuf::from(s.get(0).unwrap());
+
+            let meta = ok_or_exit(
+                app_params.used_first_arg,
+                app::process_epub_entries(
+                    &s_p,
+                    &app_params.dict_label,
+                    &mut dict,
+                ));
+
+            dict.meta = meta;
+
+            if app_params.reuse_metadata {
+                ok_or_exit(app_params.used_first_arg, dict.reuse_metadata());
+            }
+
+            dict.meta.created_date_human = "".to_string();
+            dict.meta.created_date_opf = "".to_string();
+
+            info!("Added words: {}", dict.len());
+
+            dict.use_cli_overrides(&app_params);
+
+            ok_or_exit(app_params.used_first_arg, dict.write_markdown());
+        }
+
         RunCommand::SuttaCentralPoTextsToSqlite => {
             let i = app_params.clone().source_paths.expect("source_paths is missing");
             let i_p = PathBuf::from(i.get(0).unwrap());
             let o_p = app_params.clone().output_path.expect("output_path is missing.");
-            let json_path = app_params.clone().po_text_json_path.expect("po_text_json_path is missing.");
 
             ok_or_exit(
                 app_params.used_first_arg,
                 sc_data::process_suttacentral_root_po_texts_to_sqlite(
                     &i_p,
-                    &json_path,
+                    &o_p,
+                ));
+        }
+
+        RunCommand::SuttaCentralBilaraToSqlite => {
+            let i = app_params.clone().source_paths.expect("source_paths is missing");
+            let i_p = PathBuf::from(i.get(0).unwrap());
+            let sc_p = app_params.clone().sc_data_path.expect("sc_data_path is missing.");
+            let o_p = app_params.clone().output_path.expect("output_path is missing.");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                sc_data::process_suttacentral_bilara_to_sqlite(
+                    &i_p,
+                    &sc_p,
+                    &o_p,
+                ));
+        }
+
+        RunCommand::CorpusExamplesToSqlite => {
+            let o_p = app_params.clone().output_path.expect("output_path is missing.");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                corpus_examples::process_corpus_examples_to_sqlite(
+                    &o_p,
+                    &app_params.dict_label,
+                    app_params.examples_count,
+                    &app_params.markdown_path,
+                ));
+        }
+
+        RunCommand::Lookup => {
+            let p = app_params.clone().source_paths.expect("source_paths is missing.");
+            let form = app_params.clone().lookup_word.expect("lookup_word is missing.");
+
+            ok_or_exit(app_params.used_first_arg, deinflect::lookup(&p[0], &form));
+        }
+
+        RunCommand::SplitCompound => {
+            let p = app_params.clone().source_paths.expect("source_paths is missing.");
+            let word = app_params.clone().lookup_word.expect("lookup_word is missing.");
+
+            ok_or_exit(app_params.used_first_arg, compounds::split_compound(&p[0], &word));
+        }
+
+        RunCommand::WordFrequenciesToSqlite => {
+            let o_p = app_params.clone().output_path.expect("output_path is missing.");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                word_frequencies::process_word_frequencies_to_sqlite(&o_p, &app_params.dict_label));
+        }
+
+        RunCommand::ResolveReference => {
+            let i = app_params.clone().source_paths.expect("source_paths is missing");
+            let i_p = PathBuf::from(i.get(0).unwrap());
+            let r = app_params.clone().reference.expect("reference is missing");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                concordance::resolve_reference(&i_p, &r));
+        }
+
+        RunCommand::SuttaCentralStructureToSqlite => {
+            let i = app_params.clone().source_paths.expect("source_paths is missing");
+            let i_p = PathBuf::from(i.get(0).unwrap());
+            let o_p = app_params.clone().output_path.expect("output_path is missing.");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                sc_data::process_suttacentral_structure_to_sqlite(
+                    &i_p,
                     &o_p,
                 ));
         }
@@ -160,6 +281,8 @@ fn main() {
                 sc_data::process_suttacentral_html_texts_to_sqlite(
                     &i_p,
                     &o_p,
+                    &app_params.languages,
+                    &app_params.divisions,
                 ));
         }
 
@@ -187,6 +310,50 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            if dict.meta.citation_styles.is_empty() {
+                dict.meta.citation_styles = vec!["nyanatiloka".to_string(), "sutta_number".to_string()];
+            }
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
+            dict.process_tidy();
+            ok_or_exit(app_params.used_first_arg, dict.process_summary());
+
+            info!("Added words: {}", dict.len());
+
+            ok_or_exit(app_params.used_first_arg, dict.write_markdown());
+        }
+
+        RunCommand::HtmlToMarkdown => {
+            let (i_p, o_p) = get_input_output(&app_params);
+            let mut dict = Dictionary::new(
+                app_params.output_format,
+                app_params.allow_raw_html,
+                &i_p,
+                &o_p,
+                app_params.entries_template.clone());
+
+            if app_params.reuse_metadata {
+                ok_or_exit(app_params.used_first_arg, dict.reuse_metadata());
+            }
+
+            dict.meta.created_date_human = "".to_string();
+            dict.meta.created_date_opf = "".to_string();
+
+            let s = app_params.clone().source_paths.expect("source_paths is missing");
+            let s_p = PathBuf::from(s.get(0).unwrap());
+            let rules_path = app_params.clone().rules_path.expect("rules_path is missing.");
+
+            ok_or_exit(
+                app_params.used_first_arg,
+                app::process_html_entries(
+                    &s_p,
+                    &rules_path,
+                    &app_params.dict_label,
+                    &mut dict,
+                ));
+
+            dict.use_cli_overrides(&app_params);
+
             dict.process_tidy();
             ok_or_exit(app_params.used_first_arg, dict.process_summary());
 
@@ -208,6 +375,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             match app_params.run_command {
                 RunCommand::MarkdownToEbook | RunCommand::MarkdownToSqlite => {
                     ok_or_exit(
@@ -230,8 +401,22 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            // The database has the entries of each source in its own dictionary.
+            match app_params.run_command {
+                RunCommand::MarkdownToSqlite | RunCommand::XlsxToSqlite if dict.is_merging_entries() => {
+                    warn!("The entries are not merged in the database.");
+                    dict.meta.merge_entries = false;
+                }
+
+                _ => {},
+            }
+
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
+            dict.process_word_frequencies(&app_params);
+
             match app_params.run_command {
                 RunCommand::MarkdownToEbook | RunCommand::XlsxToEbook => {
                     ok_or_exit(app_params.used_first_arg, dict.create_ebook(&app_params));
@@ -266,6 +451,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             match app_params.run_command {
                 RunCommand::MarkdownToBabylon => {
                     ok_or_exit(
@@ -288,6 +477,8 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
             // Convert /define/word links with bword://word, as recognized by Stardict.
@@ -312,6 +503,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             match app_params.run_command {
                 RunCommand::MarkdownToStardict => {
                     ok_or_exit(
@@ -334,8 +529,12 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
+            dict.process_word_frequencies(&app_params);
+
             ok_or_exit(app_params.used_first_arg, dict.create_stardict());
         }
 
@@ -352,6 +551,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             match app_params.run_command {
                 RunCommand::MarkdownToC5 => {
                     ok_or_exit(
@@ -374,6 +577,8 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
             ok_or_exit(app_params.used_first_arg, dict.create_c5());
@@ -392,6 +597,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             match app_params.run_command {
                 RunCommand::MarkdownToTei => {
                     ok_or_exit(
@@ -414,6 +623,8 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
             ok_or_exit(app_params.used_first_arg, dict.create_tei());
@@ -432,6 +643,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             ok_or_exit(
                 app_params.used_first_arg,
                 app::process_xlsx_list(source_paths, &mut dict),
@@ -441,6 +656,8 @@ fn main() {
 
             dict.use_cli_overrides(&app_params);
 
+            ok_or_exit(app_params.used_first_arg, dict.process_sutta_references(&app_params));
+
             dict.process_text();
 
             ok_or_exit(app_params.used_first_arg, dict.create_latex());
@@ -459,6 +676,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             ok_or_exit(
                 app_params.used_first_arg,
                 app::process_markdown_list(source_paths, &mut dict),
@@ -484,6 +705,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             ok_or_exit(
                 app_params.used_first_arg,
                 app::process_xlsx_list(source_paths, &mut dict),
@@ -509,6 +734,10 @@ fn main() {
             let p = paths.expect("source_paths is missing.");
             let source_paths = p.to_vec();
 
+            if let Some(ref x) = app_params.combined_metadata_path {
+                ok_or_exit(app_params.used_first_arg, dict.use_combined_metadata(x));
+            }
+
             let mp = app_params.metadata_path.clone();
             let metadata_path = mp.expect("metadata_path is missing");
 
@@ -0,0 +1,710 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+/// A declension or conjugation table of a word.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ParadigmTable {
+    /// Masculine a-stem, Present, etc.
+    pub title: String,
+    /// Singular, Plural
+    pub columns: Vec<String>,
+    pub rows: Vec<ParadigmRow>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ParadigmRow {
+    /// Nominative, 3rd person, etc.
+    pub label: String,
+    /// The forms in each column. A cell can have alternative forms.
+    pub cells: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordClass {
+    Noun,
+    Adjective,
+    Verb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stem {
+    A,
+    LongA,
+    I,
+    LongI,
+    U,
+    LongU,
+    Ant,
+    In,
+}
+
+const CASES: [&str; 8] = [
+    "Nominative", "Accusative", "Instrumental", "Dative", "Ablative", "Genitive", "Locative", "Vocative",
+];
+
+const PERSONS: [&str; 3] = ["3rd", "2nd", "1st"];
+
+/// Singular and plural endings for each case, alternatives separated with `/`. The endings are added
+/// to the stem without its final vowel (`dhamm-`, `bhagav-`, `hatth-`).
+type Endings = [(&'static str, &'static str); 8];
+
+const MASC_A: Endings = [
+    ("o", "ā/āse"),
+    ("aṃ", "e"),
+    ("ena", "ehi/ebhi"),
+    ("āya/assa", "ānaṃ"),
+    ("ā/asmā/amhā", "ehi/ebhi"),
+    ("assa", "ānaṃ"),
+    ("e/asmiṃ/amhi", "esu"),
+    ("a", "ā"),
+];
+
+const NEUT_A: Endings = [
+    ("aṃ", "āni/ā"),
+    ("aṃ", "āni/e"),
+    ("ena", "ehi/ebhi"),
+    ("āya/assa", "ānaṃ"),
+    ("ā/asmā/amhā", "ehi/ebhi"),
+    ("assa", "ānaṃ"),
+    ("e/asmiṃ/amhi", "esu"),
+    ("a", "āni"),
+];
+
+const FEM_LONG_A: Endings = [
+    ("ā", "ā/āyo"),
+    ("aṃ", "ā/āyo"),
+    ("āya", "āhi/ābhi"),
+    ("āya", "ānaṃ"),
+    ("āya", "āhi/ābhi"),
+    ("āya", "ānaṃ"),
+    ("āya/āyaṃ", "āsu"),
+    ("e", "ā/āyo"),
+];
+
+const MASC_I: Endings = [
+    ("i", "ī/ayo"),
+    ("iṃ", "ī/ayo"),
+    ("inā", "īhi/ībhi"),
+    ("ino/issa", "īnaṃ"),
+    ("inā/ismā/imhā", "īhi/ībhi"),
+    ("ino/issa", "īnaṃ"),
+    ("ismiṃ/imhi", "īsu"),
+    ("i", "ī/ayo"),
+];
+
+const NEUT_I: Endings = [
+    ("i/iṃ", "īni/ī"),
+    ("iṃ", "īni/ī"),
+    ("inā", "īhi/ībhi"),
+    ("ino/issa", "īnaṃ"),
+    ("inā/ismā/imhā", "īhi/ībhi"),
+    ("ino/issa", "īnaṃ"),
+    ("ismiṃ/imhi", "īsu"),
+    ("i", "īni/ī"),
+];
+
+const FEM_I: Endings = [
+    ("i", "ī/iyo"),
+    ("iṃ", "ī/iyo"),
+    ("iyā", "īhi/ībhi"),
+    ("iyā", "īnaṃ"),
+    ("iyā", "īhi/ībhi"),
+    ("iyā", "īnaṃ"),
+    ("iyā/iyaṃ", "īsu"),
+    ("i", "ī/iyo"),
+];
+
+const FEM_LONG_I: Endings = [
+    ("ī", "ī/iyo"),
+    ("iṃ", "ī/iyo"),
+    ("iyā", "īhi/ībhi"),
+    ("iyā", "īnaṃ"),
+    ("iyā", "īhi/ībhi"),
+    ("iyā", "īnaṃ"),
+    ("iyā/iyaṃ", "īsu"),
+    ("i", "ī/iyo"),
+];
+
+const MASC_U: Endings = [
+    ("u", "ū/avo"),
+    ("uṃ", "ū/avo"),
+    ("unā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("unā/usmā/umhā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("usmiṃ/umhi", "ūsu"),
+    ("u", "ū/avo"),
+];
+
+const NEUT_U: Endings = [
+    ("u/uṃ", "ūni/ū"),
+    ("uṃ", "ūni/ū"),
+    ("unā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("unā/usmā/umhā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("usmiṃ/umhi", "ūsu"),
+    ("u", "ūni/ū"),
+];
+
+const FEM_U: Endings = [
+    ("u", "ū/uyo"),
+    ("uṃ", "ū/uyo"),
+    ("uyā", "ūhi/ūbhi"),
+    ("uyā", "ūnaṃ"),
+    ("uyā", "ūhi/ūbhi"),
+    ("uyā", "ūnaṃ"),
+    ("uyā/uyaṃ", "ūsu"),
+    ("u", "ū/uyo"),
+];
+
+const MASC_LONG_U: Endings = [
+    ("ū", "ū/uvo"),
+    ("uṃ", "ū/uvo"),
+    ("unā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("unā/usmā", "ūhi/ūbhi"),
+    ("uno/ussa", "ūnaṃ"),
+    ("usmiṃ/umhi", "ūsu"),
+    ("u", "ū/uvo"),
+];
+
+const FEM_LONG_U: Endings = [
+    ("ū", "ū/uyo"),
+    ("uṃ", "ū/uyo"),
+    ("uyā", "ūhi/ūbhi"),
+    ("uyā", "ūnaṃ"),
+    ("uyā", "ūhi/ūbhi"),
+    ("uyā", "ūnaṃ"),
+    ("uyā/uyaṃ", "ūsu"),
+    ("u", "ū/uyo"),
+];
+
+const MASC_ANT: Endings = [
+    ("ā/anto", "anto/antā"),
+    ("antaṃ", "ante"),
+    ("atā/antena", "antehi"),
+    ("ato/antassa", "ataṃ/antānaṃ"),
+    ("atā/antasmā", "antehi"),
+    ("ato/antassa", "ataṃ/antānaṃ"),
+    ("ati/ante/antasmiṃ", "antesu"),
+    ("a/ā/anta", "anto/antā"),
+];
+
+const NEUT_ANT: Endings = [
+    ("aṃ/antaṃ", "antāni"),
+    ("aṃ/antaṃ", "antāni"),
+    ("atā/antena", "antehi"),
+    ("ato/antassa", "ataṃ/antānaṃ"),
+    ("atā/antasmā", "antehi"),
+    ("ato/antassa", "ataṃ/antānaṃ"),
+    ("ati/ante/antasmiṃ", "antesu"),
+    ("aṃ/a", "antāni"),
+];
+
+const MASC_IN: Endings = [
+    ("ī", "ī/ino"),
+    ("iṃ/inaṃ", "ī/ino"),
+    ("inā", "īhi/ībhi"),
+    ("ino/issa", "inaṃ/īnaṃ"),
+    ("inā/ismā", "īhi/ībhi"),
+    ("ino/issa", "inaṃ/īnaṃ"),
+    ("ini/ismiṃ/imhi", "īsu"),
+    ("i", "ī/ino"),
+];
+
+const NEUT_IN: Endings = [
+    ("i", "īni/ī"),
+    ("iṃ", "īni/ī"),
+    ("inā", "īhi/ībhi"),
+    ("ino/issa", "inaṃ/īnaṃ"),
+    ("inā/ismā", "īhi/ībhi"),
+    ("ino/issa", "inaṃ/īnaṃ"),
+    ("ini/ismiṃ/imhi", "īsu"),
+    ("i", "īni/ī"),
+];
+
+/// The paradigm tables of a word, from its grammar. Only the regular paradigms are generated:
+/// nouns and adjectives of the a, ā, i, ī, u, ū, ant and in stems, and the present, aorist,
+/// future and optative of verbs given in the 3rd person singular present (`gacchati`). Words with
+/// an unknown part of speech, gender or stem have no tables.
+pub fn paradigm_tables(word: &str, word_nom_sg: &str, speech: &str, gender: &str) -> Vec<ParadigmTable> {
+    let word = word.trim().to_lowercase();
+    let word_nom_sg = word_nom_sg.trim().to_lowercase();
+    if word.is_empty() || word.contains(' ') {
+        return vec![];
+    }
+
+    let mut genders = parse_genders(gender);
+    if genders.is_empty() {
+        genders = nom_sg_gender(&word, &word_nom_sg);
+    }
+
+    let word_class = match parse_word_class(speech) {
+        Some(x) => x,
+        None if !genders.is_empty() => WordClass::Noun,
+        None => return vec![],
+    };
+
+    match word_class {
+        WordClass::Verb => verb_tables(&word),
+        WordClass::Noun => {
+            let (base, stem) = match noun_stem(&word, &word_nom_sg, &genders) {
+                Some(x) => x,
+                None => return vec![],
+            };
+            let genders = if genders.is_empty() {
+                match stem {
+                    Stem::LongA | Stem::LongI | Stem::LongU => vec![Gender::Feminine],
+                    _ => return vec![],
+                }
+            } else {
+                genders
+            };
+            genders.iter()
+                .filter_map(|g| declension_table(&base, stem, *g))
+                .collect()
+        }
+        WordClass::Adjective => adjective_tables(&word),
+    }
+}
+
+/// All forms of the tables, without duplicates, in the order of the tables.
+pub fn inflected_forms(tables: &[ParadigmTable]) -> Vec<String> {
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut forms: Vec<String> = Vec::new();
+    for t in tables.iter() {
+        for r in t.rows.iter() {
+            for c in r.cells.iter() {
+                for f in c.iter() {
+                    if seen.insert(f.clone()) {
+                        forms.push(f.clone());
+                    }
+                }
+            }
+        }
+    }
+    forms
+}
+
+pub fn format_paradigm_tables_html(tables: &[ParadigmTable]) -> String {
+    let mut html = String::new();
+    for t in tables.iter() {
+        html.push_str(&format!("<table class=\"paradigm\"><caption>{}</caption>", t.title));
+        html.push_str("<tr><th></th>");
+        for c in t.columns.iter() {
+            html.push_str(&format!("<th>{}</th>", c));
+        }
+        html.push_str("</tr>");
+        for r in t.rows.iter() {
+            html.push_str(&format!("<tr><th>{}</th>", r.label));
+            for c in r.cells.iter() {
+                html.push_str(&format!("<td>{}</td>", c.join(", ")));
+            }
+            html.push_str("</tr>");
+        }
+        html.push_str("</table>");
+    }
+    html
+}
+
+/// noun, n., adj, verb, pr, etc.
+pub fn parse_word_class(speech: &str) -> Option<WordClass> {
+    let s = speech.trim().trim_end_matches('.').to_lowercase();
+    match s.as_str() {
+        "noun" | "n" | "nom" | "substantive" => Some(WordClass::Noun),
+        "adj" | "adjective" | "a" => Some(WordClass::Adjective),
+        "verb" | "v" | "vb" | "pr" | "pres" => Some(WordClass::Verb),
+        _ if s.contains("adv") => None,
+        _ if s.contains("noun") => Some(WordClass::Noun),
+        _ if s.contains("adj") => Some(WordClass::Adjective),
+        _ if s.contains("verb") => Some(WordClass::Verb),
+        _ => None,
+    }
+}
+
+/// m, f, nt, masc., "m, n", masculine, etc.
+pub fn parse_genders(gender: &str) -> Vec<Gender> {
+    let mut res: Vec<Gender> = Vec::new();
+    for g in gender.split(&[',', '/', ' ', '&'][..]) {
+        let g = g.trim().trim_end_matches('.').to_lowercase();
+        let x = match g.as_str() {
+            "m" | "masc" | "masculine" => Gender::Masculine,
+            "f" | "fem" | "feminine" => Gender::Feminine,
+            "n" | "nt" | "neut" | "neuter" => Gender::Neuter,
+            _ => continue,
+        };
+        if !res.contains(&x) {
+            res.push(x);
+        }
+    }
+    res
+}
+
+/// The gender of an a-stem from its nom. sg., when the grammar doesn't have it: `dhammo` is
+/// masculine, `rūpaṃ` is neuter.
+fn nom_sg_gender(word: &str, word_nom_sg: &str) -> Vec<Gender> {
+    let base = match word.strip_suffix('a') {
+        Some(x) => x,
+        None => return vec![],
+    };
+    match word_nom_sg.strip_prefix(base) {
+        Some("o") => vec![Gender::Masculine],
+        Some("aṃ") => vec![Gender::Neuter],
+        _ => vec![],
+    }
+}
+
+/// The stem without its final vowel and the stem class: `dhamma` -> (`dhamm`, A). A headword given
+/// in the nominative (`dhammo`, `rūpaṃ`) is recognized too.
+fn noun_stem(word: &str, word_nom_sg: &str, genders: &[Gender]) -> Option<(String, Stem)> {
+    let masc = genders.contains(&Gender::Masculine);
+
+    let endings: [(&str, Stem); 10] = [
+        ("antu", Stem::Ant),
+        ("ant", Stem::Ant),
+        ("in", Stem::In),
+        ("aṃ", Stem::A),
+        ("a", Stem::A),
+        ("ā", Stem::LongA),
+        ("i", Stem::I),
+        ("ī", Stem::LongI),
+        ("u", Stem::U),
+        ("ū", Stem::LongU),
+    ];
+
+    for (ending, stem) in endings.iter() {
+        if let Some(base) = word.strip_suffix(ending) {
+            if base.is_empty() {
+                return None;
+            }
+            // hatthī m. is an in-stem.
+            if *stem == Stem::LongI && masc {
+                return Some((base.to_string(), Stem::In));
+            }
+            // bhagavā, satimā: the headword is the nom. sg. of an ant-stem
+            if *stem == Stem::LongA && masc && (word_nom_sg.is_empty() || word_nom_sg == word)
+                && (base.ends_with('v') || base.ends_with('m'))
+            {
+                return Some((base.to_string(), Stem::Ant));
+            }
+            return Some((base.to_string(), *stem));
+        }
+    }
+
+    if let Some(base) = word.strip_suffix('o') {
+        if masc && !base.is_empty() {
+            return Some((base.to_string(), Stem::A));
+        }
+    }
+
+    None
+}
+
+fn declension_table(base: &str, stem: Stem, gender: Gender) -> Option<ParadigmTable> {
+    let (endings, name): (&Endings, &str) = match (stem, gender) {
+        (Stem::A, Gender::Masculine) => (&MASC_A, "Masculine a-stem"),
+        (Stem::A, Gender::Neuter) => (&NEUT_A, "Neuter a-stem"),
+        (Stem::A, Gender::Feminine) | (Stem::LongA, Gender::Feminine) => (&FEM_LONG_A, "Feminine ā-stem"),
+        (Stem::I, Gender::Masculine) => (&MASC_I, "Masculine i-stem"),
+        (Stem::I, Gender::Neuter) => (&NEUT_I, "Neuter i-stem"),
+        (Stem::I, Gender::Feminine) => (&FEM_I, "Feminine i-stem"),
+        (Stem::LongI, Gender::Feminine) => (&FEM_LONG_I, "Feminine ī-stem"),
+        (Stem::U, Gender::Masculine) => (&MASC_U, "Masculine u-stem"),
+        (Stem::U, Gender::Neuter) => (&NEUT_U, "Neuter u-stem"),
+        (Stem::U, Gender::Feminine) => (&FEM_U, "Feminine u-stem"),
+        (Stem::LongU, Gender::Masculine) => (&MASC_LONG_U, "Masculine ū-stem"),
+        (Stem::LongU, Gender::Feminine) => (&FEM_LONG_U, "Feminine ū-stem"),
+        (Stem::Ant, Gender::Masculine) => (&MASC_ANT, "Masculine ant-stem"),
+        (Stem::Ant, Gender::Neuter) => (&NEUT_ANT, "Neuter ant-stem"),
+        (Stem::Ant, Gender::Feminine) => (&FEM_LONG_I, "Feminine antī-stem"),
+        (Stem::In, Gender::Masculine) => (&MASC_IN, "Masculine in-stem"),
+        (Stem::In, Gender::Neuter) => (&NEUT_IN, "Neuter in-stem"),
+        (Stem::In, Gender::Feminine) => (&FEM_LONG_I, "Feminine inī-stem"),
+        _ => return None,
+    };
+
+    // The feminine of ant and in stems is an ī-stem: guṇavantī, hatthinī.
+    let base = match (stem, gender) {
+        (Stem::Ant, Gender::Feminine) => format!("{}ant", base),
+        (Stem::In, Gender::Feminine) => format!("{}in", base),
+        _ => base.to_string(),
+    };
+
+    let rows = CASES.iter()
+        .zip(endings.iter())
+        .map(|(case, (sg, pl))| ParadigmRow {
+            label: case.to_string(),
+            cells: vec![add_endings(&base, sg), add_endings(&base, pl)],
+        })
+        .collect();
+
+    Some(ParadigmTable {
+        title: name.to_string(),
+        columns: vec!["Singular".to_string(), "Plural".to_string()],
+        rows,
+    })
+}
+
+/// Adjectives are declined in the three genders: a-stems with an ā-stem feminine, ant and in
+/// stems with an ī-stem feminine.
+fn adjective_tables(word: &str) -> Vec<ParadigmTable> {
+    let all = [Gender::Masculine, Gender::Neuter, Gender::Feminine];
+    let (base, stem) = match noun_stem(word, word, &[Gender::Masculine]) {
+        Some(x) => x,
+        None => return vec![],
+    };
+    match stem {
+        Stem::A | Stem::I | Stem::U | Stem::Ant | Stem::In => all.iter()
+            .filter_map(|g| declension_table(&base, stem, *g))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Conjugation tables of a verb given in the 3rd person singular present: `gacchati`, `deseti`,
+/// `karoti`. The tenses are formed from the present stem, so the aorist is only the form without
+/// the augment (`gacchi`, not `agacchi`), and the root aorists (`agamāsi`) are not generated.
+fn verb_tables(word: &str) -> Vec<ParadigmTable> {
+    let stem = match word.strip_suffix("ti") {
+        Some(x) if x.chars().count() > 1 => x,
+        _ => return vec![],
+    };
+
+    let (base, vowel) = match stem.chars().last() {
+        Some(c) if c == 'a' || c == 'e' || c == 'o' => (&stem[..stem.len() - c.len_utf8()], c),
+        _ => return vec![],
+    };
+
+    conjugation_endings(vowel).iter()
+        .map(|(title, endings)| conjugation_table(title, base, endings))
+        .collect()
+}
+
+/// The endings of each tense for a present stem in `a`, `e` or `o`, added to the stem without its
+/// final vowel (`gacch-`, `des-`, `kar-`).
+fn conjugation_endings(vowel: char) -> Vec<(&'static str, [(String, String); 3])> {
+    let first_vowel = if vowel == 'a' { "ā".to_string() } else { vowel.to_string() };
+    let present: [(String, String); 3] = [
+        (format!("{}ti", vowel), format!("{}nti", vowel)),
+        (format!("{}si", vowel), format!("{}tha", vowel)),
+        (format!("{}mi", first_vowel), format!("{}ma", first_vowel)),
+    ];
+
+    let fut = if vowel == 'e' { "ess" } else { "iss" };
+    let future: [(String, String); 3] = [
+        (format!("{}ati", fut), format!("{}anti", fut)),
+        (format!("{}asi", fut), format!("{}atha", fut)),
+        (format!("{}āmi", fut), format!("{}āma", fut)),
+    ];
+
+    // Without the augment, see verb_tables().
+    let aorist: [(String, String); 3] = if vowel == 'e' {
+        [
+            ("esi".to_string(), "esuṃ".to_string()),
+            ("esi".to_string(), "ettha".to_string()),
+            ("esiṃ".to_string(), "esimha".to_string()),
+        ]
+    } else {
+        [
+            ("i".to_string(), "iṃsu/uṃ".to_string()),
+            ("i/o".to_string(), "ittha".to_string()),
+            ("iṃ".to_string(), "imha/imhā".to_string()),
+        ]
+    };
+
+    let optative: [(String, String); 3] = [
+        ("eyya/e".to_string(), "eyyuṃ".to_string()),
+        ("eyyāsi/e".to_string(), "eyyātha".to_string()),
+        ("eyyāmi/eyyaṃ".to_string(), "eyyāma".to_string()),
+    ];
+
+    vec![
+        ("Present", present),
+        ("Aorist", aorist),
+        ("Future", future),
+        ("Optative", optative),
+    ]
+}
+
+/// An ending of the regular paradigms, for deinflecting a word form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndingRule {
+    /// The ending of the inflected form, `ānaṃ`
+    pub ending: String,
+    /// The endings of the headword which replace it, `a`
+    pub citations: Vec<String>,
+    /// Masculine a-stem, Genitive plural
+    pub label: String,
+}
+
+/// The endings of all the paradigms, the longest first.
+pub fn ending_rules() -> Vec<EndingRule> {
+    let mut rules: Vec<EndingRule> = Vec::new();
+
+    let stems = [Stem::A, Stem::LongA, Stem::I, Stem::LongI, Stem::U, Stem::LongU, Stem::Ant, Stem::In];
+    let genders = [Gender::Masculine, Gender::Neuter, Gender::Feminine];
+
+    for stem in stems.iter() {
+        let citations: Vec<String> = match stem {
+            Stem::A => vec!["a"],
+            Stem::LongA => vec!["ā"],
+            Stem::I => vec!["i"],
+            Stem::LongI => vec!["ī"],
+            Stem::U => vec!["u"],
+            Stem::LongU => vec!["ū"],
+            Stem::Ant => vec!["ant", "antu", "ā"],
+            Stem::In => vec!["in", "ī"],
+        }.into_iter().map(|x| x.to_string()).collect();
+
+        for gender in genders.iter() {
+            // (Stem::A, Feminine) is the same as (Stem::LongA, Feminine)
+            if *stem == Stem::A && *gender == Gender::Feminine {
+                continue;
+            }
+            if let Some(t) = declension_table("", *stem, *gender) {
+                add_table_rules(&mut rules, &t, &citations);
+            }
+        }
+    }
+
+    for vowel in ['a', 'e', 'o'].iter() {
+        let citations = vec![format!("{}ti", vowel)];
+        for (title, endings) in conjugation_endings(*vowel).iter() {
+            add_table_rules(&mut rules, &conjugation_table(title, "", endings), &citations);
+        }
+    }
+
+    rules.sort_by_key(|r| Reverse(r.ending.chars().count()));
+    rules
+}
+
+fn add_table_rules(rules: &mut Vec<EndingRule>, table: &ParadigmTable, citations: &[String]) {
+    for r in table.rows.iter() {
+        for (column, cell) in table.columns.iter().zip(r.cells.iter()) {
+            for ending in cell.iter() {
+                rules.push(EndingRule {
+                    ending: ending.clone(),
+                    citations: citations.to_vec(),
+                    label: format!("{}, {} {}", table.title, r.label, column.to_lowercase()),
+                });
+            }
+        }
+    }
+}
+
+fn conjugation_table(title: &str, base: &str, endings: &[(String, String); 3]) -> ParadigmTable {
+    let rows = PERSONS.iter()
+        .zip(endings.iter())
+        .map(|(person, (sg, pl))| ParadigmRow {
+            label: person.to_string(),
+            cells: vec![add_endings(base, sg), add_endings(base, pl)],
+        })
+        .collect();
+
+    ParadigmTable {
+        title: title.to_string(),
+        columns: vec!["Singular".to_string(), "Plural".to_string()],
+        rows,
+    }
+}
+
+fn add_endings(base: &str, endings: &str) -> Vec<String> {
+    endings.split('/').map(|e| format!("{}{}", base, e)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(word: &str, speech: &str, gender: &str) -> Vec<String> {
+        inflected_forms(&paradigm_tables(word, "", speech, gender))
+    }
+
+    fn titles(word: &str, speech: &str, gender: &str) -> Vec<String> {
+        paradigm_tables(word, "", speech, gender).into_iter().map(|t| t.title).collect()
+    }
+
+    fn row(table: &ParadigmTable, label: &str) -> Vec<Vec<String>> {
+        table.rows.iter().find(|r| r.label == label).unwrap().cells.clone()
+    }
+
+    fn contains_all(forms: &[String], expected: &[&str]) {
+        for x in expected.iter() {
+            assert!(forms.contains(&x.to_string()), "{} is missing from {:?}", x, forms);
+        }
+    }
+
+    #[test]
+    fn dhamma_masculine() {
+        let tables = paradigm_tables("dhamma", "", "noun", "m");
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].title, "Masculine a-stem");
+        assert_eq!(row(&tables[0], "Nominative"), vec![vec!["dhammo"], vec!["dhammā", "dhammāse"]]);
+        assert_eq!(row(&tables[0], "Genitive"), vec![vec!["dhammassa"], vec!["dhammānaṃ"]]);
+
+        contains_all(&forms("dhamma", "noun", "m"), &["dhammaṃ", "dhammena", "dhammasmiṃ", "dhammesu", "dhammehi"]);
+    }
+
+    #[test]
+    fn rupa_neuter() {
+        assert_eq!(titles("rūpa", "n.", "nt."), vec!["Neuter a-stem"]);
+        contains_all(&forms("rūpa", "n.", "nt."), &["rūpaṃ", "rūpāni", "rūpena", "rūpassa", "rūpesu"]);
+
+        // The gender from the nom. sg. when the grammar doesn't have it.
+        let tables = paradigm_tables("rūpa", "rūpaṃ", "", "");
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].title, "Neuter a-stem");
+    }
+
+    #[test]
+    fn bhikkhu_masculine() {
+        assert_eq!(titles("bhikkhu", "noun", "m"), vec!["Masculine u-stem"]);
+        contains_all(&forms("bhikkhu", "noun", "m"), &["bhikkhūnaṃ", "bhikkhuno", "bhikkhussa", "bhikkhunā", "bhikkhū", "bhikkhavo", "bhikkhūsu"]);
+    }
+
+    #[test]
+    fn hatthi_in_stem() {
+        assert_eq!(titles("hatthī", "noun", "m"), vec!["Masculine in-stem"]);
+        let tables = paradigm_tables("hatthī", "", "noun", "m");
+        assert_eq!(row(&tables[0], "Nominative"), vec![vec!["hatthī"], vec!["hatthī", "hatthino"]]);
+        contains_all(&forms("hatthī", "noun", "m"), &["hatthinā", "hatthino", "hatthissa", "hatthīnaṃ", "hatthismiṃ"]);
+    }
+
+    #[test]
+    fn bhagava_ant_stem() {
+        assert_eq!(titles("bhagavā", "noun", "m"), vec!["Masculine ant-stem"]);
+        contains_all(&forms("bhagavā", "noun", "m"), &["bhagavā", "bhagavanto", "bhagavatā", "bhagavato", "bhagavantaṃ"]);
+    }
+
+    #[test]
+    fn gacchati_conjugation() {
+        let tables = paradigm_tables("gacchati", "", "pr", "");
+        let names: Vec<&str> = tables.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(names, vec!["Present", "Aorist", "Future", "Optative"]);
+        assert_eq!(row(&tables[0], "1st"), vec![vec!["gacchāmi"], vec!["gacchāma"]]);
+        contains_all(&forms("gacchati", "pr", ""), &["gacchanti", "gacchasi", "gacchi", "gacchiṃsu", "gacchissati", "gaccheyya"]);
+        // The aorist without the augment only.
+        assert!(!forms("gacchati", "pr", "").iter().any(|x| x == "agacchi"));
+    }
+
+    #[test]
+    fn deseti_conjugation() {
+        let tables = paradigm_tables("deseti", "", "verb", "");
+        assert_eq!(row(&tables[0], "3rd"), vec![vec!["deseti"], vec!["desenti"]]);
+        contains_all(&forms("deseti", "verb", ""), &["desemi", "desesi", "desesuṃ", "desessati", "deseyya"]);
+    }
+
+    #[test]
+    fn unknown_grammar_has_no_tables() {
+        assert!(paradigm_tables("ca", "", "ind", "").is_empty());
+        assert!(paradigm_tables("dhamma", "", "", "").is_empty());
+    }
+}
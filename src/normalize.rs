@@ -0,0 +1,136 @@
+use unicode_normalization::UnicodeNormalization;
+
+use pali_dict_core::dict_word::DictWordMarkdown;
+
+/// Apostrophes which are replaced with `'` in the words: `’ti`, `ʼti`
+const APOSTROPHES: [char; 4] = ['’', '‘', 'ʼ', '′'];
+
+/// Hyphen variants which are replaced with `-` in the words. The en and em dashes are left as they
+/// are.
+const HYPHENS: [char; 6] = ['\u{2010}', '\u{2011}', '\u{2012}', '\u{2212}', '\u{FE63}', '\u{FF0D}'];
+
+/// The niggahīta letter of `DictMetadata.niggahita`: `ṃ` or `dot_below`, `ṁ` or `dot_above`, `ŋ`
+/// or `eng`. An empty or unknown value keeps the niggahīta of the source.
+pub fn parse_niggahita(name: &str) -> Option<char> {
+    match name.trim() {
+        "ṃ" | "dot_below" => Some('ṃ'),
+        "ṁ" | "dot_above" => Some('ṁ'),
+        "ŋ" | "eng" => Some('ŋ'),
+        _ => None,
+    }
+}
+
+/// NFC normalization, so that `m` with a combining dot is the same as `ṃ`, and the preferred
+/// niggahīta.
+pub fn normalize_text(text: &str, niggahita: Option<char>) -> String {
+    let s: String = text.nfc().collect();
+    match niggahita {
+        Some(n) => {
+            let upper = n.to_uppercase().next().unwrap_or(n);
+            s.chars()
+                .map(|c| match c {
+                    'ṃ' | 'ṁ' | 'ŋ' => n,
+                    'Ṃ' | 'Ṁ' | 'Ŋ' => upper,
+                    _ => c,
+                })
+                .collect()
+        }
+        None => s,
+    }
+}
+
+/// `normalize_text()`, and the apostrophes and hyphens of a word. Soft hyphens are removed.
+pub fn normalize_word(word: &str, niggahita: Option<char>) -> String {
+    normalize_text(word, niggahita)
+        .chars()
+        .filter(|c| *c != '\u{00AD}')
+        .map(|c| {
+            if APOSTROPHES.contains(&c) {
+                '\''
+            } else if HYPHENS.contains(&c) {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Normalize the text of an entry with `normalize_text()`, and its words (the headword, the
+/// inflections and the word lists) with `normalize_word()`.
+pub fn normalize_dict_word(dict_word: &mut DictWordMarkdown, niggahita: Option<char>) {
+    let res = serde_json::to_value(&*dict_word).and_then(|mut value| {
+        normalize_json_strings(&mut value, niggahita);
+        serde_json::from_value(value)
+    });
+    match res {
+        Ok(x) => *dict_word = x,
+        Err(e) => error!("🔥 Can't normalize the text of '{}', only its words: {:?}", dict_word.word_header.word, e),
+    }
+
+    let h = &mut dict_word.word_header;
+
+    h.word = normalize_word(&h.word, niggahita);
+    h.word_nom_sg = normalize_word(&h.word_nom_sg, niggahita);
+
+    for words in [
+        &mut h.inflections,
+        &mut h.synonyms,
+        &mut h.antonyms,
+        &mut h.homonyms,
+        &mut h.also_written_as,
+        &mut h.see_also,
+        &mut h.grammar_roots,
+    ].iter_mut() {
+        for w in words.iter_mut() {
+            *w = normalize_word(w, niggahita);
+        }
+    }
+}
+
+fn normalize_json_strings(value: &mut serde_json::Value, niggahita: Option<char>) {
+    match value {
+        serde_json::Value::String(s) => *s = normalize_text(s, niggahita),
+        serde_json::Value::Array(xs) => {
+            for x in xs.iter_mut() {
+                normalize_json_strings(x, niggahita);
+            }
+        }
+        serde_json::Value::Object(m) => {
+            for x in m.values_mut() {
+                normalize_json_strings(x, niggahita);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combining_dot_is_niggahita() {
+        // m + combining dot below, m + combining dot above
+        assert_eq!(normalize_text("sam\u{0323}gha", None), "saṃgha");
+        assert_eq!(normalize_text("sam\u{0307}gha", None), "saṁgha");
+        assert_eq!(normalize_text("sam\u{0307}gha", Some('ṃ')), "saṃgha");
+    }
+
+    #[test]
+    fn preferred_niggahita() {
+        assert_eq!(normalize_text("saṃgha saŋgha Ṃ", Some('ṁ')), "saṁgha saṁgha Ṁ");
+        assert_eq!(normalize_text("saṁgha", None), "saṁgha");
+        assert_eq!(parse_niggahita("dot_above"), Some('ṁ'));
+        assert_eq!(parse_niggahita("x"), None);
+    }
+
+    #[test]
+    fn apostrophes_hyphens_and_soft_hyphens() {
+        assert_eq!(normalize_word(" kin’ti ", None), "kin'ti");
+        assert_eq!(normalize_word("kusala\u{2011}kamma", None), "kusala-kamma");
+        assert_eq!(normalize_word("dham\u{00AD}ma", None), "dhamma");
+    }
+}
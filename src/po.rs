@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::error::ToolError;
+
+/// A message from a gettext PO file, such as sc-data/po_text/pli-en/mn/mn001.po
+///
+/// The fields follow polib, which was used to convert the PO files to JSON before.
+///
+/// ```text
+/// #. </p><p>
+/// #. <a class="sc" id="sc2"></a>
+/// #. VAR: Na hi nūna → nahanūna (bj, s1, s2, km, pts1) | naha nūna (s3)
+/// msgctxt "an4.67:2.1"
+/// msgid ""
+/// "“Na hi nūna so, bhikkhave, bhikkhu cattāri ahirājakulāni mettena cittena "
+/// "phari."
+/// msgstr ""
+/// "“Mendicants, that monk mustn’t have spread a mind of love to the four royal "
+/// "snake families."
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PoMessage {
+    pub msgctxt: String,
+    pub msgid: String,
+    pub msgid_plural: String,
+    pub msgstr: String,
+    /// msgstr[0], msgstr[1], ...
+    pub msgstr_plural: BTreeMap<usize, String>,
+    /// Extracted comments (`#.`), one line per comment line.
+    pub comment: String,
+    /// Translator comments (`# `), one line per comment line.
+    pub tcomment: String,
+    /// References (`#:`)
+    pub occurrences: Vec<String>,
+    /// Flags (`#,`), such as fuzzy.
+    pub flags: Vec<String>,
+    /// Line number of the msgid.
+    pub linenum: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    None,
+    Comment,
+    Ctxt,
+    Id,
+    IdPlural,
+    Str,
+    StrPlural(usize),
+}
+
+pub fn parse_po_file(path: &Path) -> Result<Vec<PoMessage>, Box<dyn Error>> {
+    let s = fs::read_to_string(path)?;
+    match parse_po_str(&s) {
+        Ok(x) => Ok(x),
+        Err(e) => {
+            let msg = format!("🔥 Can't parse PO file: {:?}\n{}", path, e);
+            Err(Box::new(ToolError::Exit(msg)))
+        }
+    }
+}
+
+/// Parse the messages of a PO file. Obsolete messages (`#~`) and previous values (`#|`) are
+/// skipped. The header (the first message, with an empty msgid and no msgctxt) is skipped too, as
+/// in the JSON of polib.
+pub fn parse_po_str(s: &str) -> Result<Vec<PoMessage>, Box<dyn Error>> {
+    let mut messages: Vec<PoMessage> = Vec::new();
+    let mut msg = PoMessage::default();
+    let mut has_msgid = false;
+    let mut last = Field::None;
+
+    fn push_line(s: &mut String, line: &str) {
+        if !s.is_empty() {
+            s.push('\n');
+        }
+        s.push_str(line);
+    }
+
+    for (n, line) in s.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() {
+            if has_msgid {
+                messages.push(msg);
+            }
+            msg = PoMessage::default();
+            has_msgid = false;
+            last = Field::None;
+            continue;
+        }
+
+        if line.starts_with("#~") || line.starts_with("#|") {
+            continue;
+        }
+
+        // A comment after a msgstr starts the next message.
+        let is_str = match last {
+            Field::Str | Field::StrPlural(_) => true,
+            _ => false,
+        };
+
+        if line.starts_with('#') {
+            if is_str {
+                messages.push(msg);
+                msg = PoMessage::default();
+                has_msgid = false;
+            }
+
+            // Strip the marker and one space after it.
+            let text = |prefix: &str| -> String {
+                let a = &line[prefix.len()..];
+                a.strip_prefix(' ').unwrap_or(a).to_string()
+            };
+
+            if line.starts_with("#.") {
+                push_line(&mut msg.comment, &text("#."));
+            } else if line.starts_with("#:") {
+                msg.occurrences.extend(text("#:").split_whitespace().map(|x| x.to_string()));
+            } else if line.starts_with("#,") {
+                msg.flags.extend(text("#,").split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()));
+            } else {
+                push_line(&mut msg.tcomment, &text("#"));
+            }
+
+            last = Field::Comment;
+            continue;
+        }
+
+        // Continuation of the previous string.
+        if line.starts_with('"') {
+            let value = unquote(line, n)?;
+            match last {
+                Field::Ctxt => msg.msgctxt.push_str(&value),
+                Field::Id => msg.msgid.push_str(&value),
+                Field::IdPlural => msg.msgid_plural.push_str(&value),
+                Field::Str => msg.msgstr.push_str(&value),
+                Field::StrPlural(i) => msg.msgstr_plural.entry(i).or_insert_with(String::new).push_str(&value),
+                Field::None | Field::Comment => {
+                    let e = format!("Unexpected string on line {}: {}", n + 1, line);
+                    return Err(Box::new(ToolError::Exit(e)));
+                }
+            }
+            continue;
+        }
+
+        let (keyword, rest) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+        let value = unquote(rest, n)?;
+
+        // msgctxt or msgid after a msgstr starts the next message.
+        if (keyword == "msgctxt" || keyword == "msgid") && is_str {
+            messages.push(msg);
+            msg = PoMessage::default();
+            has_msgid = false;
+        }
+
+        match keyword {
+            "msgctxt" => {
+                msg.msgctxt = value;
+                last = Field::Ctxt;
+            }
+            "msgid" => {
+                msg.msgid = value;
+                msg.linenum = n + 1;
+                has_msgid = true;
+                last = Field::Id;
+            }
+            "msgid_plural" => {
+                msg.msgid_plural = value;
+                last = Field::IdPlural;
+            }
+            "msgstr" => {
+                msg.msgstr = value;
+                last = Field::Str;
+            }
+            _ if keyword.starts_with("msgstr[") && keyword.ends_with(']') => {
+                let i = match keyword["msgstr[".len()..keyword.len() - 1].parse::<usize>() {
+                    Ok(i) => i,
+                    Err(_) => {
+                        let e = format!("Wrong plural index on line {}: {}", n + 1, line);
+                        return Err(Box::new(ToolError::Exit(e)));
+                    }
+                };
+                msg.msgstr_plural.insert(i, value);
+                last = Field::StrPlural(i);
+            }
+            _ => {
+                let e = format!("Unknown keyword on line {}: {}", n + 1, line);
+                return Err(Box::new(ToolError::Exit(e)));
+            }
+        }
+    }
+
+    if has_msgid {
+        messages.push(msg);
+    }
+
+    if let Some(x) = messages.first() {
+        if x.msgid.is_empty() && x.msgctxt.is_empty() {
+            messages.remove(0);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// "Say \"hi\"\n" -> Say "hi"<newline>
+fn unquote(s: &str, n: usize) -> Result<String, Box<dyn Error>> {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        let e = format!("Expected a quoted string on line {}: {}", n + 1, s);
+        return Err(Box::new(ToolError::Exit(e)));
+    }
+
+    let mut res = String::new();
+    let mut chars = s[1..s.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some('"') => res.push('"'),
+            Some('\\') => res.push('\\'),
+            Some(x) => {
+                res.push('\\');
+                res.push(x);
+            }
+            None => res.push('\\'),
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PO: &str = r#"# Translators: Sujato
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+"Language: en\n"
+
+#. </p><p>
+#. <a class="sc" id="sc2"></a>
+#. VAR: Na hi nūna → nahanūna (bj, s1, s2, km, pts1) | naha nūna (s3)
+msgctxt "an4.67:2.1"
+msgid ""
+"“Na hi nūna so, bhikkhave, bhikkhu cattāri ahirājakulāni mettena cittena "
+"phari."
+msgstr ""
+"“Mendicants, that monk mustn’t have spread a mind of love to the four royal "
+"snake families."
+
+# A translator comment
+#: an4.67.po:12
+#, fuzzy
+msgctxt "an4.67:2.2"
+msgid "Katamāni \"cattāri\"?"
+msgstr "Which four?\tTab\\"
+msgctxt "an4.67:2.3"
+msgid "Virūpakkhaṃ"
+msgstr "Virūpakkha"
+"#;
+
+    #[test]
+    fn header_is_skipped() {
+        let res = parse_po_str(PO).unwrap();
+        assert_eq!(res.len(), 3);
+        assert!(res.iter().all(|x| !x.msgid.is_empty()));
+    }
+
+    #[test]
+    fn multi_line_strings_and_comments() {
+        let res = parse_po_str(PO).unwrap();
+        let m = &res[0];
+        assert_eq!(m.msgctxt, "an4.67:2.1");
+        assert_eq!(m.msgid, "“Na hi nūna so, bhikkhave, bhikkhu cattāri ahirājakulāni mettena cittena phari.");
+        assert_eq!(m.msgstr, "“Mendicants, that monk mustn’t have spread a mind of love to the four royal snake families.");
+        assert_eq!(m.comment, "</p><p>\n<a class=\"sc\" id=\"sc2\"></a>\nVAR: Na hi nūna → nahanūna (bj, s1, s2, km, pts1) | naha nūna (s3)");
+        assert_eq!(m.linenum, 11);
+    }
+
+    #[test]
+    fn translator_comments_flags_and_escapes() {
+        let res = parse_po_str(PO).unwrap();
+        let m = &res[1];
+        assert_eq!(m.msgctxt, "an4.67:2.2");
+        assert_eq!(m.tcomment, "A translator comment");
+        assert_eq!(m.occurrences, vec!["an4.67.po:12"]);
+        assert_eq!(m.flags, vec!["fuzzy"]);
+        assert_eq!(m.msgid, "Katamāni \"cattāri\"?");
+        assert_eq!(m.msgstr, "Which four?\tTab\\");
+    }
+
+    #[test]
+    fn msgctxt_after_msgstr_starts_a_message() {
+        let res = parse_po_str(PO).unwrap();
+        assert_eq!(res[2].msgctxt, "an4.67:2.3");
+        assert_eq!(res[2].msgid, "Virūpakkhaṃ");
+        assert_eq!(res[2].msgstr, "Virūpakkha");
+    }
+
+    #[test]
+    fn plural_forms() {
+        let s = "msgid \"one sutta\"\nmsgid_plural \"%d suttas\"\nmsgstr[0] \"ekaṃ\"\nmsgstr[1] \"%d\"\n";
+        let res = parse_po_str(s).unwrap();
+        assert_eq!(res[0].msgid_plural, "%d suttas");
+        assert_eq!(res[0].msgstr_plural.get(&1), Some(&"%d".to_string()));
+    }
+
+    #[test]
+    fn unquoted_string_is_an_error() {
+        assert!(parse_po_str("msgid hello\n").is_err());
+    }
+}
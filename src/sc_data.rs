@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -6,18 +7,17 @@ use regex::Regex;
 use diesel::prelude::*;
 use diesel::sqlite::SqliteConnection;
 
+use crate::concordance::{self, parse_pts_markers, PtsMarker, PtsRef};
 use crate::db_schema;
-use crate::db_models::{DbAuthor, NewAuthor, DbTranslatedText, NewTranslatedText, DbRootText, NewRootText};
+use crate::po::{parse_po_file, PoMessage};
+use crate::db_models::{DbAuthor, NewAuthor, DbTranslatedText, NewTranslatedText, DbRootText, NewRootText, NewTextSegment, NewVariantReading, NewCollection, NewSuttaIndex, NewPtsConcordance};
 
 use walkdir::{DirEntry, WalkDir};
 
 use scraper::{Html, Selector};
 
-// NOTE Run 'make po2json' at least once to produce the JSON from the PO files.
-
 pub fn process_suttacentral_root_po_texts_to_sqlite(
     sc_data_path: &Path,
-    po_text_json_path: &Path,
     sqlite_db_path: &Path
     ) -> Result<(), Box<dyn Error>>
 {
@@ -32,22 +32,11 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
     let mut root_texts: Vec<DbRootText> = vec![];
     let mut translated_texts: Vec<DbTranslatedText> = vec![];
 
-    info!{"\n=== Begin processing JSON data. ===\n"};
+    info!{"\n=== Begin processing PO texts. ===\n"};
 
     // Deserialize data from JSON
 
-    let structure_suttas: Vec<StructureSutta>;
-    {
-        let p = sc_data_path.join(PathBuf::from("structure/sutta.json"));
-        let mut s = fs::read_to_string(p).unwrap();
-
-        // In sutta.json, numerals are sometimes int type, sometimes quoted as
-        // string. Fixing this here, every numeral will quoted as a string.
-        s = Regex::new("(\"number_in_vagga\": )([0-9]+),").unwrap().replace_all(&s, "$1\"$2\",").to_string();
-        s = Regex::new("(\"vagga_number\": )([0-9]+),").unwrap().replace_all(&s, "$1\"$2\",").to_string();
-
-        structure_suttas = serde_json::from_str(&s).unwrap();
-    }
+    let structure_suttas = read_structure_suttas(sc_data_path)?;
 
     let authors_editions: Vec<AuthorEdition>;
     {
@@ -59,25 +48,49 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
     // folder is pli-en, one half of the PO pairs is Pali, the other is the translated language
     let translated_lang = String::from("en");
 
-    let pli_en_path = po_text_json_path.join(PathBuf::from("pli-en"));
+    let pli_en_path = sc_data_path.join(PathBuf::from("po_text/pli-en"));
 
-    let po_divisions: Vec<String> = vec![
+    let mut po_divisions: Vec<String> = vec![
         String::from("an"),// an/an01 -- an/an11
         String::from("dn"),
         String::from("kn"),// kn/thag, kn/thig
         String::from("mn"),
-        //String::from("pli-tv"),// FIXME complex sub-foldes
         String::from("sn"),// sn/sn01 -- sn/sn56
     ];
 
-    info!{"\n=== Parsing the PO texts (as JSON) into division books. ===\n"};
+    // The Vinaya divisions have nested sub-folders:
+    //
+    // pli-tv-bu-vb/pli-tv-bu-vb-pc/pli-tv-bu-vb-pc48.po
+    //
+    // They are either directly in pli-en, or grouped in a pli-tv folder.
+    {
+        let tv_path = pli_en_path.join("pli-tv");
+        let (folder, prefix) = if tv_path.is_dir() {
+            (tv_path, "pli-tv/")
+        } else {
+            (pli_en_path.clone(), "")
+        };
+
+        let mut tv_divisions: Vec<String> = vec![];
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            let name = entry.file_name().to_str().unwrap().to_string();
+            if entry.path().is_dir() && name.starts_with("pli-tv") {
+                tv_divisions.push(format!("{}{}", prefix, name));
+            }
+        }
+        tv_divisions.sort();
+        po_divisions.extend(tv_divisions);
+    }
+
+    info!{"\n=== Parsing the PO texts into division books. ===\n"};
 
     for division in po_divisions.iter() {
 
         // Construct a DivisionBook to hold info for each PO file, and find the
-        // info.json which has metadata about the division.
+        // info.po which has metadata about the division.
         //
-        // Walk the folders recurively. info.json is at the root of the division
+        // Walk the folders recurively. info.po is at the root of the division
         // folder.
         //
         // Derive sub_section from the folder:
@@ -86,7 +99,7 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
         //
         // Derive vagga_name from the file name:
         //
-        // an/an01/an1.001-10.json -> an1.001-10
+        // an/an01/an1.001-10.po -> an1.001-10
 
         #[allow(dead_code)]
         struct DivisionBook {
@@ -95,12 +108,11 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
             sub_section: Option<String>,
             vagga_name: String,
             po_msgs: Vec<PoMessage>,
-            json_content: String,
             file_path: PathBuf,
             file_name: String,
         }
 
-        let mut division_info: Vec<PoAuthorInfo> = vec![];
+        let mut division_info: Vec<PoMessage> = vec![];
         let mut division_books: Vec<DivisionBook> = vec![];
 
         let folder = pli_en_path.join(PathBuf::from(division));
@@ -117,45 +129,43 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
 
             info!("{}", entry.file_name().to_str().unwrap());
 
-            let json_content = fs::read_to_string(entry.path().to_str().unwrap()).unwrap();
+            if !entry.file_name().to_str().unwrap().ends_with(".po") {
+                continue;
+            }
+
+            // If the current file is info.po, it has the author uids and blurbs.
 
-            // If the current file is info.json, deserialize it to PoAuthorInfo.
+            // Vinaya divisions may have an info.po in the sub-folders too, prefer the one at the
+            // division root.
 
-            if entry.file_name().to_str().unwrap() == "info.json" {
-                division_info = serde_json::from_str(&json_content).unwrap();
+            if entry.file_name().to_str().unwrap() == "info.po" {
+                if division_info.is_empty() || entry.depth() == 1 {
+                    division_info = parse_po_file(entry.path())?;
+                }
             } else {
 
                 // Else:
-                // - deserialize to Gettext PO messages
+                // - parse the Gettext PO messages
                 // - obtain sub_section
                 // - obtain vagga_name
 
-                // Fixing a null value in the JSON.
-                // if entry.path().ends_with("pli-tv-bu-vb/en/pli-tv-bu-vb-pc/pli-tv-bu-vb-pc46.json") {
-                //     json_content = json_content.replace("\"msgctxt\": null,",
-                //                                         "\"msgctxt\": \"pli-tv-bu-vb-pc46:pts-cs2.2.1\",");
-                // }
-
-                // NOTE The `comment` field is sometimes string-typed with a 'HTML:' prefix. We
-                // strip that in the result.
-
-                let po_msgs = match serde_json::from_str::<Vec<PoMessage>>(&json_content) {
-                    Ok(x) => {
-                        let msgs: Vec<PoMessage> = x.iter().map(|i| {
-                            let mut a = i.clone();
-                            a.comment = a.comment.trim_start_matches("HTML: ").to_string();
-                            a
-                        }).collect();
-                        msgs
-                    },
-
-                    Err(_) => {
-                        panic!{"Can't read PoMessages."};
-                    }
-                };
+                // NOTE The extracted comments sometimes have a 'HTML:' prefix. We strip that in
+                // the result.
+
+                let po_msgs: Vec<PoMessage> = parse_po_file(entry.path())?
+                    .into_iter()
+                    .map(|mut i| {
+                        i.comment = i.comment
+                            .lines()
+                            .map(|line| line.trim_start_matches("HTML: "))
+                            .collect::<Vec<&str>>()
+                            .join("\n");
+                        i
+                    })
+                    .collect();
 
                 let sub_section: Option<String>;
-                if entry.path()        // path: an/an01/an1.001-10.json
+                if entry.path()        // path: an/an01/an1.001-10.po
                     .parent().unwrap() // parent: an/an01
                     .ends_with(Path::new(division))
                 {
@@ -169,12 +179,12 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
                     // Parse out the subsection for AN, SN, and Vinaya texts.
 
                     // an
-                    let above_sec = entry.path() // an/an01/an1.001-10.json
+                    let above_sec = entry.path() // an/an01/an1.001-10.po
                         .parent().unwrap()       // an/an01
                         .parent().unwrap();      // an
 
                     // an01
-                    let sub_sec = entry.path()   // an/an01/an1.001-10.json
+                    let sub_sec = entry.path()   // an/an01/an1.001-10.po
                         .parent().unwrap()       // an/an01
                         .strip_prefix(&above_sec).unwrap(); // an01
 
@@ -192,7 +202,6 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
                     // an1.001-10
                     vagga_name: entry.path().file_stem().unwrap().to_str().unwrap().to_string(),
                     po_msgs,
-                    json_content,
                     file_path: entry.path().to_path_buf(),
                     file_name: entry.file_name().to_str().unwrap().to_string(),
                 };
@@ -308,9 +317,10 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
             // --- Obtain uid ---
 
             // an
-            let division = Path::new(&book.division).parent().unwrap().to_str().unwrap();
-            // 1.1-10 from an1.001-10.json
-            // np10     from pli-tv-bu-vb-np10.json
+            // pli-tv-bu-vb from pli-tv/pli-tv-bu-vb
+            let division = Path::new(&book.division).file_name().unwrap().to_str().unwrap();
+            // 1.1-10 from an1.001-10.po
+            // np10     from pli-tv-bu-vb-np10.po
             let with_dash = format!{"{}-", division};
             let mut div_number: String = if book.vagga_name.contains(&with_dash) {
                 book.vagga_name.replace(&with_dash, "").to_string()
@@ -349,10 +359,20 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
             // within a range (sn45.98, sn45.99, ...)
             //
             // The chapter will be read and stored as one entry, such as
-            // sn45.098-102.json
+            // sn45.098-102.po
+
+            // NOTE sutta.json uses the pali names as uid for the pli-tv... texts,
+            // so their acronyms are derived from the file name, and volpage is
+            // looked up by the acronym.
 
-            // NOTE Ignoring pli-tv... ids, because sutta.json uses the pali
-            // names as uid.
+            let vinaya_info = vinaya_acronym_and_title(&book.vagga_name);
+
+            if let Some((ref a, _)) = vinaya_info {
+                acronym = a.clone();
+                if let Some(s) = structure_suttas.iter().find(|i| i.acronym == *a) {
+                    volpage = s.volpage.clone();
+                }
+            }
 
             // mn007
             // an2.087-97
@@ -377,27 +397,30 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
 
             let title_re = Regex::new("^[0-9 \\.]+").unwrap();
 
-            let title_pali = {
-                let a = match book.po_msgs.iter().find(|i| i.comment.starts_with("</p><h1>")) {
-                    Some(x) => x,
-                    None => {
-                        panic!{"Can't find title in:\n{:#?}", &book.po_msgs};
-                    }
-                };
-                title_re.replace(&a.msgid, "").to_string()
+            // The Vinaya texts don't always have the title in a '</p><h1>', but
+            // the rule files can be named after their rule class.
+
+            let title_msg = match book.po_msgs.iter().find(|i| i.comment.starts_with("</p><h1>")) {
+                Some(x) => Some(x),
+                None => book.po_msgs.iter().find(|i| i.comment.contains("<h1")),
             };
 
-            let title_translated = {
-                let s = &book.po_msgs.iter()
-                    .find(|i| i.comment.starts_with("</p><h1>")).unwrap()
-                    .msgstr;
-                title_re.replace(&s, "").to_string()
+            let (title_pali, title_translated) = match (title_msg, &vinaya_info) {
+                (Some(a), _) => (title_re.replace(&a.msgid, "").to_string(),
+                                 title_re.replace(&a.msgstr, "").to_string()),
+                (None, Some((_, title))) => (title.clone(), title.clone()),
+                (None, None) => {
+                    panic!{"Can't find title in:\n{:#?}", &book.po_msgs};
+                }
             };
 
             // --- Build page content: root and translated, html and plain text ---
 
             let mut root_html = String::new();
             let mut translated_html = String::new();
+            let mut segments: Vec<TextSegment> = vec![];
+            let mut variants: Vec<(String, VariantReading)> = vec![];
+            let mut pts_markers: Vec<(PtsMarker, usize)> = vec![];
 
             for i in book.po_msgs.iter().filter(|i| !i.msgctxt.is_empty()) {
                 // some comments contain variation info
@@ -420,25 +443,35 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
 
                 if i.comment.contains("VAR") {
 
-                    let re = Regex::new("VAR[:L].*").unwrap();
-                    let mut variation = String::new();
+                    // The variants are stored in variant_readings, and not
+                    // added to the text.
+
+                    let re = Regex::new("VAR[:L] *(.*)").unwrap();
                     for cap in re.captures_iter(&i.comment) {
-                        variation.push_str(&cap[0]);
+                        for v in parse_variant_note(&cap[1]) {
+                            variants.push((i.msgctxt.clone(), v));
+                        }
                     }
 
-                    // only adding variation info to the root text.
-                    root_html.push_str(&format!{"</p><p class=\"variation\">{}</p><p>", variation});
-
                     let s = re.replace_all(&i.comment, "").to_string();
 
                     root_html.push_str(&s);
                     translated_html.push_str(&s);
 
+                    segments.push(TextSegment::new(&i.msgctxt, &i.msgid, &i.msgstr, &s));
+
                 } else {
 
                     root_html.push_str(&i.comment);
                     translated_html.push_str(&i.comment);
 
+                    segments.push(TextSegment::new(&i.msgctxt, &i.msgid, &i.msgstr, &i.comment));
+
+                }
+
+                // PTS page markers start at this segment.
+                for m in parse_pts_markers(&i.comment) {
+                    pts_markers.push((m, segments.len()));
                 }
 
                 // append space to separate sentences
@@ -468,6 +501,10 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
                 translated_plain = re.replace_all(&translated_plain, "\n").to_string();
             }
 
+            // --- PTS page concordance ---
+
+            let pts_pages = pts_pages_from_markers(&pts_markers, &segments, &volpage, division);
+
             // --- Construct records for database ---
 
             let new_root_text = NewRootText {
@@ -499,6 +536,9 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
                     info!("Inserting root text: {}", new_root_text.uid);
                     let text = create_new_root_text(&conn, &new_root_text);
                     root_texts.push(text);
+
+                    create_variant_readings(&conn, &root_text_uid, &variants);
+                    create_pts_concordance(&conn, &root_text_uid, &pts_pages);
                 }
             }
 
@@ -508,6 +548,8 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
                     info!("Inserting translated text: {}", new_translated_text.uid);
                     let text = create_new_translated_text(&conn, &new_translated_text);
                     translated_texts.push(text);
+
+                    create_text_segments(&conn, &root_text_uid, &translated_text_uid, &segments);
                 }
             }
         }
@@ -524,9 +566,14 @@ pub fn process_suttacentral_root_po_texts_to_sqlite(
     Ok(())
 }
 
+/// Import the legacy HTML translations from sc-data/html_text/<lang>/pli/...
+///
+/// `languages` and `divisions` restrict the import, such as `["en", "de"]` and `["an", "mn"]`.
 pub fn process_suttacentral_html_texts_to_sqlite(
     sc_data_path: &Path,
-    sqlite_db_path: &Path
+    sqlite_db_path: &Path,
+    languages: &Option<Vec<String>>,
+    divisions: &Option<Vec<String>>,
     ) -> Result<(), Box<dyn Error>>
 {
     info!("process_suttacentral_html_texts()");
@@ -547,26 +594,88 @@ pub fn process_suttacentral_html_texts_to_sqlite(
         authors_editions = serde_json::from_str(&s).unwrap();
     }
 
+    let structure_suttas = read_structure_suttas(sc_data_path)?;
+
     info!{"\n=== Being processing HTML texts data. ===\n"};
 
     let html_root = sc_data_path.join(Path::new("html_text"));
-    let folder = html_root.join(Path::new("en/pli/sutta/an"));
 
-    info!("Walking '{:?}'", folder);
+    info!("Walking '{:?}'", html_root);
 
-    let walker = WalkDir::new(&folder).into_iter();
+    let walker = WalkDir::new(&html_root).sort_by(|a, b| a.file_name().cmp(b.file_name())).into_iter();
     for entry in walker.filter_entry(|e| !is_hidden(e)) {
         let entry = entry.unwrap();
         let entry_path = entry.path().to_str().unwrap();
         let entry_file_name = entry.file_name().to_str().unwrap();
 
         if entry.path().is_dir() {
-            info!("Skipping dir entry '{}'", entry.path().to_str().unwrap());
             continue;
         }
         if !entry_file_name.ends_with(".html") {
             continue;
         }
+        // Only the translations of Pali texts: html_text/<lang>/pli/...
+        if !entry_path.contains("/pli/") {
+            continue;
+        }
+
+        // name of the the first folder after html_root
+        // sc-data/html_text/en/pli/sutta/kn
+        // en
+        let translated_lang;
+        {
+            // No slash at the beginning:
+            // en/pli/sutta/mn
+            let folder = entry_path
+                .trim_start_matches(html_root.to_str().unwrap())
+                .trim_start_matches('/');
+            let a = folder.find('/').unwrap();
+            translated_lang = folder[0..a].to_string();
+        }
+
+        if let Some(langs) = languages {
+            if !langs.contains(&translated_lang) {
+                continue;
+            }
+        }
+
+        // an
+        let mut division = String::new();
+        if entry_path.contains("/pli/sutta/") || entry_path.contains("/pli/abhidhamma/")
+        {
+            // $ ls html_text/en/pli/sutta
+            // an  dn  kn  mn  sn
+            // $ ls html_text/en/pli/abhidhamma
+            // ds  kv  patthana  vb
+
+            let re = Regex::new("/pli/(sutta|abhidhamma)/([^/]+)/").unwrap();
+            for cap in re.captures_iter(entry_path) {
+                division = cap[2].to_string();
+            }
+
+        } else if entry_path.contains("/pli/vinaya/") {
+
+            // pli-tv-bu-vb from pli-tv-bu-vb-np10
+            // pli-tv-kd from pli-tv-kd1
+            let stem = entry.path().file_stem().unwrap().to_str().unwrap();
+            let re = Regex::new("^(pli-tv-(?:bu|bi)-vb|pli-tv-[a-z]+)").unwrap();
+            division = match re.captures(stem) {
+                Some(caps) => caps[1].to_string(),
+                None => stem.to_string(),
+            };
+
+        }
+
+        if division.is_empty() {
+            warn!{"Can't obtain division, skipping: {}", entry_path};
+            continue;
+        }
+
+        if let Some(divs) = divisions {
+            if !divs.contains(&division) {
+                continue;
+            }
+        }
 
         info!("Processing: {}", entry_path);
 
@@ -590,20 +699,6 @@ pub fn process_suttacentral_html_texts_to_sqlite(
         // in the middle of the text
         body_plain = Regex::new("\n[#> ]+").unwrap().replace_all(&body_plain, "\n").to_string();
 
-        // name of the the first folder after html_root
-        // sc-data/html_text/en/pli/sutta/kn
-        // en
-        let translated_lang;
-        {
-            // No slash at the beginning:
-            // en/pli/sutta/mn
-            let folder = entry_path
-                .trim_start_matches(html_root.to_str().unwrap())
-                .trim_start_matches('/');
-            let a = folder.find('/').unwrap();
-            translated_lang = folder[0..a].to_string();
-        }
-
         let translated_author: DbAuthor;
         {
 
@@ -673,7 +768,10 @@ pub fn process_suttacentral_html_texts_to_sqlite(
                             .find(|i| i.long_name == *long_name)
                         {
                             Some(author) => author,
-                            None => panic!{"Can't find author in the JSON: '{}'", long_name},
+                            None => {
+                                warn!{"Can't find author in the JSON: '{}', skipping: {}", long_name, entry_path};
+                                continue;
+                            }
                         };
 
                         let new_author = NewAuthor {
@@ -692,30 +790,6 @@ pub fn process_suttacentral_html_texts_to_sqlite(
             };
         }
 
-        // an
-        let mut division = String::new();
-        if entry_path.contains("/pli/sutta/") || entry_path.contains("/pli/abhidhamma/")
-        {
-            // $ ls html_text/en/pli/sutta
-            // an  dn  kn  mn  sn
-            // $ ls html_text/en/pli/abhidhamma
-            // ds  kv  patthana  vb
-
-            let re = Regex::new("/pli/(sutta|abhidhamma)/([^/]+)/").unwrap();
-            for cap in re.captures_iter(entry_path) {
-                division = cap[2].to_string();
-            }
-
-        } else if entry_path.contains("/pli/vinaya/") {
-
-            division = entry.path().file_stem().unwrap().to_str().unwrap().to_string();
-
-        }
-
-        if division.is_empty() {
-            panic!{"Can't obtain division from: {}", entry_path};
-        }
-
         let vagga_name = entry.path().file_stem().unwrap().to_str().unwrap().to_string();
         // 1.001-10 from an1.001-10.json
         // np10     from pli-tv-bu-vb-np10.json
@@ -790,25 +864,35 @@ pub fn process_suttacentral_html_texts_to_sqlite(
         let mut acronym = String::new();
         let mut volpage = String::new();
 
-        // Obtain acronym and volpage from a root text corresponding to the
-        // translated text.
+        // Obtain acronym and volpage from sutta.json, using the file name as
+        // uid, with leading zeros removed: mn077 -> mn77
 
-        let root_text_uid = format!{"{}/{}/{}/{}",
-                                    division,
-                                    div_number,
-                                    "pli",
-                                    "ms"};
+        let vol_uid = Regex::new("([a-z\\.-]+)0*([1-9][0-9]*)").unwrap().replace_all(&vagga_name, "$1$2").to_string();
+        if let Some(s) = structure_suttas.iter().find(|i| i.uid == vol_uid) {
+            acronym = s.acronym.clone();
+            volpage = s.volpage.clone();
+        }
 
-        match get_root_text(&conn, &root_text_uid) {
-            Some(text) => {
-                acronym = text.acronym;
-                volpage = text.volpage;
-            },
-            None => {
-                warn!("Can't obtain acronym and volpage text with uid '{}', for path: {}",
-                      root_text_uid,
-                      entry_path);
-            },
+        // Otherwise from a root text corresponding to the translated text.
+
+        if acronym.is_empty() {
+            let root_text_uid = format!{"{}/{}/{}/{}",
+                                        division,
+                                        div_number,
+                                        "pli",
+                                        "ms"};
+
+            match get_root_text(&conn, &root_text_uid) {
+                Some(text) => {
+                    acronym = text.acronym;
+                    volpage = text.volpage;
+                },
+                None => {
+                    warn!("Can't obtain acronym and volpage text with uid '{}', for path: {}",
+                          root_text_uid,
+                          entry_path);
+                },
+            }
         }
 
         let new_translated_text = NewTranslatedText {
@@ -842,94 +926,1179 @@ pub fn process_suttacentral_html_texts_to_sqlite(
     Ok(())
 }
 
-fn is_hidden(entry: &DirEntry) -> bool {
-    entry.file_name()
-        .to_str()
-        .map(|s| s.starts_with('.'))
-        .unwrap_or(false)
+/// Acronym and title for the Vinaya texts, derived from the file name.
+///
+/// - pli-tv-bu-vb-np10 -> (Bu NP 10, Nissaggiya Pācittiya 10)
+/// - pli-tv-bi-vb-pc5  -> (Bi Pc 5, Pācittiya 5)
+/// - pli-tv-kd1        -> (Kd 1, Khandhaka 1)
+fn vinaya_acronym_and_title(vagga_name: &str) -> Option<(String, String)> {
+    let re = Regex::new(r"^pli-tv-(?:(?P<vb>bu|bi)-vb-(?P<class>[a-z]+)|(?P<other>kd|pvr))0*(?P<num>[0-9][0-9\.-]*)$").unwrap();
+    let caps = re.captures(vagga_name)?;
+    let num = &caps["num"];
+
+    if let Some(other) = caps.name("other") {
+        let (acr, name) = match other.as_str() {
+            "kd" => ("Kd", "Khandhaka"),
+            _ => ("Pvr", "Parivāra"),
+        };
+        return Some((format!("{} {}", acr, num), format!("{} {}", name, num)));
+    }
+
+    let vb = if &caps["vb"] == "bu" { "Bu" } else { "Bi" };
+    let (class_acr, class_name) = match &caps["class"] {
+        "pj" => ("Pj", "Pārājika"),
+        "ss" => ("Ss", "Saṅghādisesa"),
+        "ay" => ("Ay", "Aniyata"),
+        "np" => ("NP", "Nissaggiya Pācittiya"),
+        "pc" => ("Pc", "Pācittiya"),
+        "pd" => ("Pd", "Pāṭidesanīya"),
+        "sk" => ("Sk", "Sekhiya"),
+        "as" => ("As", "Adhikaraṇasamatha"),
+        _ => return None,
+    };
+
+    Some((format!("{} {} {}", vb, class_acr, num), format!("{} {}", class_name, num)))
 }
 
-fn get_author(conn: &SqliteConnection,
-                  author_uid: &str)
-                  -> Option<DbAuthor>
+/// Import the segmented texts of suttacentral/bilara-data.
+///
+/// The `root/`, `translation/` and `html/` JSON files are keyed by segment id, such as
+/// `mn1:1.2`. The html file has a template for each segment, with `{}` where the text goes.
+///
+/// - bilara-data/root/pli/ms/sutta/mn/mn1_root-pli-ms.json
+/// - bilara-data/translation/en/sujato/sutta/mn/mn1_translation-en-sujato.json
+/// - bilara-data/html/pli/ms/sutta/mn/mn1_html.json
+/// - bilara-data/reference/pli/ms/sutta/mn/mn1_reference.json
+///
+/// The PTS pages of the `pts-vp-pli` ids in the reference file are added to `pts_concordance`.
+///
+/// Authors are looked up by the uid in the path (`ms`, `sujato`) in sc-data's author_edition.json.
+pub fn process_suttacentral_bilara_to_sqlite(
+    bilara_data_path: &Path,
+    sc_data_path: &Path,
+    sqlite_db_path: &Path
+    ) -> Result<(), Box<dyn Error>>
 {
-    use db_schema::authors::dsl::*;
-    //use schema::authors;
+    info!("process_suttacentral_bilara_to_sqlite()");
 
-    let mut items = authors
-        .filter(uid.eq(author_uid))
-        .load::<DbAuthor>(conn)
-        .expect("Error loading the author.");
+    // === Database connection ===
 
-    items.pop()
-}
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
 
-fn get_author_by_long_name(conn: &SqliteConnection,
-                               author_long_name: &str)
-                               -> Option<DbAuthor>
-{
-    use db_schema::authors::dsl::*;
+    let mut authors: Vec<DbAuthor> = vec![];
+    let mut root_texts: Vec<DbRootText> = vec![];
+    let mut translated_texts: Vec<DbTranslatedText> = vec![];
 
-    let mut items = authors
-        .filter(long_name.eq(author_long_name))
-        .load::<DbAuthor>(conn)
-        .expect("Error loading the author.");
+    let authors_editions: Vec<AuthorEdition>;
+    {
+        let p = sc_data_path.join(PathBuf::from("additional-info/author_edition.json"));
+        let s = fs::read_to_string(p)?;
+        authors_editions = serde_json::from_str(&s)?;
+    }
 
-    items.pop()
-}
+    // sutta.json is only used for acronym and volpage, it is not an error if it is missing.
+    let structure_suttas: Vec<StructureSutta>;
+    {
+        let p = sc_data_path.join(PathBuf::from("structure/sutta.json"));
+        if p.exists() {
+            structure_suttas = read_structure_suttas(sc_data_path)?;
+        } else {
+            warn!{"Missing: {:?}", p};
+            structure_suttas = vec![];
+        }
+    }
 
-fn create_new_author<'a>(conn: &SqliteConnection,
-                         new_author: &'a NewAuthor)
-                         -> DbAuthor
-{
-    use db_schema::authors::dsl::*;
-    use db_schema::authors;
+    info!{"\n=== Begin processing bilara-data. ===\n"};
 
-    diesel::insert_into(authors::table)
-        .values(new_author)
-        .execute(conn)
-        .expect("Error inserting the author.");
+    // mn1_root-pli-ms.json
+    let re_root = Regex::new(r"^(?P<uid>.+)_root-(?P<lang>[a-z]+)-(?P<author>[a-z0-9-]+)\.json$").unwrap();
+    // mn1_translation-en-sujato.json
+    let re_translation = Regex::new(r"^(?P<uid>.+)_translation-(?P<lang>[a-z]+)-(?P<author>[a-z0-9-]+)\.json$").unwrap();
 
-    let mut items = authors
-        .filter(uid.eq(new_author.uid))
-        .load::<DbAuthor>(conn)
-        .expect("Error loading the inserted author.");
+    // Index the translations by sutta uid, there may be more than one for each root text.
+    let mut translation_files: BTreeMap<String, Vec<BilaraFile>> = BTreeMap::new();
+    {
+        let folder = bilara_data_path.join("translation");
+        info!("Walking '{:?}'", folder);
 
-    items.pop().unwrap()
-}
+        let walker = WalkDir::new(&folder).into_iter();
+        for entry in walker.filter_entry(|e| !is_hidden(e)) {
+            let entry = entry?;
+            let file_name = entry.file_name().to_str().unwrap();
+            if let Some(caps) = re_translation.captures(file_name) {
+                let f = BilaraFile {
+                    uid: caps["uid"].to_string(),
+                    lang: caps["lang"].to_string(),
+                    author_uid: caps["author"].to_string(),
+                    path: entry.path().to_path_buf(),
+                };
+                translation_files.entry(f.uid.clone()).or_insert_with(Vec::new).push(f);
+            }
+        }
+    }
 
-fn get_root_text(conn: &SqliteConnection,
-                     text_uid: &str) -> Option<DbRootText>
-{
-    use db_schema::root_texts::dsl::*;
-    //use db_schema::root_texts;
+    let mut root_files: Vec<BilaraFile> = vec![];
+    {
+        let folder = bilara_data_path.join("root");
+        info!("Walking '{:?}'", folder);
 
-    let mut items = root_texts
-        .filter(uid.eq(text_uid))
-        .load::<DbRootText>(conn)
-        .expect("Error loading the root text.");
+        let walker = WalkDir::new(&folder).into_iter();
+        for entry in walker.filter_entry(|e| !is_hidden(e)) {
+            let entry = entry?;
+            let file_name = entry.file_name().to_str().unwrap();
+            if let Some(caps) = re_root.captures(file_name) {
+                root_files.push(BilaraFile {
+                    uid: caps["uid"].to_string(),
+                    lang: caps["lang"].to_string(),
+                    author_uid: caps["author"].to_string(),
+                    path: entry.path().to_path_buf(),
+                });
+            }
+        }
+    }
 
-    items.pop()
-}
+    root_files.sort_by(|a, b| a.path.cmp(&b.path));
 
-fn create_new_root_text<'a>(conn: &SqliteConnection,
-                            new_root_text: &'a NewRootText)
-                            -> DbRootText
-{
-    use db_schema::root_texts::dsl::*;
-    use db_schema::root_texts;
+    for root_file in root_files.iter() {
+        info!("Processing: {}", root_file.uid);
 
-    diesel::insert_into(root_texts::table)
-        .values(new_root_text)
-        .execute(conn)
-        .expect("Error inserting the root text.");
+        // root/pli/ms/sutta/mn/mn1_root-pli-ms.json -> html/pli/ms/sutta/mn/mn1_html.json
+        let html_path = {
+            let rel = root_file.path.strip_prefix(bilara_data_path.join("root"))?;
+            let p = bilara_data_path.join("html").join(rel.parent().unwrap());
+            p.join(format!("{}_html.json", root_file.uid))
+        };
 
-    let mut items = root_texts
-        .filter(uid.eq(new_root_text.uid))
-        .load::<DbRootText>(conn)
-        .expect("Error loading the inserted root text.");
+        if !html_path.exists() {
+            warn!{"Missing html, skipping: {:?}", html_path};
+            continue;
+        }
 
-    items.pop().unwrap()
+        let root_segments = read_bilara_segments(&root_file.path)?;
+        let html_segments = read_bilara_segments(&html_path)?;
+
+        let seg_ids = sorted_segment_ids(&html_segments);
+
+        // mn1 -> mn/1
+        let (division, div_number) = split_sutta_uid(&root_file.uid);
+
+        let mut acronym = String::new();
+        let mut volpage = String::new();
+        if let Some(s) = structure_suttas.iter().find(|i| i.uid == root_file.uid) {
+            acronym = s.acronym.clone();
+            volpage = s.volpage.clone();
+        }
+
+        // --- Root text ---
+
+        let root_author = match get_or_insert_author(&conn, &root_file.author_uid, &authors_editions, &mut authors) {
+            Some(x) => x,
+            None => {
+                warn!{"Can't find author uid: '{}', skipping: {:?}", root_file.author_uid, root_file.path};
+                continue;
+            }
+        };
+
+        // dn/1/pli/ms
+        let root_text_uid = format!{"{}/{}/{}/{}",
+                                    division,
+                                    div_number,
+                                    root_file.lang,
+                                    root_author.uid};
+
+        let title_pali = bilara_title(&seg_ids, &html_segments, &root_segments);
+        let root_html = bilara_segments_to_html(&seg_ids, &html_segments, &root_segments);
+        let root_plain = html_to_plain_text(&root_html);
+
+        match get_root_text(&conn, &root_text_uid) {
+            Some(_text) => warn!("Already exists: {}", root_text_uid),
+            None => {
+                let new_root_text = NewRootText {
+                    author_id:        &root_author.id,
+                    uid:              &root_text_uid,
+                    acronym:          &acronym,
+                    volpage:          &volpage,
+                    title:            &title_pali,
+                    content_language: &root_file.lang,
+                    content_html:     &root_html,
+                    content_plain:    &root_plain,
+                };
+
+                info!("Inserting root text: {}", new_root_text.uid);
+                let text = create_new_root_text(&conn, &new_root_text);
+                root_texts.push(text);
+
+                // bilara-data/variant/pli/ms/sutta/mn/mn1_variant-pli-ms.json
+                let variant_path = {
+                    let rel = root_file.path.strip_prefix(bilara_data_path.join("root"))?;
+                    let p = bilara_data_path.join("variant").join(rel.parent().unwrap());
+                    p.join(format!("{}_variant-{}-{}.json", root_file.uid, root_file.lang, root_file.author_uid))
+                };
+
+                if variant_path.exists() {
+                    let mut variants: Vec<(String, VariantReading)> = vec![];
+                    for (seg_id, note) in read_bilara_segments(&variant_path)?.iter() {
+                        for v in parse_variant_note(note) {
+                            variants.push((seg_id.clone(), v));
+                        }
+                    }
+                    create_variant_readings(&conn, &root_text_uid, &variants);
+                }
+
+                // bilara-data/reference/pli/ms/sutta/mn/mn1_reference.json
+                let reference_path = {
+                    let rel = root_file.path.strip_prefix(bilara_data_path.join("root"))?;
+                    let p = bilara_data_path.join("reference").join(rel.parent().unwrap());
+                    p.join(format!("{}_reference.json", root_file.uid))
+                };
+
+                if reference_path.exists() {
+                    let references = read_bilara_segments(&reference_path)?;
+                    // PTS page markers start at this segment.
+                    let mut pts_markers: Vec<(PtsMarker, usize)> = vec![];
+                    for (n, seg_id) in seg_ids.iter().enumerate() {
+                        if let Some(x) = references.get(seg_id) {
+                            for m in concordance::parse_bilara_pts_references(x) {
+                                pts_markers.push((m, n + 1));
+                            }
+                        }
+                    }
+
+                    let empty = BTreeMap::new();
+                    let segments = bilara_text_segments(&seg_ids, &html_segments, &root_segments, &empty);
+                    let pts_pages = pts_pages_from_markers(&pts_markers, &segments, &volpage, &division);
+                    create_pts_concordance(&conn, &root_text_uid, &pts_pages);
+                }
+
+                // Without a translation, the segments are stored with the root text only.
+                if !translation_files.contains_key(&root_file.uid) {
+                    let empty = BTreeMap::new();
+                    let segments = bilara_text_segments(&seg_ids, &html_segments, &root_segments, &empty);
+                    create_text_segments(&conn, &root_text_uid, "", &segments);
+                }
+            }
+        }
+
+        // --- Translated texts ---
+
+        let files = match translation_files.get(&root_file.uid) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        for tr_file in files.iter() {
+            let tr_segments = read_bilara_segments(&tr_file.path)?;
+
+            let translated_author = match get_or_insert_author(&conn, &tr_file.author_uid, &authors_editions, &mut authors) {
+                Some(x) => x,
+                None => {
+                    warn!{"Can't find author uid: '{}', skipping: {:?}", tr_file.author_uid, tr_file.path};
+                    continue;
+                }
+            };
+
+            // dn/1/en/sujato
+            let translated_text_uid = format!{"{}/{}/{}/{}",
+                                              division,
+                                              div_number,
+                                              tr_file.lang,
+                                              translated_author.uid};
+
+            let title_translated = bilara_title(&seg_ids, &html_segments, &tr_segments);
+            let translated_html = bilara_segments_to_html(&seg_ids, &html_segments, &tr_segments);
+            let translated_plain = html_to_plain_text(&translated_html);
+
+            match get_translated_text(&conn, &translated_text_uid) {
+                Some(_text) => warn!("Already exists: {}", translated_text_uid),
+                None => {
+                    let new_translated_text = NewTranslatedText {
+                        author_id:        &translated_author.id,
+                        uid:              &translated_text_uid,
+                        acronym:          &acronym,
+                        volpage:          &volpage,
+                        title:            &title_translated,
+                        root_title:       &title_pali,
+                        content_language: &tr_file.lang,
+                        content_html:     &translated_html,
+                        content_plain:    &translated_plain,
+                    };
+
+                    info!("Inserting translated text: {}", new_translated_text.uid);
+                    let text = create_new_translated_text(&conn, &new_translated_text);
+                    translated_texts.push(text);
+
+                    let segments = bilara_text_segments(&seg_ids, &html_segments, &root_segments, &tr_segments);
+                    create_text_segments(&conn, &root_text_uid, &translated_text_uid, &segments);
+                }
+            }
+        }
+    }
+
+    info!{"\n=== End of processing bilara-data. ===\n"};
+
+    info!{"Created Authors: {}", authors.len()};
+
+    info!{"Created RootTexts: {}", root_texts.len()};
+
+    info!{"Created TranslatedTexts: {}", translated_texts.len()};
+
+    Ok(())
+}
+
+/// A root or translation JSON file in bilara-data, with the info parsed from its file name.
+struct BilaraFile {
+    uid: String,
+    lang: String,
+    author_uid: String,
+    path: PathBuf,
+}
+
+fn read_bilara_segments(path: &Path) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let s = fs::read_to_string(path)?;
+    let segments: BTreeMap<String, String> = serde_json::from_str(&s)?;
+    Ok(segments)
+}
+
+/// Segment ids sorted in text order. The keys have to be compared by their numbers, otherwise
+/// `mn1:10.1` would come before `mn1:2.1`.
+fn sorted_segment_ids(segments: &BTreeMap<String, String>) -> Vec<String> {
+    lazy_static! {
+        static ref RE_SEG_ID_PARTS: Regex = Regex::new(r"[0-9]+|[^0-9]+").unwrap();
+    }
+
+    fn sort_key(seg_id: &str) -> Vec<(usize, String)> {
+        // mn1:1.2 -> [(0, "mn"), (1, ""), (0, ":"), (1, ""), (0, "."), (2, "")]
+        RE_SEG_ID_PARTS.find_iter(seg_id)
+            .map(|m| match m.as_str().parse::<usize>() {
+                Ok(n) => (n, "".to_string()),
+                Err(_) => (0, m.as_str().to_string()),
+            })
+            .collect()
+    }
+
+    let mut ids: Vec<String> = segments.keys().cloned().collect();
+    ids.sort_by_cached_key(|i| sort_key(i));
+    ids
+}
+
+/// mn1 -> (mn, 1), an1.1-10 -> (an, 1.1-10), pli-tv-bu-vb-pj1 -> (pli-tv-bu-vb-pj, 1)
+fn split_sutta_uid(uid: &str) -> (String, String) {
+    let re = Regex::new(r"^(?P<div>[a-z-]+?)-?(?P<num>[0-9].*)$").unwrap();
+    match re.captures(uid) {
+        Some(caps) => (caps["div"].to_string(), caps["num"].to_string()),
+        None => (uid.to_string(), "".to_string()),
+    }
+}
+
+/// Fill the html templates with the segment texts.
+fn bilara_segments_to_html(
+    seg_ids: &[String],
+    html_segments: &BTreeMap<String, String>,
+    text_segments: &BTreeMap<String, String>,
+) -> String {
+    let mut html = String::new();
+    for seg_id in seg_ids.iter() {
+        let template = html_segments.get(seg_id).map(|x| x.as_str()).unwrap_or("{}");
+        let text = text_segments.get(seg_id).map(|x| x.trim()).unwrap_or("");
+        // append space to separate sentences
+        html.push_str(&template.replace("{}", &format!("{} ", text)));
+    }
+    html
+}
+
+fn bilara_text_segments(
+    seg_ids: &[String],
+    html_segments: &BTreeMap<String, String>,
+    root_segments: &BTreeMap<String, String>,
+    tr_segments: &BTreeMap<String, String>,
+) -> Vec<TextSegment> {
+    seg_ids.iter()
+        .map(|seg_id| {
+            let get = |m: &BTreeMap<String, String>| m.get(seg_id).map(|x| x.trim().to_string()).unwrap_or_default();
+            TextSegment {
+                segment_id: seg_id.clone(),
+                root_text: get(root_segments),
+                translation: get(tr_segments),
+                comment_html: get(html_segments),
+            }
+        })
+        .collect()
+}
+
+/// The title is the segment which the html template puts in a <h1>.
+fn bilara_title(
+    seg_ids: &[String],
+    html_segments: &BTreeMap<String, String>,
+    text_segments: &BTreeMap<String, String>,
+) -> String {
+    let title_re = Regex::new("^[0-9 \\.]+").unwrap();
+    for seg_id in seg_ids.iter() {
+        if let Some(template) = html_segments.get(seg_id) {
+            if template.contains("<h1") {
+                let text = text_segments.get(seg_id).map(|x| x.trim()).unwrap_or("");
+                return title_re.replace(text, "").to_string();
+            }
+        }
+    }
+    "".to_string()
+}
+
+/// The author of a bilara file, inserted from `authors_editions` if needed. `None` when the uid is
+/// not in `authors_editions`.
+fn get_or_insert_author(
+    conn: &SqliteConnection,
+    author_uid: &str,
+    authors_editions: &[AuthorEdition],
+    authors: &mut Vec<DbAuthor>,
+) -> Option<DbAuthor> {
+    match get_author(conn, author_uid) {
+        Some(author) => Some(author),
+        None => {
+            let a = authors_editions.iter().find(|i| i.uid == author_uid)?;
+
+            let new_author = NewAuthor {
+                uid:         &a.uid,
+                blurb:       "",
+                long_name:   &a.long_name,
+                short_name:  &a.short_name,
+            };
+
+            info!("Inserting author: {}", new_author.uid);
+            let author: DbAuthor = create_new_author(conn, &new_author);
+            authors.push(author);
+
+            get_author(conn, author_uid)
+        }
+    }
+}
+
+fn html_to_plain_text(html: &str) -> String {
+    let mut plain = html2text::from_read(html.as_bytes(), 100);
+
+    // strip markdown # and > from plain text content
+
+    // at the beginning of the text
+    plain = Regex::new("^[#> ]+").unwrap().replace_all(&plain, "").to_string();
+
+    // in the middle of the text
+    plain = Regex::new("\n[#> ]+").unwrap().replace_all(&plain, "\n").to_string();
+
+    plain
+}
+
+/// A segment of a text, with its root and translation. The order is given by its position in the
+/// list when it is inserted.
+struct TextSegment {
+    segment_id: String,
+    root_text: String,
+    translation: String,
+    comment_html: String,
+}
+
+impl TextSegment {
+    fn new(segment_id: &str, root_text: &str, translation: &str, comment_html: &str) -> Self {
+        TextSegment {
+            segment_id: segment_id.to_string(),
+            root_text: root_text.trim().to_string(),
+            translation: translation.trim().to_string(),
+            comment_html: comment_html.to_string(),
+        }
+    }
+}
+
+fn create_text_segments(conn: &SqliteConnection,
+                        root_text_uid: &str,
+                        translated_text_uid: &str,
+                        segments: &[TextSegment])
+{
+    use db_schema::text_segments;
+
+    let orders: Vec<i32> = (1..=segments.len() as i32).collect();
+
+    let new_segments: Vec<NewTextSegment> = segments.iter()
+        .zip(orders.iter())
+        .map(|(seg, order)| NewTextSegment {
+            segment_id:          &seg.segment_id,
+            root_text_uid,
+            translated_text_uid,
+            segment_order:       order,
+            root_text:           &seg.root_text,
+            translation:         &seg.translation,
+            comment_html:        &seg.comment_html,
+        })
+        .collect();
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        for i in new_segments.iter() {
+            diesel::insert_into(text_segments::table)
+                .values(i)
+                .execute(conn)?;
+        }
+        Ok(())
+    }).expect("Error inserting the text segments.");
+}
+
+/// A PTS page of a text, from the page marker to the segment before the next marker of the same
+/// edition.
+struct PtsPage {
+    collection: String,
+    volume: i32,
+    page: i32,
+    edition: String,
+    sutta_uid: String,
+    segment_start: String,
+    segment_end: String,
+    start_order: i32,
+    end_order: i32,
+}
+
+/// The markers are paired with the segment order (1-based) where they appear. The collection,
+/// and the volume when the marker id doesn't include it, are taken from the volpage of the text.
+fn pts_pages_from_markers(markers: &[(PtsMarker, usize)],
+                          segments: &[TextSegment],
+                          volpage: &str,
+                          division: &str)
+                          -> Vec<PtsPage>
+{
+    let text_pts = PtsRef::parse(volpage);
+    let collection = match &text_pts {
+        Some(x) => x.collection.clone(),
+        None if division.starts_with("pli-tv") => "vin".to_string(),
+        None => division.to_string(),
+    };
+
+    let mut pages: Vec<PtsPage> = Vec::new();
+
+    for (n, (marker, start)) in markers.iter().enumerate() {
+        let volume = match (marker.volume, &text_pts) {
+            (Some(v), _) => v,
+            (None, Some(x)) => x.volume,
+            (None, None) => {
+                warn!{"Can't obtain the PTS volume for page {} in {}", marker.page, division};
+                continue;
+            }
+        };
+
+        let end = match markers[n+1..].iter().find(|(m, _)| m.edition == marker.edition) {
+            Some((_, next)) if next > start => next - 1,
+            Some(_) => *start,
+            None => segments.len(),
+        };
+
+        if *start == 0 || end == 0 || *start > segments.len() || end > segments.len() {
+            continue;
+        }
+
+        let segment_start = segments[start - 1].segment_id.clone();
+        let sutta_uid = segment_start.split(':').next().unwrap_or("").to_string();
+
+        pages.push(PtsPage {
+            collection: collection.clone(),
+            volume,
+            page: marker.page,
+            edition: marker.edition.clone(),
+            sutta_uid,
+            segment_start,
+            segment_end: segments[end - 1].segment_id.clone(),
+            start_order: *start as i32,
+            end_order: end as i32,
+        });
+    }
+
+    pages
+}
+
+fn create_pts_concordance(conn: &SqliteConnection,
+                          root_text_uid: &str,
+                          pages: &[PtsPage])
+{
+    use db_schema::pts_concordance;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        for p in pages.iter() {
+            diesel::insert_into(pts_concordance::table)
+                .values(&NewPtsConcordance {
+                    collection:          &p.collection,
+                    volume:              &p.volume,
+                    page:                &p.page,
+                    edition:             &p.edition,
+                    sutta_uid:           &p.sutta_uid,
+                    root_text_uid,
+                    segment_start:       &p.segment_start,
+                    segment_end:         &p.segment_end,
+                    segment_start_order: &p.start_order,
+                    segment_end_order:   &p.end_order,
+                })
+                .execute(conn)?;
+        }
+        Ok(())
+    }).expect("Error inserting the PTS concordance.");
+}
+
+/// A variant reading from a VAR note.
+#[derive(Debug, Clone, PartialEq)]
+struct VariantReading {
+    lemma: String,
+    reading: String,
+    editions: String,
+}
+
+/// Parse a variant note. Alternative readings are separated by `|`, and several notes for a
+/// segment are separated by `;`.
+///
+/// `Na hi nūna → nahanūna (bj, s1, s2, km, pts1) | naha nūna (s3)`
+///
+/// - Na hi nūna, nahanūna, bj, s1, s2, km, pts1
+/// - Na hi nūna, naha nūna, s3
+fn parse_variant_note(note: &str) -> Vec<VariantReading> {
+    lazy_static! {
+        static ref RE_EDITIONS: Regex = Regex::new(r"^(?P<reading>.*?) *\((?P<editions>[^\(\)]*)\) *$").unwrap();
+    }
+
+    let mut res: Vec<VariantReading> = vec![];
+    let mut lemma = String::new();
+
+    for part in note.split(';') {
+        let alternatives = match part.find('→') {
+            Some(n) => {
+                lemma = part[..n].trim().to_string();
+                &part[n + '→'.len_utf8()..]
+            }
+            // Without an arrow, the note continues the previous lemma.
+            None => part,
+        };
+
+        if lemma.is_empty() {
+            continue;
+        }
+
+        for alt in alternatives.split('|') {
+            let alt = alt.trim();
+            if alt.is_empty() {
+                continue;
+            }
+
+            let (reading, editions) = match RE_EDITIONS.captures(alt) {
+                Some(caps) => (caps["reading"].trim().to_string(), caps["editions"].trim().to_string()),
+                None => (alt.to_string(), "".to_string()),
+            };
+
+            res.push(VariantReading {
+                lemma: lemma.clone(),
+                reading,
+                editions,
+            });
+        }
+    }
+
+    res
+}
+
+fn create_variant_readings(conn: &SqliteConnection,
+                           root_text_uid: &str,
+                           variants: &[(String, VariantReading)])
+{
+    use db_schema::variant_readings;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        for (seg_id, v) in variants.iter() {
+            let new_variant = NewVariantReading {
+                segment_id:    seg_id,
+                root_text_uid,
+                lemma:         &v.lemma,
+                reading:       &v.reading,
+                editions:      &v.editions,
+            };
+
+            diesel::insert_into(variant_readings::table)
+                .values(&new_variant)
+                .execute(conn)?;
+        }
+        Ok(())
+    }).expect("Error inserting the variant readings.");
+}
+
+/// Import the sutta hierarchy of sc-data/structure/sutta.json into the `collections` and
+/// `sutta_index` tables, and link the root and translated texts to it.
+///
+/// Run it after the texts were imported. Running it again replaces the previous import.
+///
+/// - division:    an
+/// - subdivision: an4
+/// - vagga:       an4-vagga7
+/// - sutta:       an4.67
+pub fn process_suttacentral_structure_to_sqlite(
+    sc_data_path: &Path,
+    sqlite_db_path: &Path
+    ) -> Result<(), Box<dyn Error>>
+{
+    info!("process_suttacentral_structure_to_sqlite()");
+
+    // === Database connection ===
+
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
+
+    let structure_suttas = read_structure_suttas(sc_data_path)?;
+
+    {
+        use db_schema::{collections, sutta_index, root_texts, translated_texts};
+
+        diesel::update(root_texts::table)
+            .set(root_texts::sutta_index_id.eq(None::<i32>))
+            .execute(&conn)?;
+        diesel::update(translated_texts::table)
+            .set(translated_texts::sutta_index_id.eq(None::<i32>))
+            .execute(&conn)?;
+        diesel::delete(sutta_index::table).execute(&conn)?;
+        diesel::delete(collections::table).execute(&conn)?;
+
+        use db_schema::pts_concordance;
+        diesel::delete(pts_concordance::table.filter(pts_concordance::edition.eq(concordance::EDITION_VOLPAGE)))
+            .execute(&conn)?;
+    }
+
+    info!{"\n=== Begin processing structure/sutta.json. ===\n"};
+
+    // collection uid -> id
+    let mut collection_ids: BTreeMap<String, i32> = BTreeMap::new();
+    // parent id (0 for the divisions) -> number of child collections
+    let mut collection_counts: BTreeMap<i32, i32> = BTreeMap::new();
+    // sutta uid -> id
+    let mut sutta_ids: BTreeMap<String, i32> = BTreeMap::new();
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        use db_schema::sutta_index;
+
+        for (n, sutta) in structure_suttas.iter().enumerate() {
+            if sutta.uid.is_empty() {
+                continue;
+            }
+            if sutta_ids.contains_key(&sutta.uid) {
+                warn!{"Duplicate sutta uid, skipping: {}", sutta.uid};
+                continue;
+            }
+
+            let subdivision = if sutta.subdivision_uid.is_empty() {
+                split_sutta_uid(&sutta.uid).0
+            } else {
+                sutta.subdivision_uid.clone()
+            };
+            let division = structure_division(&subdivision);
+
+            let mut parent_id = get_or_create_collection(
+                &conn, &mut collection_ids, &mut collection_counts, &division, None, "division")?;
+
+            if subdivision != division {
+                parent_id = get_or_create_collection(
+                    &conn, &mut collection_ids, &mut collection_counts, &subdivision, Some(parent_id), "subdivision")?;
+            }
+
+            if !sutta.vagga_number.is_empty() {
+                let vagga_uid = format!("{}-vagga{}", subdivision, sutta.vagga_number);
+                parent_id = get_or_create_collection(
+                    &conn, &mut collection_ids, &mut collection_counts, &vagga_uid, Some(parent_id), "vagga")?;
+            }
+
+            let order = n as i32 + 1;
+
+            diesel::insert_into(sutta_index::table)
+                .values(&NewSuttaIndex {
+                    uid:             &sutta.uid,
+                    collection_id:   &parent_id,
+                    division:        &division,
+                    subdivision:     &subdivision,
+                    vagga_number:    &sutta.vagga_number,
+                    number_in_vagga: &sutta.number_in_vagga,
+                    sutta_order:     &order,
+                    acronym:         &sutta.acronym,
+                    volpage:         &sutta.volpage,
+                    title_pali:      &sutta.name,
+                    title_trans:     "",
+                })
+                .execute(&conn)?;
+
+            let id = sutta_index::table
+                .filter(sutta_index::uid.eq(&sutta.uid))
+                .select(sutta_index::id)
+                .first::<i32>(&conn)?;
+
+            sutta_ids.insert(sutta.uid.clone(), id);
+
+            if let Some(pts) = PtsRef::parse(&sutta.volpage) {
+                diesel::insert_into(db_schema::pts_concordance::table)
+                    .values(&NewPtsConcordance {
+                        collection:          &pts.collection,
+                        volume:              &pts.volume,
+                        page:                &pts.page,
+                        edition:             concordance::EDITION_VOLPAGE,
+                        sutta_uid:           &sutta.uid,
+                        root_text_uid:       "",
+                        segment_start:       "",
+                        segment_end:         "",
+                        segment_start_order: &0,
+                        segment_end_order:   &0,
+                    })
+                    .execute(&conn)?;
+            }
+        }
+
+        Ok(())
+    }).expect("Error inserting the sutta index.");
+
+    info!{"Created Collections: {}", collection_ids.len()};
+    info!{"Created Suttas: {}", sutta_ids.len()};
+
+    // === Link the texts ===
+
+    let mut linked_count = 0;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        use db_schema::{root_texts, translated_texts, sutta_index};
+
+        let items: Vec<(i32, String)> = root_texts::table
+            .select((root_texts::id, root_texts::uid))
+            .load(&conn)?;
+
+        for (text_id, text_uid) in items.iter() {
+            if let Some(sutta_id) = sutta_ids.get(&text_uid_to_sutta_uid(text_uid)) {
+                diesel::update(root_texts::table.filter(root_texts::id.eq(text_id)))
+                    .set(root_texts::sutta_index_id.eq(sutta_id))
+                    .execute(&conn)?;
+                linked_count += 1;
+            }
+        }
+
+        let items: Vec<(i32, String, String, String)> = translated_texts::table
+            .select((translated_texts::id,
+                     translated_texts::uid,
+                     translated_texts::title,
+                     translated_texts::content_language))
+            .load(&conn)?;
+
+        for (text_id, text_uid, text_title, text_lang) in items.iter() {
+            if let Some(sutta_id) = sutta_ids.get(&text_uid_to_sutta_uid(text_uid)) {
+                diesel::update(translated_texts::table.filter(translated_texts::id.eq(text_id)))
+                    .set(translated_texts::sutta_index_id.eq(sutta_id))
+                    .execute(&conn)?;
+                linked_count += 1;
+
+                // The English title of the first linked translation.
+                if text_lang == "en" && !text_title.is_empty() {
+                    diesel::update(sutta_index::table
+                                   .filter(sutta_index::id.eq(sutta_id))
+                                   .filter(sutta_index::title_trans.eq("")))
+                        .set(sutta_index::title_trans.eq(text_title))
+                        .execute(&conn)?;
+                }
+            }
+        }
+
+        Ok(())
+    }).expect("Error linking the texts to the sutta index.");
+
+    info!{"Linked Texts: {}", linked_count};
+
+    info!{"\n=== End of processing structure/sutta.json. ===\n"};
+
+    Ok(())
+}
+
+fn get_or_create_collection(
+    conn: &SqliteConnection,
+    collection_ids: &mut BTreeMap<String, i32>,
+    collection_counts: &mut BTreeMap<i32, i32>,
+    collection_uid: &str,
+    parent_id: Option<i32>,
+    collection_type: &str,
+) -> Result<i32, diesel::result::Error> {
+    use db_schema::collections;
+
+    if let Some(id) = collection_ids.get(collection_uid) {
+        return Ok(*id);
+    }
+
+    let order = {
+        let n = collection_counts.entry(parent_id.unwrap_or(0)).or_insert(0);
+        *n += 1;
+        *n
+    };
+
+    let (title_pali, title_trans) = collection_titles(collection_uid, collection_type);
+
+    diesel::insert_into(collections::table)
+        .values(&NewCollection {
+            uid:              collection_uid,
+            parent_id,
+            collection_type,
+            collection_order: &order,
+            title_pali:       &title_pali,
+            title_trans:      &title_trans,
+        })
+        .execute(conn)?;
+
+    let id = collections::table
+        .filter(collections::uid.eq(collection_uid))
+        .select(collections::id)
+        .first::<i32>(conn)?;
+
+    collection_ids.insert(collection_uid.to_string(), id);
+
+    Ok(id)
+}
+
+/// The division of a subdivision uid.
+///
+/// - an4             -> an
+/// - dn              -> dn
+/// - thag            -> kn
+/// - pli-tv-bu-vb-pj -> pli-tv-bu-vb
+/// - pli-tv-kd       -> pli-tv-kd
+fn structure_division(subdivision: &str) -> String {
+    if subdivision.starts_with("pli-tv-") {
+        let re = Regex::new("^(pli-tv-(?:bu|bi)-vb|pli-tv-[a-z]+)").unwrap();
+        return match re.captures(subdivision) {
+            Some(caps) => caps[1].to_string(),
+            None => subdivision.to_string(),
+        };
+    }
+
+    let div = subdivision.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-');
+    if division_titles(div).is_some() {
+        div.to_string()
+    } else if kn_book_titles(div).is_some() {
+        "kn".to_string()
+    } else {
+        div.to_string()
+    }
+}
+
+/// Pali and English titles of a collection.
+///
+/// - an          -> (Aṅguttara Nikāya, Numbered Discourses)
+/// - an4         -> (Aṅguttara Nikāya 4, Numbered Discourses 4)
+/// - thag        -> (Theragāthā, Verses of the Senior Monks)
+/// - an4-vagga7  -> (Vagga 7, Chapter 7)
+fn collection_titles(uid: &str, collection_type: &str) -> (String, String) {
+    let to_owned = |(a, b): (&str, &str)| (a.to_string(), b.to_string());
+
+    if collection_type == "vagga" {
+        let n = uid.rsplit("-vagga").next().unwrap_or("");
+        return (format!("Vagga {}", n), format!("Chapter {}", n));
+    }
+
+    if let Some(x) = division_titles(uid) {
+        return to_owned(x);
+    }
+    if let Some(x) = kn_book_titles(uid) {
+        return to_owned(x);
+    }
+
+    // pli-tv-bu-vb-pj
+    let re = Regex::new("^pli-tv-(?:bu|bi)-vb-([a-z]+)$").unwrap();
+    if let Some(caps) = re.captures(uid) {
+        let name = match &caps[1] {
+            "pj" => "Pārājika",
+            "ss" => "Saṅghādisesa",
+            "ay" => "Aniyata",
+            "np" => "Nissaggiya Pācittiya",
+            "pc" => "Pācittiya",
+            "pd" => "Pāṭidesanīya",
+            "sk" => "Sekhiya",
+            "as" => "Adhikaraṇasamatha",
+            _ => uid,
+        };
+        return (name.to_string(), name.to_string());
+    }
+
+    // an4, sn12, thag1
+    let (div, num) = split_sutta_uid(uid);
+    if !num.is_empty() {
+        if let Some((pali, trans)) = division_titles(&div).or_else(|| kn_book_titles(&div)) {
+            return (format!("{} {}", pali, num), format!("{} {}", trans, num));
+        }
+    }
+
+    (uid.to_string(), uid.to_string())
+}
+
+fn division_titles(uid: &str) -> Option<(&'static str, &'static str)> {
+    let x = match uid {
+        "dn" => ("Dīgha Nikāya", "Long Discourses"),
+        "mn" => ("Majjhima Nikāya", "Middle Discourses"),
+        "sn" => ("Saṃyutta Nikāya", "Linked Discourses"),
+        "an" => ("Aṅguttara Nikāya", "Numbered Discourses"),
+        "kn" => ("Khuddaka Nikāya", "Minor Collection"),
+        "pli-tv-bu-vb" => ("Bhikkhuvibhaṅga", "Monks’ Analysis"),
+        "pli-tv-bi-vb" => ("Bhikkhunīvibhaṅga", "Nuns’ Analysis"),
+        "pli-tv-kd" => ("Khandhaka", "Chapters on Legal Topics"),
+        "pli-tv-pvr" => ("Parivāra", "Compendium"),
+        _ => return None,
+    };
+    Some(x)
+}
+
+fn kn_book_titles(uid: &str) -> Option<(&'static str, &'static str)> {
+    let x = match uid {
+        "kp" => ("Khuddakapāṭha", "Minor Readings"),
+        "dhp" => ("Dhammapada", "Sayings of the Dhamma"),
+        "ud" => ("Udāna", "Heartfelt Sayings"),
+        "iti" => ("Itivuttaka", "So It Was Said"),
+        "snp" => ("Suttanipāta", "The Anthology of Discourses"),
+        "vv" => ("Vimānavatthu", "Stories of the Mansions"),
+        "pv" => ("Petavatthu", "Stories of the Ghosts"),
+        "thag" => ("Theragāthā", "Verses of the Senior Monks"),
+        "thig" => ("Therīgāthā", "Verses of the Senior Nuns"),
+        "tha-ap" => ("Therāpadāna", "Legends of the Senior Monks"),
+        "thi-ap" => ("Therīapadāna", "Legends of the Senior Nuns"),
+        "bv" => ("Buddhavaṃsa", "Chronicle of Buddhas"),
+        "cp" => ("Cariyāpiṭaka", "Basket of Conduct"),
+        "ja" => ("Jātaka", "Birth Stories"),
+        "mnd" => ("Mahāniddesa", "Great Exposition"),
+        "cnd" => ("Cūḷaniddesa", "Lesser Exposition"),
+        "ps" => ("Paṭisambhidāmagga", "Path of Discrimination"),
+        "ne" => ("Nettippakaraṇa", "Guide"),
+        "pe" => ("Peṭakopadesa", "Pitaka Disclosure"),
+        "mil" => ("Milindapañha", "Questions of King Milinda"),
+        _ => return None,
+    };
+    Some(x)
+}
+
+/// The sutta uid of a root or translated text uid.
+///
+/// - mn/1/pli/ms               -> mn1
+/// - an/4.67/en/sujato         -> an4.67
+/// - kn/thag1.1/en/x           -> thag1.1
+/// - thag/1.1/pli/ms           -> thag1.1
+/// - pli-tv-bu-vb/np10/pli/ms  -> pli-tv-bu-vb-np10
+pub fn text_uid_to_sutta_uid(text_uid: &str) -> String {
+    let parts: Vec<&str> = text_uid.split('/').collect();
+    if parts.len() < 2 {
+        return text_uid.to_string();
+    }
+    let (div, num) = (parts[0], parts[1]);
+
+    if num.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}{}", div, num)
+    } else if div.starts_with("pli-tv") {
+        format!("{}-{}", div, num)
+    } else {
+        num.to_string()
+    }
+}
+
+/// The corpus division of a root or translated text uid, for the word frequency counts.
+///
+/// - mn/1/pli/ms               -> mn
+/// - kn/thag1.1/en/x           -> kn
+/// - thag/1.1/pli/ms           -> kn
+/// - pli-tv-bu-vb/np10/pli/ms  -> vinaya
+///
+/// Abhidhamma and other texts have no division.
+pub fn text_uid_division(text_uid: &str) -> Option<&'static str> {
+    let div = text_uid.split('/').next().unwrap_or("");
+    match div {
+        "dn" => Some("dn"),
+        "mn" => Some("mn"),
+        "sn" => Some("sn"),
+        "an" => Some("an"),
+        "kn" => Some("kn"),
+        _ if div.starts_with("pli-tv") => Some("vinaya"),
+        _ if kn_book_titles(div).is_some() => Some("kn"),
+        _ => None,
+    }
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    entry.file_name()
+        .to_str()
+        .map(|s| s.starts_with('.'))
+        .unwrap_or(false)
+}
+
+fn get_author(conn: &SqliteConnection,
+                  author_uid: &str)
+                  -> Option<DbAuthor>
+{
+    use db_schema::authors::dsl::*;
+    //use schema::authors;
+
+    let mut items = authors
+        .filter(uid.eq(author_uid))
+        .load::<DbAuthor>(conn)
+        .expect("Error loading the author.");
+
+    items.pop()
+}
+
+fn get_author_by_long_name(conn: &SqliteConnection,
+                               author_long_name: &str)
+                               -> Option<DbAuthor>
+{
+    use db_schema::authors::dsl::*;
+
+    let mut items = authors
+        .filter(long_name.eq(author_long_name))
+        .load::<DbAuthor>(conn)
+        .expect("Error loading the author.");
+
+    items.pop()
+}
+
+fn create_new_author<'a>(conn: &SqliteConnection,
+                         new_author: &'a NewAuthor)
+                         -> DbAuthor
+{
+    use db_schema::authors::dsl::*;
+    use db_schema::authors;
+
+    diesel::insert_into(authors::table)
+        .values(new_author)
+        .execute(conn)
+        .expect("Error inserting the author.");
+
+    let mut items = authors
+        .filter(uid.eq(new_author.uid))
+        .load::<DbAuthor>(conn)
+        .expect("Error loading the inserted author.");
+
+    items.pop().unwrap()
+}
+
+fn get_root_text(conn: &SqliteConnection,
+                     text_uid: &str) -> Option<DbRootText>
+{
+    use db_schema::root_texts::dsl::*;
+    //use db_schema::root_texts;
+
+    let mut items = root_texts
+        .filter(uid.eq(text_uid))
+        .load::<DbRootText>(conn)
+        .expect("Error loading the root text.");
+
+    items.pop()
+}
+
+fn create_new_root_text<'a>(conn: &SqliteConnection,
+                            new_root_text: &'a NewRootText)
+                            -> DbRootText
+{
+    use db_schema::root_texts::dsl::*;
+    use db_schema::root_texts;
+
+    diesel::insert_into(root_texts::table)
+        .values(new_root_text)
+        .execute(conn)
+        .expect("Error inserting the root text.");
+
+    let mut items = root_texts
+        .filter(uid.eq(new_root_text.uid))
+        .load::<DbRootText>(conn)
+        .expect("Error loading the inserted root text.");
+
+    items.pop().unwrap()
 }
 
 fn get_translated_text(conn: &SqliteConnection,
@@ -971,17 +2140,57 @@ fn create_new_translated_text<'a>(conn: &SqliteConnection,
 ///
 /// Ignored fields:
 ///
-/// - "subdivision_uid": "dn",
-/// - "vagga_number": 1,
-/// - "number_in_vagga": 1,
 /// - "biblio_uid": ""
+///
+/// In sutta.json, numerals are sometimes int type, sometimes quoted as string, or null. These
+/// fields are read as strings.
 #[derive(Deserialize)]
 pub struct StructureSutta {
+    #[serde(default, deserialize_with = "de_lenient_string")]
     pub name: String,
     pub uid: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
     pub language: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
     pub acronym: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
     pub volpage: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
+    pub subdivision_uid: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
+    pub vagga_number: String,
+    #[serde(default, deserialize_with = "de_lenient_string")]
+    pub number_in_vagga: String,
+}
+
+/// Accept a string, a number or null.
+fn de_lenient_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Int(i64),
+        Float(f64),
+    }
+
+    Ok(match Option::<StringOrNumber>::deserialize(deserializer)? {
+        Some(StringOrNumber::String(x)) => x,
+        Some(StringOrNumber::Int(x)) => x.to_string(),
+        Some(StringOrNumber::Float(x)) => x.to_string(),
+        None => String::new(),
+    })
+}
+
+pub fn read_structure_suttas(sc_data_path: &Path) -> Result<Vec<StructureSutta>, Box<dyn Error>> {
+    let p = sc_data_path.join(PathBuf::from("structure/sutta.json"));
+    let s = fs::read_to_string(p)?;
+    let structure_suttas: Vec<StructureSutta> = serde_json::from_str(&s)?;
+    Ok(structure_suttas)
 }
 
 /// sc-data/additional-info/author_edition.json
@@ -994,48 +2203,109 @@ pub struct AuthorEdition {
     pub long_name: String,
 }
 
-/// sc-data/po_text/pli-en/mn/mn001.json
-///
-/// Ignored fields:
-///
-/// - "msgid_plural": "",
-/// - "msgstr_plural": {},
-/// - "obsolete": 0,
-/// - "tcomment": "",
-/// - "occurrences": [],
-/// - "flags": [],
-/// - "previous_msgctxt": null,
-/// - "previous_msgid": null,
-/// - "previous_msgid_plural": null,
-#[derive(Debug, Clone, Deserialize)]
-pub struct PoMessage {
-    pub msgid: String,
-    pub msgstr: String,
-    pub msgctxt: String,
-    pub encoding: String,
-    pub comment: String,
-    pub linenum: i32,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_ids_in_text_order() {
+        let segments: BTreeMap<String, String> = ["mn1:10.1", "mn1:2.1", "mn1:1.2", "mn1:1.10", "mn1:0.1"]
+            .iter()
+            .map(|x| (x.to_string(), "".to_string()))
+            .collect();
+        assert_eq!(sorted_segment_ids(&segments), vec!["mn1:0.1", "mn1:1.2", "mn1:1.10", "mn1:2.1", "mn1:10.1"]);
+    }
 
-/// sc-data/po_text/mn/info.json
-///
-/// Ignored fields:
-///
-/// - "msgid_plural": "",
-/// - "msgstr_plural": {},
-/// - "msgctxt": null,
-/// - "obsolete": 0,
-/// - "encoding": "utf-8",
-/// - "tcomment": "",
-/// - "occurrences": [],
-/// - "flags": [],
-/// - "previous_msgctxt": null,
-/// - "previous_msgid": null,
-/// - "previous_msgid_plural": null,
-/// - "linenum": 17
-#[derive(Deserialize)]
-pub struct PoAuthorInfo {
-    pub msgid: String,
-    pub msgstr: String,
-    pub comment: String,
+    fn pts_pages(markers: &[(&str, Option<i32>, i32, usize)], volpage: &str, division: &str) -> Vec<String> {
+        let segments: Vec<TextSegment> = (1..=5)
+            .map(|n| TextSegment::new(&format!("{}1:1.{}", division, n), "", "", ""))
+            .collect();
+        let markers: Vec<(PtsMarker, usize)> = markers.iter()
+            .map(|(edition, volume, page, start)| (PtsMarker {
+                edition: edition.to_string(),
+                volume: *volume,
+                page: *page,
+            }, *start))
+            .collect();
+
+        pts_pages_from_markers(&markers, &segments, volpage, division).iter()
+            .map(|p| format!("{} {} {} {} {} {}-{} ({}-{})",
+                             p.edition, p.collection, p.volume, p.page, p.sutta_uid,
+                             p.segment_start, p.segment_end, p.start_order, p.end_order))
+            .collect()
+    }
+
+    #[test]
+    fn pts_pages_end_before_the_next_marker() {
+        let markers = [("pts1ed", None, 1, 1), ("pts2ed", Some(1), 5, 2), ("pts1ed", None, 2, 3)];
+        assert_eq!(pts_pages(&markers, "MN i 1", "mn"), vec![
+            "pts1ed mn 1 1 mn1 mn1:1.1-mn1:1.2 (1-2)",
+            "pts2ed mn 1 5 mn1 mn1:1.2-mn1:1.5 (2-5)",
+            "pts1ed mn 1 2 mn1 mn1:1.3-mn1:1.5 (3-5)",
+        ]);
+    }
+
+    #[test]
+    fn pts_pages_in_one_segment() {
+        let markers = [("pts1ed", None, 7, 4), ("pts1ed", None, 8, 4)];
+        assert_eq!(pts_pages(&markers, "MN i 7", "mn"), vec![
+            "pts1ed mn 1 7 mn1 mn1:1.4-mn1:1.4 (4-4)",
+            "pts1ed mn 1 8 mn1 mn1:1.4-mn1:1.5 (4-5)",
+        ]);
+    }
+
+    #[test]
+    fn pts_pages_without_volpage() {
+        // The volume is only known from the marker id.
+        let markers = [("pts", None, 1, 1), ("pts", Some(3), 2, 2)];
+        assert_eq!(pts_pages(&markers, "", "pli-tv-bu-vb-pj"), vec![
+            "pts vin 3 2 pli-tv-bu-vb-pj1 pli-tv-bu-vb-pj1:1.2-pli-tv-bu-vb-pj1:1.5 (2-5)",
+        ]);
+    }
+
+    #[test]
+    fn pts_pages_outside_the_segments() {
+        let markers = [("pts1ed", None, 1, 0), ("pts1ed", None, 2, 6)];
+        assert!(pts_pages(&markers, "MN i 1", "mn").is_empty());
+    }
+
+    fn variant(lemma: &str, reading: &str, editions: &str) -> VariantReading {
+        VariantReading {
+            lemma: lemma.to_string(),
+            reading: reading.to_string(),
+            editions: editions.to_string(),
+        }
+    }
+
+    #[test]
+    fn variant_note_alternatives() {
+        assert_eq!(parse_variant_note("Na hi nūna → nahanūna (bj, s1, s2, km, pts1) | naha nūna (s3)"),
+                   vec![variant("Na hi nūna", "nahanūna", "bj, s1, s2, km, pts1"),
+                        variant("Na hi nūna", "naha nūna", "s3")]);
+    }
+
+    #[test]
+    fn variant_note_several_lemmas() {
+        assert_eq!(parse_variant_note("evaṃ → evam (pts1); bhagavā → bhagavāti (s1) |"),
+                   vec![variant("evaṃ", "evam", "pts1"),
+                        variant("bhagavā", "bhagavāti", "s1")]);
+    }
+
+    #[test]
+    fn variant_note_continues_the_lemma() {
+        assert_eq!(parse_variant_note("evaṃ → evam (pts1); evañ (s1)"),
+                   vec![variant("evaṃ", "evam", "pts1"),
+                        variant("evaṃ", "evañ", "s1")]);
+    }
+
+    #[test]
+    fn variant_note_without_editions() {
+        assert_eq!(parse_variant_note("evaṃ → evam"), vec![variant("evaṃ", "evam", "")]);
+    }
+
+    #[test]
+    fn variant_note_without_lemma() {
+        assert!(parse_variant_note("evam (pts1)").is_empty());
+        assert!(parse_variant_note("").is_empty());
+    }
 }
@@ -0,0 +1,411 @@
+use deunicode::deunicode;
+
+use pali_dict_core::pali;
+
+/// The scripts which Pali is written in, besides the Roman script of the dictionaries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Script {
+    Roman,
+    Sinhala,
+    Devanagari,
+    Thai,
+    Myanmar,
+    Khmer,
+}
+
+impl Script {
+    /// Parse a script name, such as `sinhala`, or its ISO 15924 code, such as `Sinh`.
+    pub fn from_name(name: &str) -> Option<Script> {
+        match name.trim().to_lowercase().as_str() {
+            "roman" | "latin" | "latn" => Some(Script::Roman),
+            "sinhala" | "sinh" => Some(Script::Sinhala),
+            "devanagari" | "deva" => Some(Script::Devanagari),
+            "thai" => Some(Script::Thai),
+            "myanmar" | "burmese" | "mymr" => Some(Script::Myanmar),
+            "khmer" | "khmr" => Some(Script::Khmer),
+            _ => None,
+        }
+    }
+
+    /// The scripts of a list of names, such as `DictMetadata.display_scripts`. Unknown names are
+    /// skipped with a warning.
+    pub fn from_names(names: &[String]) -> Vec<Script> {
+        names.iter()
+            .filter_map(|x| {
+                let s = Script::from_name(x);
+                if s.is_none() {
+                    warn!("Unknown script: {}", x);
+                }
+                s
+            })
+            .collect()
+    }
+
+    fn table(self) -> Option<&'static ScriptTable> {
+        match self {
+            Script::Roman => None,
+            Script::Sinhala => Some(&SINHALA),
+            Script::Devanagari => Some(&DEVANAGARI),
+            Script::Thai => Some(&THAI),
+            Script::Myanmar => Some(&MYANMAR),
+            Script::Khmer => Some(&KHMER),
+        }
+    }
+}
+
+/// The Roman consonants, in the order of `ScriptTable.consonants`. The aspirates are written with
+/// two letters.
+const CONSONANTS: [&str; 32] = [
+    "k", "kh", "g", "gh", "ṅ",
+    "c", "ch", "j", "jh", "ñ",
+    "ṭ", "ṭh", "ḍ", "ḍh", "ṇ",
+    "t", "th", "d", "dh", "n",
+    "p", "ph", "b", "bh", "m",
+    "y", "r", "l", "v", "s", "h", "ḷ",
+];
+
+/// The Roman vowels, in the order of `ScriptTable.vowels` and `ScriptTable.vowel_signs`.
+const VOWELS: [&str; 8] = ["a", "ā", "i", "ī", "u", "ū", "e", "o"];
+
+struct ScriptTable {
+    consonants: [&'static str; 32],
+    /// The vowels at the start of a word or after another vowel.
+    vowels: [&'static str; 8],
+    /// The vowels after a consonant. The first is the inherent `a`.
+    vowel_signs: [&'static str; 8],
+    /// Joins the consonants of a cluster.
+    cluster_virama: &'static str,
+    /// Marks a consonant at the end of a word.
+    final_virama: &'static str,
+    anusvara: &'static str,
+    /// The signs of `e` and `o` are written before the consonant (Thai).
+    preposed_e_o: bool,
+    /// The second consonant of a cluster written as a mark, such as the Myanmar medials.
+    medials: &'static [(&'static str, &'static str)],
+}
+
+const DEVANAGARI: ScriptTable = ScriptTable {
+    consonants: [
+        "क", "ख", "ग", "घ", "ङ",
+        "च", "छ", "ज", "झ", "ञ",
+        "ट", "ठ", "ड", "ढ", "ण",
+        "त", "थ", "द", "ध", "न",
+        "प", "फ", "ब", "भ", "म",
+        "य", "र", "ल", "व", "स", "ह", "ळ",
+    ],
+    vowels: ["अ", "आ", "इ", "ई", "उ", "ऊ", "ए", "ओ"],
+    vowel_signs: ["", "ा", "ि", "ी", "ु", "ू", "े", "ो"],
+    cluster_virama: "्",
+    final_virama: "्",
+    anusvara: "ं",
+    preposed_e_o: false,
+    medials: &[],
+};
+
+const SINHALA: ScriptTable = ScriptTable {
+    consonants: [
+        "ක", "ඛ", "ග", "ඝ", "ඞ",
+        "ච", "ඡ", "ජ", "ඣ", "ඤ",
+        "ට", "ඨ", "ඩ", "ඪ", "ණ",
+        "ත", "ථ", "ද", "ධ", "න",
+        "ප", "ඵ", "බ", "භ", "ම",
+        "ය", "ර", "ල", "ව", "ස", "හ", "ළ",
+    ],
+    vowels: ["අ", "ආ", "ඉ", "ඊ", "උ", "ඌ", "එ", "ඔ"],
+    vowel_signs: ["", "ා", "ි", "ී", "ු", "ූ", "ෙ", "ො"],
+    cluster_virama: "්",
+    final_virama: "්",
+    anusvara: "ං",
+    preposed_e_o: false,
+    medials: &[],
+};
+
+const THAI: ScriptTable = ScriptTable {
+    consonants: [
+        "ก", "ข", "ค", "ฆ", "ง",
+        "จ", "ฉ", "ช", "ฌ", "ญ",
+        "ฏ", "ฐ", "ฑ", "ฒ", "ณ",
+        "ต", "ถ", "ท", "ธ", "น",
+        "ป", "ผ", "พ", "ภ", "ม",
+        "ย", "ร", "ล", "ว", "ส", "ห", "ฬ",
+    ],
+    vowels: ["อ", "อา", "อิ", "อี", "อุ", "อู", "เอ", "โอ"],
+    vowel_signs: ["", "า", "ิ", "ี", "ุ", "ู", "เ", "โ"],
+    cluster_virama: "ฺ",
+    final_virama: "ฺ",
+    anusvara: "ํ",
+    preposed_e_o: true,
+    medials: &[],
+};
+
+const MYANMAR: ScriptTable = ScriptTable {
+    consonants: [
+        "က", "ခ", "ဂ", "ဃ", "င",
+        "စ", "ဆ", "ဇ", "ဈ", "ဉ",
+        "ဋ", "ဌ", "ဍ", "ဎ", "ဏ",
+        "တ", "ထ", "ဒ", "ဓ", "န",
+        "ပ", "ဖ", "ဗ", "ဘ", "မ",
+        "ယ", "ရ", "လ", "ဝ", "သ", "ဟ", "ဠ",
+    ],
+    vowels: ["အ", "အာ", "ဣ", "ဤ", "ဥ", "ဦ", "ဧ", "ဩ"],
+    vowel_signs: ["", "ာ", "ိ", "ီ", "ု", "ူ", "ေ", "ော"],
+    cluster_virama: "္",
+    final_virama: "်",
+    anusvara: "ံ",
+    preposed_e_o: false,
+    medials: &[("y", "ျ"), ("r", "ြ"), ("v", "ွ"), ("h", "ှ")],
+};
+
+const KHMER: ScriptTable = ScriptTable {
+    consonants: [
+        "ក", "ខ", "គ", "ឃ", "ង",
+        "ច", "ឆ", "ជ", "ឈ", "ញ",
+        "ដ", "ឋ", "ឌ", "ឍ", "ណ",
+        "ត", "ថ", "ទ", "ធ", "ន",
+        "ប", "ផ", "ព", "ភ", "ម",
+        "យ", "រ", "ល", "វ", "ស", "ហ", "ឡ",
+    ],
+    vowels: ["អ", "អា", "ឥ", "ឦ", "ឧ", "ឩ", "ឯ", "ឱ"],
+    vowel_signs: ["", "ា", "ិ", "ី", "ុ", "ូ", "េ", "ោ"],
+    cluster_virama: "្",
+    final_virama: "៑",
+    anusvara: "ំ",
+    preposed_e_o: false,
+    medials: &[],
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Consonant(usize),
+    Vowel(usize),
+    Niggahita,
+    Other(char),
+}
+
+/// Write a Pali text in a script. The text is in the Roman script, with `ṃ` or `ṁ` for the
+/// niggahīta. Other characters, such as spaces and punctuation, are kept.
+///
+/// The consonant clusters are written with the virama (or the script's stacking sign) and the
+/// Myanmar medials, the other conjunct forms are left to the fonts.
+pub fn to_script(text: &str, script: Script) -> String {
+    let t = match script.table() {
+        Some(x) => x,
+        None => return text.to_string(),
+    };
+
+    let tokens = tokenize(text);
+    let mut res = String::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i] {
+            Token::Consonant(c) => {
+                // The medials follow the consonant, e.g. Myanmar ky -> ကျ
+                let mut j = i + 1;
+                let mut medials = String::new();
+                while let Some(Token::Consonant(c2)) = tokens.get(j) {
+                    match t.medials.iter().find(|(r, _)| *r == CONSONANTS[*c2]) {
+                        Some((_, m)) => {
+                            medials.push_str(m);
+                            j += 1;
+                        }
+                        None => break,
+                    }
+                }
+
+                match tokens.get(j) {
+                    Some(Token::Vowel(v)) => {
+                        let is_e_o = *v == 6 || *v == 7;
+                        if t.preposed_e_o && is_e_o {
+                            res.push_str(t.vowel_signs[*v]);
+                            res.push_str(t.consonants[c]);
+                            res.push_str(&medials);
+                        } else {
+                            res.push_str(t.consonants[c]);
+                            res.push_str(&medials);
+                            res.push_str(t.vowel_signs[*v]);
+                        }
+                        j += 1;
+                    }
+                    Some(Token::Consonant(_)) => {
+                        res.push_str(t.consonants[c]);
+                        res.push_str(&medials);
+                        res.push_str(t.cluster_virama);
+                    }
+                    _ => {
+                        res.push_str(t.consonants[c]);
+                        res.push_str(&medials);
+                        res.push_str(t.final_virama);
+                    }
+                }
+
+                i = j;
+                continue;
+            }
+            Token::Vowel(v) => res.push_str(t.vowels[v]),
+            Token::Niggahita => res.push_str(t.anusvara),
+            Token::Other(c) => res.push(c),
+        }
+        i += 1;
+    }
+
+    res
+}
+
+/// The Pali words of a text as letters. An `h` after a stop is part of an aspirate, such as `kh`.
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut res: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let s = c.to_string();
+
+        if c == 'ṃ' || c == 'ṁ' {
+            res.push(Token::Niggahita);
+            i += 1;
+            continue;
+        }
+
+        if let Some(n) = VOWELS.iter().position(|x| *x == s) {
+            res.push(Token::Vowel(n));
+            i += 1;
+            continue;
+        }
+
+        if let Some(n) = CONSONANTS.iter().position(|x| *x == s) {
+            let is_stop = "kgcjṭḍtdpb".contains(c);
+            if is_stop && chars.get(i + 1) == Some(&'h') {
+                let aspirate = format!("{}h", c);
+                let m = CONSONANTS.iter().position(|x| *x == aspirate).unwrap_or(n);
+                res.push(Token::Consonant(m));
+                i += 2;
+            } else {
+                res.push(Token::Consonant(n));
+                i += 1;
+            }
+            continue;
+        }
+
+        res.push(Token::Other(c));
+        i += 1;
+    }
+
+    res
+}
+
+/// The conventions of writing Pali in the Roman script, which are added as lookup keys with
+/// `DictMetadata.lookup_conventions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Romanization {
+    /// Niggahīta with a dot below: `saṃgha`
+    Iast,
+    /// Niggahīta with a dot above: `saṁgha`
+    Iso15919,
+    /// Niggahīta as eng: `saŋgha`
+    Eng,
+    /// `saMgha`, `dhammA`
+    HarvardKyoto,
+    /// `sa.mgha`, `dhammaa`
+    Velthuis,
+    /// `samgha`, `dhamma`
+    Ascii,
+}
+
+impl Romanization {
+    pub fn from_name(name: &str) -> Option<Romanization> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "iast" => Some(Romanization::Iast),
+            "iso15919" | "iso_15919" | "iso" => Some(Romanization::Iso15919),
+            "eng" => Some(Romanization::Eng),
+            "harvard_kyoto" | "hk" => Some(Romanization::HarvardKyoto),
+            "velthuis" => Some(Romanization::Velthuis),
+            "ascii" => Some(Romanization::Ascii),
+            _ => None,
+        }
+    }
+
+    /// The conventions of a list of names, such as `DictMetadata.lookup_conventions`. Unknown names
+    /// are skipped with a warning.
+    pub fn from_names(names: &[String]) -> Vec<Romanization> {
+        names.iter()
+            .filter_map(|x| {
+                let r = Romanization::from_name(x);
+                if r.is_none() {
+                    warn!("Unknown lookup convention: {}", x);
+                }
+                r
+            })
+            .collect()
+    }
+}
+
+/// Write a romanized Pali text in another convention. The input may use any of `ṃ`, `ṁ` or `ŋ`
+/// for the niggahīta.
+pub fn to_romanization(text: &str, romanization: Romanization) -> String {
+    let iast = text.replace(&['ṁ', 'ŋ'][..], "ṃ");
+
+    match romanization {
+        Romanization::Iast => iast,
+        Romanization::Iso15919 => iast.replace('ṃ', "ṁ"),
+        Romanization::Eng => iast.replace('ṃ', "ŋ"),
+        Romanization::Velthuis => pali::to_velthuis(&iast),
+        Romanization::Ascii => deunicode(&iast),
+        Romanization::HarvardKyoto => {
+            iast.to_lowercase()
+                .chars()
+                .map(|c| match c {
+                    'ā' => 'A',
+                    'ī' => 'I',
+                    'ū' => 'U',
+                    'ṅ' => 'G',
+                    'ñ' => 'J',
+                    'ṭ' => 'T',
+                    'ḍ' => 'D',
+                    'ṇ' => 'N',
+                    'ṃ' => 'M',
+                    'ḷ' => 'L',
+                    _ => c,
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_word_per_script() {
+        assert_eq!(to_script("dhamma", Script::Sinhala), "ධම්ම");
+        assert_eq!(to_script("dhamma", Script::Devanagari), "धम्म");
+        assert_eq!(to_script("buddho", Script::Thai), "พุทฺโธ");
+        assert_eq!(to_script("buddho", Script::Myanmar), "ဗုဒ္ဓော");
+        assert_eq!(to_script("bhikkhūnaṃ", Script::Khmer), "ភិក្ខូនំ");
+        assert_eq!(to_script("saṃgha", Script::Roman), "saṃgha");
+    }
+
+    #[test]
+    fn niggahita_in_the_scripts() {
+        assert_eq!(to_script("saṃgha", Script::Sinhala), "සංඝ");
+        assert_eq!(to_script("saṃgha", Script::Devanagari), "संघ");
+        assert_eq!(to_script("saṃgha", Script::Thai), "สํฆ");
+    }
+
+    #[test]
+    fn romanizations() {
+        assert_eq!(to_romanization("bhikkhūnaṃ", Romanization::Iso15919), "bhikkhūnaṁ");
+        assert_eq!(to_romanization("bhikkhūnaṃ", Romanization::Eng), "bhikkhūnaŋ");
+        assert_eq!(to_romanization("bhikkhūnaṃ", Romanization::HarvardKyoto), "bhikkhUnaM");
+        assert_eq!(to_romanization("bhikkhūnaṃ", Romanization::Ascii), "bhikkhunam");
+    }
+
+    #[test]
+    fn script_names() {
+        assert_eq!(Script::from_name("Sinh"), Some(Script::Sinhala));
+        assert_eq!(Script::from_name("thai"), Some(Script::Thai));
+        assert_eq!(Script::from_name("klingon"), None);
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use regex::Regex;
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::concordance::PtsRef;
+use crate::db_schema;
+use crate::sc_data::StructureSutta;
+
+/// The link target of a resolved reference, `{uid}` is replaced with the sutta uid.
+pub const DEFAULT_SUTTA_LINK_TEMPLATE: &str = "ssp://suttas/{uid}";
+
+/// The ways a dictionary writes its references to the suttas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CitationStyle {
+    /// PTS volume and page: `DN i 1`, `D ii.45`, `M.I.23`, `S iv 12`, `A ii 72`
+    VolumePage,
+    /// Sutta number: `DN 1`, `M 23`, `A 4.67`, `S. XII, 15`, `A. III, 99`
+    SuttaNumber,
+    /// Nyanatiloka's lists of sutta numbers: `D. 34`, `M. 4, 6, 77`, `S. XII, 15`, `A. III, 99, 101`
+    Nyanatiloka,
+}
+
+impl CitationStyle {
+    /// `volume_page`, `sutta_number` or `nyanatiloka`
+    pub fn from_name(name: &str) -> Option<CitationStyle> {
+        match name.trim().to_lowercase().as_str() {
+            "volume_page" => Some(CitationStyle::VolumePage),
+            "sutta_number" => Some(CitationStyle::SuttaNumber),
+            "nyanatiloka" => Some(CitationStyle::Nyanatiloka),
+            _ => None,
+        }
+    }
+}
+
+/// The citation styles used by a dictionary, from the `citation_styles` of its metadata. When these
+/// are not given, the styles are chosen by the label. The roman numerals are volumes in the PTS
+/// style references (`S IV 12`), but they are the book number in Nyanatiloka's (`S. XII, 15`).
+pub fn citation_styles(dict_label: &str, names: &[String]) -> Vec<CitationStyle> {
+    let mut styles: Vec<CitationStyle> = Vec::new();
+    for name in names.iter() {
+        match CitationStyle::from_name(name) {
+            Some(x) => styles.push(x),
+            None => warn!("Unknown citation style: {}", name),
+        }
+    }
+    if !styles.is_empty() {
+        return styles;
+    }
+
+    match dict_label.to_lowercase().as_str() {
+        "nyana" | "nyanatiloka" => vec![CitationStyle::Nyanatiloka, CitationStyle::SuttaNumber],
+        _ => vec![CitationStyle::VolumePage, CitationStyle::SuttaNumber],
+    }
+}
+
+/// A sutta reference found in a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuttaRef {
+    /// The reference as written: `M I 23`
+    pub ref_text: String,
+    /// The reference in SuttaCentral's notation: `MN i 23`, `AN 4.67`
+    pub normalized_ref: String,
+    /// dn, mn, sn, an
+    pub collection: String,
+    /// The PTS volume of a volume and page reference.
+    pub volume: Option<usize>,
+    /// The PTS page of a volume and page reference.
+    pub page: Option<usize>,
+    /// The sutta uid, such as `an4.67`. Volume and page references are resolved with a
+    /// `VolpageIndex`.
+    pub sutta_uid: Option<String>,
+    /// Byte range in the text.
+    pub range: Range<usize>,
+}
+
+/// Find the sutta references in a text. References in the text of existing Markdown links are
+/// skipped, so that the text can be processed again.
+pub fn find_sutta_refs(text: &str, styles: &[CitationStyle]) -> Vec<SuttaRef> {
+    lazy_static! {
+        static ref RE_LINK: Regex = Regex::new(r"\[[^\]]*\]\([^\)]*\)").unwrap();
+
+        // DN i 1; D ii.45; M.I.23; S iv, 12
+        static ref RE_VOLUME_PAGE: Regex = Regex::new(
+            r"\b(?P<col>DN|MN|SN|AN|D|M|S|A)(?:\. ?| )(?P<vol>(?i:vi|v|iv|iii|ii|i))(?:\. ?|, ?| )(?P<page>[0-9]+)\b").unwrap();
+
+        // S. XII, 15; A. III, 99
+        static ref RE_ROMAN_BOOK: Regex = Regex::new(
+            r"\b(?P<col>SN|AN|S|A)\. ?(?P<book>[IVXL]+), ?(?P<num>[0-9]+)\b").unwrap();
+
+        // DN 1; M. 23; A 4.67; SN 12.15
+        static ref RE_NUMBER: Regex = Regex::new(
+            r"\b(?P<col>DN|MN|SN|AN|D|M|S|A)(?:\. ?| )(?P<num>[0-9]+(?:\.[0-9]+)?)\b").unwrap();
+
+        // D. 34; M. 4, 6, 77; S. XII, 15; A. III, 99, 101
+        static ref RE_NYANATILOKA: Regex = Regex::new(
+            r"\b(?:(?P<col>[DM])\. ?|(?P<book_col>[SA])\. ?(?P<book>[IVXL]+), ?)(?P<nums>[0-9]+(?:, ?[0-9]+)*)\b").unwrap();
+
+        static ref RE_DIGITS: Regex = Regex::new(r"[0-9]+").unwrap();
+    }
+
+    let mut skip: Vec<Range<usize>> = RE_LINK.find_iter(text).map(|m| m.range()).collect();
+    let mut refs: Vec<SuttaRef> = Vec::new();
+
+    let mut add_ref = |r: SuttaRef, skip: &mut Vec<Range<usize>>| {
+        if skip.iter().any(|s| r.range.start < s.end && s.start < r.range.end) {
+            return;
+        }
+        skip.push(r.range.clone());
+        refs.push(r);
+    };
+
+    for style in styles.iter() {
+        match style {
+            CitationStyle::VolumePage => {
+                for caps in RE_VOLUME_PAGE.captures_iter(text) {
+                    let col = collection_uid(&caps["col"]);
+                    let vol = roman_to_int(&caps["vol"].to_uppercase()).unwrap_or(0);
+                    let page: usize = caps["page"].parse().unwrap_or(0);
+                    add_ref(SuttaRef {
+                        ref_text: caps[0].to_string(),
+                        normalized_ref: format!("{} {} {}", col.to_uppercase(), caps["vol"].to_lowercase(), page),
+                        collection: col.to_string(),
+                        volume: Some(vol),
+                        page: Some(page),
+                        sutta_uid: None,
+                        range: caps.get(0).unwrap().range(),
+                    }, &mut skip);
+                }
+            }
+
+            CitationStyle::SuttaNumber => {
+                for caps in RE_ROMAN_BOOK.captures_iter(text) {
+                    let col = collection_uid(&caps["col"]);
+                    let book = match roman_to_int(&caps["book"]) {
+                        Some(n) => n,
+                        None => continue,
+                    };
+                    let num = format!("{}.{}", book, &caps["num"]);
+                    add_ref(SuttaRef {
+                        ref_text: caps[0].to_string(),
+                        normalized_ref: format!("{} {}", col.to_uppercase(), num),
+                        collection: col.to_string(),
+                        volume: None,
+                        page: None,
+                        sutta_uid: Some(format!("{}{}", col, num)),
+                        range: caps.get(0).unwrap().range(),
+                    }, &mut skip);
+                }
+
+                for caps in RE_NUMBER.captures_iter(text) {
+                    let col = collection_uid(&caps["col"]);
+                    let num = &caps["num"];
+                    // SN and AN suttas are numbered within the saṃyutta or nipāta.
+                    if (col == "sn" || col == "an") && !num.contains('.') {
+                        continue;
+                    }
+                    add_ref(SuttaRef {
+                        ref_text: caps[0].to_string(),
+                        normalized_ref: format!("{} {}", col.to_uppercase(), num),
+                        collection: col.to_string(),
+                        volume: None,
+                        page: None,
+                        sutta_uid: Some(format!("{}{}", col, num)),
+                        range: caps.get(0).unwrap().range(),
+                    }, &mut skip);
+                }
+            }
+
+            CitationStyle::Nyanatiloka => {
+                for caps in RE_NYANATILOKA.captures_iter(text) {
+                    let (col, prefix) = match (caps.name("col"), caps.name("book")) {
+                        (Some(x), _) => (collection_uid(x.as_str()), "".to_string()),
+                        (None, Some(x)) => match roman_to_int(x.as_str()) {
+                            Some(n) => (collection_uid(&caps["book_col"]), format!("{}.", n)),
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+
+                    // One reference for each number. The first one includes the collection, so
+                    // that `M. 4, 6` gives `[MN 4](...), [MN 6](...)`.
+                    let nums = caps.name("nums").unwrap();
+                    for (i, n) in RE_DIGITS.find_iter(nums.as_str()).enumerate() {
+                        let start = if i == 0 { caps.get(0).unwrap().start() } else { nums.start() + n.start() };
+                        let range = start..(nums.start() + n.end());
+                        let num = format!("{}{}", prefix, n.as_str());
+                        add_ref(SuttaRef {
+                            ref_text: text[range.clone()].to_string(),
+                            normalized_ref: format!("{} {}", col.to_uppercase(), num),
+                            collection: col.to_string(),
+                            volume: None,
+                            page: None,
+                            sutta_uid: Some(format!("{}{}", col, num)),
+                            range,
+                        }, &mut skip);
+                    }
+                }
+            }
+        }
+    }
+
+    refs.sort_by_key(|r| r.range.start);
+    refs
+}
+
+/// Replace the sutta references with Markdown links. The link text is the normalized reference.
+/// Unresolved references are left as they are written.
+pub fn sutta_refs_to_links(
+    text: &str,
+    styles: &[CitationStyle],
+    index: &VolpageIndex,
+    link_template: &str,
+) -> String {
+    let mut res = String::new();
+    let mut pos = 0;
+
+    for r in find_sutta_refs(text, styles).iter() {
+        res.push_str(&text[pos..r.range.start]);
+        match index.resolve(r) {
+            Some(uid) => res.push_str(&format!("[{}]({})", r.normalized_ref, link_template.replace("{uid}", &uid))),
+            None => res.push_str(&r.ref_text),
+        }
+        pos = r.range.end;
+    }
+    res.push_str(&text[pos..]);
+
+    res
+}
+
+/// The PTS volume and starting page of the suttas, to resolve volume and page references, and the
+/// uids of the suttas, to check the sutta number references.
+#[derive(Default)]
+pub struct VolpageIndex {
+    /// (collection, volume) -> first page -> sutta uid
+    pages: BTreeMap<(String, usize), BTreeMap<usize, String>>,
+    uids: BTreeSet<String>,
+}
+
+impl VolpageIndex {
+    pub fn new() -> Self {
+        VolpageIndex::default()
+    }
+
+    pub fn from_structure_suttas(suttas: &[StructureSutta]) -> Self {
+        let mut index = VolpageIndex::new();
+        for s in suttas.iter() {
+            index.add(&s.uid, &s.volpage);
+        }
+        index
+    }
+
+    /// Read the `sutta_index` table. The index is empty if the table is missing.
+    pub fn from_db(conn: &SqliteConnection) -> Self {
+        use db_schema::sutta_index::dsl::*;
+
+        let mut index = VolpageIndex::new();
+
+        let items: Vec<(String, String)> = match sutta_index.select((uid, volpage)).load(conn) {
+            Ok(x) => x,
+            Err(_) => return index,
+        };
+
+        for (sutta_uid, sutta_volpage) in items.iter() {
+            index.add(sutta_uid, sutta_volpage);
+        }
+        index
+    }
+
+    /// Add a sutta with its `volpage`, such as `DN i 1`. Ranges and page notes after the first
+    /// page are ignored.
+    pub fn add(&mut self, sutta_uid: &str, volpage: &str) {
+        self.uids.insert(sutta_uid.to_string());
+        if let Some(r) = PtsRef::parse(volpage) {
+            self.pages
+                .entry((r.collection, r.volume as usize))
+                .or_default()
+                .entry(r.page as usize)
+                .or_insert_with(|| sutta_uid.to_string());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uids.is_empty()
+    }
+
+    /// The sutta uid of a reference. A page reference resolves to the sutta which starts on or
+    /// before that page. A sutta number reference resolves when the index has its uid, so loose
+    /// matches in the text, such as `A 9.99`, are not linked to suttas which don't exist.
+    pub fn resolve(&self, r: &SuttaRef) -> Option<String> {
+        if let Some(x) = &r.sutta_uid {
+            return self.uids.get(x).cloned();
+        }
+        let (vol, page) = (r.volume?, r.page?);
+        let pages = self.pages.get(&(r.collection.clone(), vol))?;
+        pages.range(..=page).next_back().map(|(_, x)| x.clone())
+    }
+}
+
+fn collection_uid(col: &str) -> &'static str {
+    match col {
+        "D" | "DN" => "dn",
+        "M" | "MN" => "mn",
+        "S" | "SN" => "sn",
+        _ => "an",
+    }
+}
+
+pub fn roman_to_int(roman: &str) -> Option<usize> {
+    let mut total = 0;
+    let mut prev = 0;
+    for c in roman.chars().rev() {
+        let n = match c {
+            'I' => 1,
+            'V' => 5,
+            'X' => 10,
+            'L' => 50,
+            _ => return None,
+        };
+        if n < prev {
+            total -= n;
+        } else {
+            total += n;
+            prev = n;
+        }
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOTH: [CitationStyle; 2] = [CitationStyle::VolumePage, CitationStyle::SuttaNumber];
+
+    fn refs(text: &str) -> Vec<(String, String)> {
+        find_sutta_refs(text, &BOTH)
+            .into_iter()
+            .map(|r| (r.ref_text, r.normalized_ref))
+            .collect()
+    }
+
+    fn index() -> VolpageIndex {
+        let mut index = VolpageIndex::new();
+        index.add("mn1", "MN i 1");
+        index.add("mn2", "MN i 6");
+        index.add("dn34", "DN iii 272");
+        index.add("mn4", "MN i 16");
+        index.add("mn6", "MN i 33");
+        index.add("an3.99", "AN i 253");
+        index
+    }
+
+    #[test]
+    fn volume_page_refs() {
+        assert_eq!(refs("See M I 23 and D.ii.45."), vec![
+            ("M I 23".to_string(), "MN i 23".to_string()),
+            ("D.ii.45".to_string(), "DN ii 45".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn sutta_number_refs() {
+        assert_eq!(refs("DN 1; A 4.67; S. XII, 15"), vec![
+            ("DN 1".to_string(), "DN 1".to_string()),
+            ("A 4.67".to_string(), "AN 4.67".to_string()),
+            ("S. XII, 15".to_string(), "SN 12.15".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn no_false_positives() {
+        assert!(refs("It has 12 chapters and 3 parts.").is_empty());
+        assert!(refs("See vol. 2 p. 3 in the introduction.").is_empty());
+        // AN suttas are numbered within the nipāta.
+        assert!(refs("A 12").is_empty());
+    }
+
+    #[test]
+    fn refs_in_links_are_skipped() {
+        assert!(refs("[MN i 23](ssp://suttas/mn3)").is_empty());
+    }
+
+    #[test]
+    fn resolved_refs_are_linked() {
+        let text = sutta_refs_to_links("See M I 7.", &BOTH, &index(), DEFAULT_SUTTA_LINK_TEMPLATE);
+        assert_eq!(text, "See [MN i 7](ssp://suttas/mn2).");
+    }
+
+    #[test]
+    fn unknown_uids_are_unchanged() {
+        let text = "See DN 3, M 4 and A 9.99.";
+        assert_eq!(sutta_refs_to_links(text, &BOTH, &index(), DEFAULT_SUTTA_LINK_TEMPLATE),
+                   "See DN 3, [MN 4](ssp://suttas/mn4) and A 9.99.");
+        assert_eq!(sutta_refs_to_links(text, &BOTH, &VolpageIndex::new(), DEFAULT_SUTTA_LINK_TEMPLATE), text);
+    }
+
+    #[test]
+    fn unresolved_refs_are_unchanged() {
+        let text = "See M III 5 and D.ii.45.";
+        assert_eq!(sutta_refs_to_links(text, &BOTH, &index(), DEFAULT_SUTTA_LINK_TEMPLATE), text);
+    }
+
+    #[test]
+    fn nyanatiloka_lists() {
+        let styles = citation_styles("Nyana", &[]);
+        let text = sutta_refs_to_links("D. 34; M. 4, 6; A. III, 99, 101", &styles, &index(), DEFAULT_SUTTA_LINK_TEMPLATE);
+        assert_eq!(text, "[DN 34](ssp://suttas/dn34); [MN 4](ssp://suttas/mn4), [MN 6](ssp://suttas/mn6); \
+                          [AN 3.99](ssp://suttas/an3.99), 101");
+    }
+
+    #[test]
+    fn citation_styles_from_metadata() {
+        assert_eq!(citation_styles("NCPED", &[]), BOTH.to_vec());
+        assert_eq!(citation_styles("Nyana", &[]), vec![CitationStyle::Nyanatiloka, CitationStyle::SuttaNumber]);
+        assert_eq!(citation_styles("NCPED", &["sutta_number".to_string()]), vec![CitationStyle::SuttaNumber]);
+    }
+}
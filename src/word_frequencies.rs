@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+
+use deunicode::deunicode;
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::db_models::{DbWordFrequency, NewWordFrequency};
+use crate::db_schema;
+use crate::error::ToolError;
+use crate::helpers;
+use crate::sc_data;
+
+/// The corpus divisions of the counts, in the order of `WordFrequency`.
+pub const DIVISIONS: [&str; 6] = ["dn", "mn", "sn", "an", "kn", "vinaya"];
+
+/// The occurrences of a headword in the Pali root texts. It is added to the entries of the render
+/// JSON and the StarDict output when a database with the counts is given.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct WordFrequency {
+    /// All occurrences, including the texts outside of the divisions, such as the Abhidhamma.
+    pub total: i32,
+    /// The number of suttas with at least one occurrence.
+    pub texts_count: i32,
+    pub dn: i32,
+    pub mn: i32,
+    pub sn: i32,
+    pub an: i32,
+    pub kn: i32,
+    pub vinaya: i32,
+}
+
+impl From<&DbWordFrequency> for WordFrequency {
+    fn from(x: &DbWordFrequency) -> Self {
+        WordFrequency {
+            total: x.total,
+            texts_count: x.texts_count,
+            dn: x.dn,
+            mn: x.mn,
+            sn: x.sn,
+            an: x.an,
+            kn: x.kn,
+            vinaya: x.vinaya,
+        }
+    }
+}
+
+impl WordFrequency {
+    /// `1234 in 56 texts (DN 12, MN 30, SN 400, AN 300, KN 200, Vinaya 292)`
+    pub fn summary(&self) -> String {
+        if self.total == 0 {
+            return "not found in the root texts".to_string();
+        }
+
+        let parts: Vec<String> = [
+            ("DN", self.dn),
+            ("MN", self.mn),
+            ("SN", self.sn),
+            ("AN", self.an),
+            ("KN", self.kn),
+            ("Vinaya", self.vinaya),
+        ].iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(div, n)| format!("{} {}", div, n))
+            .collect();
+
+        let texts = if self.texts_count == 1 { "text" } else { "texts" };
+
+        if parts.is_empty() {
+            format!("{} in {} {}", self.total, self.texts_count, texts)
+        } else {
+            format!("{} in {} {} ({})", self.total, self.texts_count, texts, parts.join(", "))
+        }
+    }
+}
+
+/// The occurrences of a word form in the root texts.
+#[derive(Default)]
+struct FormCount {
+    total: i32,
+    /// Counts in the order of `DIVISIONS`.
+    divisions: [i32; 6],
+    /// Indexes of the texts with the form, in increasing order.
+    texts: Vec<usize>,
+}
+
+/// Count the occurrences of the dictionary words in the Pali root texts of the same database, and
+/// write them to the `word_frequencies` table. Run it after the texts and the dictionaries are
+/// imported. The counts of the words are replaced when the command is run again.
+///
+/// A word is counted with its headword, `word_nom_sg` and `inflections`. Inflections which are only
+/// the ASCII version of another form (`dhamma` for `dhammā`) are not counted separately. When an
+/// entry has no diacritics at all, its forms are matched to the deunicoded words of the texts.
+///
+/// Every word gets a row, with zero counts when it doesn't occur in the texts.
+pub fn process_word_frequencies_to_sqlite(
+    sqlite_db_path: &Path,
+    dict_label: &Option<String>,
+    ) -> Result<(), Box<dyn Error>>
+{
+    info!("process_word_frequencies_to_sqlite()");
+
+    let conn = SqliteConnection::establish(sqlite_db_path.to_str().unwrap())
+        .expect("Error connecting to database.");
+
+    // === Root texts ===
+
+    let texts: Vec<(String, String)> = {
+        use db_schema::root_texts::dsl::*;
+
+        root_texts
+            .filter(content_language.eq("pli"))
+            .order(id.asc())
+            .select((uid, content_plain))
+            .load(&conn)?
+    };
+
+    if texts.is_empty() {
+        let msg = "🔥 There are no Pali root texts in the database.".to_string();
+        return Err(Box::new(ToolError::Exit(msg)));
+    }
+
+    let (counts, texts_count) = count_word_forms(&texts);
+    let ascii_forms = deunicoded_forms(&counts);
+
+    info!("Texts: {}, word forms: {}", texts_count, counts.len());
+
+    // === Dictionary words ===
+
+    let words: Vec<(i32, String, String, String)> = {
+        use db_schema::{dict_words, dictionaries};
+
+        let mut q = dict_words::table
+            .select((dict_words::id, dict_words::word, dict_words::word_nom_sg, dict_words::inflections))
+            .order(dict_words::id.asc())
+            .into_boxed();
+
+        if let Some(d_label) = dict_label {
+            let d_id: i32 = match dictionaries::table
+                .filter(dictionaries::label.eq(d_label))
+                .select(dictionaries::id)
+                .first(&conn)
+            {
+                Ok(x) => x,
+                Err(_) => {
+                    let msg = format!("🔥 Dictionary not found: {}", d_label);
+                    return Err(Box::new(ToolError::Exit(msg)));
+                }
+            };
+            q = q.filter(dict_words::dictionary_id.eq(d_id));
+        }
+
+        q.load(&conn)?
+    };
+
+    let mut unattested_count = 0;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        use db_schema::word_frequencies;
+
+        for (word_id, word, word_nom_sg, inflections) in words.iter() {
+            diesel::delete(word_frequencies::table.filter(word_frequencies::dict_word_id.eq(word_id)))
+                .execute(&conn)?;
+
+            let freq = word_frequency(word, word_nom_sg, inflections, &counts, &ascii_forms);
+
+            if freq.total == 0 {
+                unattested_count += 1;
+            }
+
+            diesel::insert_into(word_frequencies::table)
+                .values(&NewWordFrequency {
+                    dict_word_id: word_id,
+                    word,
+                    total: &freq.total,
+                    texts_count: &freq.texts_count,
+                    dn: &freq.dn,
+                    mn: &freq.mn,
+                    sn: &freq.sn,
+                    an: &freq.an,
+                    kn: &freq.kn,
+                    vinaya: &freq.vinaya,
+                })
+                .execute(&conn)?;
+        }
+
+        Ok(())
+    }).expect("Error inserting the word frequencies.");
+
+    info!("Words: {}, not found in the texts: {}", words.len(), unattested_count);
+
+    Ok(())
+}
+
+/// Read the counts of a dictionary's words from the `word_frequencies` table, with the word as the
+/// map key. When the dictionary label is not in the database, the counts of all words are read.
+/// The map is empty if the table is missing.
+pub fn load_word_frequencies(sqlite_db_path: &Path, dict_label: &str) -> BTreeMap<String, WordFrequency> {
+    let mut res: BTreeMap<String, WordFrequency> = BTreeMap::new();
+
+    let conn = match SqliteConnection::establish(sqlite_db_path.to_str().unwrap()) {
+        Ok(x) => x,
+        Err(_) => return res,
+    };
+
+    let d_id: Option<i32> = {
+        use db_schema::dictionaries::dsl::*;
+        dictionaries
+            .filter(label.eq(dict_label))
+            .select(id)
+            .first(&conn)
+            .ok()
+    };
+
+    let word_ids: Option<BTreeSet<i32>> = d_id.map(|d_id| {
+        use db_schema::dict_words::dsl::*;
+        dict_words
+            .filter(dictionary_id.eq(d_id))
+            .select(id)
+            .load(&conn)
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    });
+
+    let items: Vec<DbWordFrequency> = {
+        use db_schema::word_frequencies::dsl::*;
+        word_frequencies
+            .order(id.asc())
+            .load(&conn)
+            .unwrap_or_default()
+    };
+
+    for i in items.iter() {
+        if let Some(ref ids) = word_ids {
+            if !ids.contains(&i.dict_word_id) {
+                continue;
+            }
+        }
+        res.entry(i.word.clone()).or_insert_with(|| WordFrequency::from(i));
+    }
+
+    res
+}
+
+/// Count the word forms of the root texts, `(uid, content)`. Several editions of a sutta are
+/// counted once. Returns the counts and the number of counted texts.
+fn count_word_forms(texts: &[(String, String)]) -> (BTreeMap<String, FormCount>, usize) {
+    // word form -> counts
+    let mut counts: BTreeMap<String, FormCount> = BTreeMap::new();
+    let mut seen_suttas: BTreeSet<String> = BTreeSet::new();
+    let mut texts_count = 0;
+
+    for (n, (text_uid, content)) in texts.iter().enumerate() {
+        if !seen_suttas.insert(sc_data::text_uid_to_sutta_uid(text_uid)) {
+            continue;
+        }
+        texts_count += 1;
+
+        let div_idx = sc_data::text_uid_division(text_uid)
+            .and_then(|d| DIVISIONS.iter().position(|x| *x == d));
+
+        for token in helpers::lowercase_words(content).into_iter() {
+            let c = counts.entry(token).or_default();
+            c.total += 1;
+            if let Some(i) = div_idx {
+                c.divisions[i] += 1;
+            }
+            if c.texts.last() != Some(&n) {
+                c.texts.push(n);
+            }
+        }
+    }
+
+    (counts, texts_count)
+}
+
+/// deunicoded form -> word forms
+fn deunicoded_forms(counts: &BTreeMap<String, FormCount>) -> BTreeMap<String, Vec<String>> {
+    let mut res: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for form in counts.keys() {
+        res.entry(deunicode(form)).or_default().push(form.clone());
+    }
+    res
+}
+
+/// The counts of a dictionary word, the sum of the counts of its forms. A text is counted once,
+/// even when it has more than one form of the word.
+fn word_frequency(
+    word: &str,
+    word_nom_sg: &str,
+    inflections: &str,
+    counts: &BTreeMap<String, FormCount>,
+    ascii_forms: &BTreeMap<String, Vec<String>>,
+) -> WordFrequency {
+    let (forms, match_deunicoded) = word_forms(word, word_nom_sg, inflections);
+
+    let mut matched: BTreeSet<&str> = BTreeSet::new();
+    for form in forms.iter() {
+        if match_deunicoded {
+            if let Some(xs) = ascii_forms.get(form) {
+                matched.extend(xs.iter().map(|x| x.as_str()));
+            }
+        } else if let Some((k, _)) = counts.get_key_value(form) {
+            matched.insert(k.as_str());
+        }
+    }
+
+    let mut freq = WordFrequency::default();
+    let mut texts: BTreeSet<usize> = BTreeSet::new();
+
+    for form in matched.iter() {
+        let c = &counts[*form];
+        freq.total += c.total;
+        freq.dn += c.divisions[0];
+        freq.mn += c.divisions[1];
+        freq.sn += c.divisions[2];
+        freq.an += c.divisions[3];
+        freq.kn += c.divisions[4];
+        freq.vinaya += c.divisions[5];
+        texts.extend(c.texts.iter());
+    }
+    freq.texts_count = texts.len() as i32;
+
+    freq
+}
+
+/// The lowercase forms of a word. ASCII inflections of another form are left out. When the entry
+/// has no diacritics at all, the forms are matched to the deunicoded words of the texts, this is
+/// the returned `bool`.
+fn word_forms(word: &str, word_nom_sg: &str, inflections: &str) -> (BTreeSet<String>, bool) {
+    let headword = word.to_lowercase();
+    let nom_sg = word_nom_sg.to_lowercase();
+
+    let mut forms: BTreeSet<String> = BTreeSet::new();
+    forms.insert(headword.clone());
+    forms.insert(nom_sg.clone());
+    for i in inflections.split(',') {
+        forms.insert(i.trim().to_lowercase());
+    }
+    forms.retain(|x| !x.is_empty() && !x.contains(' '));
+
+    if forms.iter().all(|x| x.is_ascii()) {
+        return (forms, true);
+    }
+
+    let aliases: BTreeSet<String> = forms.iter()
+        .filter(|x| !x.is_ascii())
+        .map(|x| deunicode(x))
+        .collect();
+
+    forms.retain(|x| !x.is_ascii() || *x == headword || *x == nom_sg || !aliases.contains(x));
+
+    (forms, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(xs: &[&str]) -> BTreeSet<String> {
+        xs.iter().map(|x| x.to_string()).collect()
+    }
+
+    fn texts() -> Vec<(String, String)> {
+        [
+            ("dn/1/pli/ms", "Dhammo dhammaṃ."),
+            ("dn/1/pli/vri", "Dhammo."),
+            ("mn/1/pli/ms", "Dhammā ca."),
+            ("dhp/1-20/pli/ms", "Dhammo."),
+            ("pli-tv-bu-vb/pj1/pli/ms", "Dhammaṃ, dhammaṃ."),
+            ("ds/1.1/pli/ms", "Dhammo."),
+        ].iter()
+            .map(|(uid, content)| (uid.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn forms_of_a_word() {
+        assert_eq!(word_forms("Dhamma", "dhammo", "dhammā, dhamma, dhammaṃ, dhammassa ca"),
+                   (forms(&["dhamma", "dhammo", "dhammā", "dhammaṃ"]), false));
+    }
+
+    #[test]
+    fn ascii_inflections_are_left_out() {
+        assert_eq!(word_forms("saccā", "", "sacca, saccaṃ"),
+                   (forms(&["saccā", "saccaṃ"]), false));
+    }
+
+    #[test]
+    fn ascii_entry_matches_deunicoded() {
+        assert_eq!(word_forms("dhamma", "", "dhammo"),
+                   (forms(&["dhamma", "dhammo"]), true));
+    }
+
+    #[test]
+    fn counts_by_division() {
+        let (counts, texts_count) = count_word_forms(&texts());
+        // The second edition of dn1 is not counted.
+        assert_eq!(texts_count, 5);
+        assert_eq!(counts["dhammo"].total, 3);
+        assert_eq!(counts["dhammo"].divisions, [1, 0, 0, 0, 1, 0]);
+        assert_eq!(counts["dhammaṃ"].divisions, [1, 0, 0, 0, 0, 2]);
+        assert_eq!(counts["dhammaṃ"].texts, vec![0, 4]);
+
+        let ascii_forms = deunicoded_forms(&counts);
+        let freq = word_frequency("dhamma", "dhammo", "dhammaṃ, dhammā", &counts, &ascii_forms);
+        assert_eq!(freq.total, 7);
+        assert_eq!(freq.texts_count, 5);
+        assert_eq!((freq.dn, freq.mn, freq.sn, freq.an, freq.kn, freq.vinaya), (2, 1, 0, 0, 1, 2));
+        assert_eq!(freq.summary(), "7 in 5 texts (DN 2, MN 1, KN 1, Vinaya 2)");
+    }
+
+    #[test]
+    fn ascii_entry_counts_deunicoded_forms() {
+        let (counts, _) = count_word_forms(&texts());
+        let ascii_forms = deunicoded_forms(&counts);
+        let freq = word_frequency("dhamma", "dhammo", "", &counts, &ascii_forms);
+        // dhammo and dhammā, but not dhammaṃ
+        assert_eq!(freq.total, 4);
+        assert_eq!(freq.texts_count, 4);
+        assert_eq!((freq.dn, freq.mn, freq.kn, freq.vinaya), (1, 1, 1, 0));
+    }
+}